@@ -3,11 +3,81 @@
 in vec4 v_world_position;
 in vec4 v_color;
 in vec4 v_normal;
+in vec2 v_tex_coords;
 out vec4 f_color;
-uniform vec3 u_light_position;
+uniform vec3 u_light_position;
+uniform vec3 u_camera_position;
+uniform float u_ambient;
+uniform float u_diffuse;
+uniform float u_specular;
+uniform float u_shininess;
+uniform bool u_lighting;
+uniform bool u_double_sided;
+// Lights the colors in linear space, taking them to be sRGB. The window doesn't ask for an sRGB
+// framebuffer, so that this can be switched while running, and the output is encoded here instead.
+uniform bool u_gamma_correct;
+uniform vec4 u_tint;
+// Shows each fragment's distance between the clip planes in gray, from black at `u_near` to
+// white at `u_far`, instead of its color.
+uniform bool u_show_depth;
+uniform float u_near;
+uniform float u_far;
+uniform bool u_orthographic;
+// Fills the model with `u_tint` alone, for its outline.
+uniform bool u_flat_color;
+uniform bool u_textured;
+uniform sampler2D u_texture;
 
 void main() {
+    if (u_show_depth) {
+        // The depth buffer holds the perspective depth, which is squeezed up to one not far past
+        // the near plane. Undoing the projection gives the distance in view space.
+        float z = gl_FragCoord.z;
+        float distance = u_orthographic
+            ? u_near + z * (u_far - u_near)
+            : u_near * u_far / (u_far - z * (u_far - u_near));
+        // Spread out logarithmically, or everything closer than a tenth of the way to a far plane
+        // at 1000 would be black.
+        float gray = log(distance / u_near) / log(u_far / u_near);
+        f_color = vec4(vec3(gray), 1.0);
+        return;
+    }
+
+    if (u_flat_color) {
+        f_color = u_tint;
+        return;
+    }
+
+    vec4 color = u_tint * (u_textured ? v_color * texture(u_texture, v_tex_coords) : v_color);
+    if (!u_lighting) {
+        f_color = color;
+        return;
+    }
+
+    vec3 normal = normalize(v_normal.xyz);
+    // With culling off, the inside of a model shows through its back faces. Their normals point
+    // away from the camera, so they're turned around to light that side. The winding of the
+    // triangles isn't consistent, so this goes by the normal rather than `gl_FrontFacing`.
+    if (u_double_sided && dot(normal, u_camera_position - v_world_position.xyz) < 0.0) {
+        normal = -normal;
+    }
     vec3 light = normalize(u_light_position - v_world_position.xyz);
-    float brightness = clamp(dot(light, normalize(v_normal.xyz)), 0.01, 1.0);
-    f_color = brightness * v_color;
+    // The ambient term is the least light any face gets, so faces turned away never go fully black.
+    float brightness = clamp(u_diffuse * dot(light, normal), u_ambient, 1.0);
+    // Blinn-Phong highlight, white whatever the face's color. The light, camera and world
+    // position are all in world space. Faces turned away from the light don't get one.
+    vec3 view = normalize(u_camera_position - v_world_position.xyz);
+    vec3 halfway = normalize(light + view);
+    float highlight = dot(light, normal) > 0.0
+        ? u_specular * pow(max(dot(normal, halfway), 0.0), u_shininess)
+        : 0.0;
+    if (!u_gamma_correct) {
+        f_color = vec4(brightness * color.rgb + highlight, color.a);
+        return;
+    }
+    // Scaling the sRGB values darkens the faces turned partly away too much, since the same step
+    // in them is a smaller step in light at the dark end. The unlit colors are left as they are,
+    // where converting there and back would change nothing.
+    vec3 linear = pow(color.rgb, vec3(2.2));
+    f_color = vec4(pow(brightness * linear + highlight, vec3(1.0 / 2.2)), color.a);
 }
@@ -3,15 +3,18 @@
 in vec3 position;
 in vec4 color;
 in vec3 normal;
+in vec2 tex_coords;
 out vec4 v_color;
 out vec4 v_normal;
 out vec4 v_world_position;
+out vec2 v_tex_coords;
 uniform mat4 u_view;
 uniform mat4 u_model;
 uniform mat4 u_projection;
 
 void main() {
     v_color = color;
+    v_tex_coords = tex_coords;
     v_world_position = u_model * vec4(position, 1.0);
     v_normal = u_model * vec4(normal, 0.0);
     gl_Position = u_projection * u_view * v_world_position;
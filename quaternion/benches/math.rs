@@ -0,0 +1,62 @@
+#[macro_use]
+extern crate criterion;
+extern crate nalgebra;
+extern crate quaternion;
+
+use criterion::{black_box, Criterion};
+use nalgebra::base::Vector3;
+use nalgebra::geometry::UnitQuaternion;
+use quaternion::Quaternion;
+
+// Long enough to stand in for a large list of rotations in the demo.
+const LIST_LENGTH: usize = 1000;
+
+fn bench_slerp(c: &mut Criterion) {
+    let from = Quaternion::from_euler_angles(0.1, 0.2, 0.3);
+    let to = Quaternion::from_euler_angles(1.5, -0.7, 2.0);
+    c.bench_function("slerp", move |b| b.iter(|| black_box(from).slerp(black_box(to), black_box(0.4))));
+}
+
+// The same slerp through nalgebra, for comparing against the demo's `unit-quaternion` feature.
+fn bench_unit_quaternion_slerp(c: &mut Criterion) {
+    let from = UnitQuaternion::from(Quaternion::from_euler_angles(0.1, 0.2, 0.3));
+    let to = UnitQuaternion::from(Quaternion::from_euler_angles(1.5, -0.7, 2.0));
+    c.bench_function("UnitQuaternion slerp", move |b| b.iter(|| black_box(from).slerp(&black_box(to), black_box(0.4))));
+}
+
+fn bench_nlerp(c: &mut Criterion) {
+    let from = Quaternion::from_euler_angles(0.1, 0.2, 0.3);
+    let to = Quaternion::from_euler_angles(1.5, -0.7, 2.0);
+    c.bench_function("nlerp", move |b| b.iter(|| black_box(from).nlerp(black_box(to), black_box(0.4))));
+}
+
+fn bench_into_matrix(c: &mut Criterion) {
+    let q = Quaternion::from_euler_angles(0.1, 0.2, 0.3);
+    c.bench_function("into_matrix", move |b| b.iter(|| black_box(q).into_matrix()));
+}
+
+fn bench_rotate_vector(c: &mut Criterion) {
+    let q = Quaternion::from_euler_angles(0.1, 0.2, 0.3);
+    let v = Vector3::new(1.0, 2.0, 3.0);
+    c.bench_function("rotate_vector", move |b| b.iter(|| black_box(q).rotate_vector(black_box(v))));
+}
+
+fn bench_compose(c: &mut Criterion) {
+    let rotations: Vec<Quaternion> = (0..LIST_LENGTH)
+        .map(|i| Quaternion::from_euler_angles(i as f32 * 0.01, i as f32 * 0.02, i as f32 * 0.03))
+        .collect();
+    c.bench_function("compose 1000 rotations", move |b| b.iter(|| Quaternion::compose(black_box(&rotations))));
+}
+
+fn bench_unit_quaternion_compose(c: &mut Criterion) {
+    let rotations: Vec<UnitQuaternion<f32>> = (0..LIST_LENGTH)
+        .map(|i| Quaternion::from_euler_angles(i as f32 * 0.01, i as f32 * 0.02, i as f32 * 0.03).into())
+        .collect();
+    c.bench_function("UnitQuaternion compose 1000 rotations", move |b| b.iter(|| {
+        black_box(&rotations).iter().fold(UnitQuaternion::identity(), |product, q| product * q)
+    }));
+}
+
+criterion_group!(benches, bench_slerp, bench_unit_quaternion_slerp, bench_nlerp, bench_into_matrix, bench_rotate_vector,
+    bench_compose, bench_unit_quaternion_compose);
+criterion_main!(benches);
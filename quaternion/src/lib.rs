@@ -0,0 +1,1213 @@
+//! A quaternion type for representing 3D rotations, with conversions to and from Euler angles,
+//! axis-angle pairs and rotation matrices. Vectors and matrices are `nalgebra` types.
+
+extern crate nalgebra;
+#[cfg(test)]
+extern crate quickcheck;
+
+use std::ops;
+
+/// A rotation, stored as the quaternion `w + xi + yj + zk`. The constructors all return unit
+/// quaternions, and multiplying renormalizes, so a product of rotations stays a rotation.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Quaternion {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+// `len` is the quaternion's norm, and a quaternion can't be empty.
+#[allow(clippy::len_without_is_empty)]
+impl Quaternion {
+    /// Returns the rotation that leaves everything where it is.
+    pub fn identity() -> Quaternion {
+        Quaternion { x: 0.0, y: 0.0, z: 0.0, w: 1.0 }
+    }
+
+    /// Returns the rotation by `roll` about Z, then `pitch` about X, then `yaw` about Y, with the
+    /// angles in radians. That is, the product of those three rotations in the order yaw * pitch * roll.
+    pub fn from_euler_angles(yaw: f32, pitch: f32, roll: f32) -> Quaternion {
+        let yaw_c = (yaw * 0.5).cos();
+        let yaw_s = (yaw * 0.5).sin();
+        let pitch_c = (pitch * 0.5).cos();
+        let pitch_s = (pitch * 0.5).sin();
+        let roll_c = (roll * 0.5).cos();
+        let roll_s = (roll * 0.5).sin();
+
+        Quaternion {
+            x: pitch_s * yaw_c * roll_c + pitch_c * yaw_s * roll_s,
+			y: pitch_c * yaw_s * roll_c - pitch_s * yaw_c * roll_s,
+			z: pitch_c * yaw_c * roll_s - pitch_s * yaw_s * roll_c,
+			w: pitch_c * yaw_c * roll_c + pitch_s * yaw_s * roll_s,
+        }
+    }
+
+    /// Like `from_euler_angles`, with the angles in degrees.
+    pub fn from_euler_degrees(yaw: f32, pitch: f32, roll: f32) -> Quaternion {
+        Quaternion::from_euler_angles(yaw.to_radians(), pitch.to_radians(), roll.to_radians())
+    }
+
+    /// Returns the rotation from URDF's `rpy` angles in radians: `roll` about X, then `pitch`
+    /// about Y, then `yaw` about Z, all about the fixed axes. That is, yaw * pitch * roll about
+    /// Z, Y and X, which is a different order and different axes from `from_euler_angles`.
+    pub fn from_rpy(roll: f32, pitch: f32, yaw: f32) -> Quaternion {
+        let (roll_s, roll_c) = (roll * 0.5).sin_cos();
+        let (pitch_s, pitch_c) = (pitch * 0.5).sin_cos();
+        let (yaw_s, yaw_c) = (yaw * 0.5).sin_cos();
+
+        Quaternion {
+            x: roll_s * pitch_c * yaw_c - roll_c * pitch_s * yaw_s,
+            y: roll_c * pitch_s * yaw_c + roll_s * pitch_c * yaw_s,
+            z: roll_c * pitch_c * yaw_s - roll_s * pitch_s * yaw_c,
+            w: roll_c * pitch_c * yaw_c + roll_s * pitch_s * yaw_s,
+        }
+    }
+
+    /// Returns the rotation by `angle` radians about the axis `(x, y, z)`, which must be unit
+    /// length. See `normalize_axis` for axes that may not be.
+    pub fn from_axis_angle(x: f32, y: f32, z: f32, angle: f32) -> Quaternion {
+        let sin = (angle / 2.0).sin();
+        let cos = (angle / 2.0).cos();
+        Quaternion {
+            x: x * sin,
+            y: y * sin,
+            z: z * sin,
+            w: cos,
+        }
+    }
+
+    /// Returns the rotation that points the local -Z axis along `forward`, with the local +Y axis
+    /// as close to `up` as possible. This matches how the camera looks down its -Z axis.
+    /// `forward` and `up` must not be parallel.
+    pub fn look_rotation(forward: nalgebra::base::Vector3<f32>, up: nalgebra::base::Vector3<f32>) -> Quaternion {
+        let back = -forward.normalize();
+        let right = up.cross(&back).normalize();
+        let up = back.cross(&right);
+        Quaternion::from_basis(right, up, back)
+    }
+
+    /// Returns the smallest rotation that turns the direction `from` to the direction `to`. Neither
+    /// needs to be normalized. Directions that are already the same give the identity, and for
+    /// opposite ones, which any half turn about a perpendicular axis would do, the axis is the one
+    /// perpendicular to `from` and closest to +X, or to +Y if `from` is along X.
+    pub fn from_two_vectors(from: nalgebra::base::Vector3<f32>, to: nalgebra::base::Vector3<f32>) -> Quaternion {
+        const PARALLEL: f32 = 1.0e-6;
+
+        let from = normalize_axis(from);
+        let to = normalize_axis(to);
+        let dot = from.dot(&to);
+        if dot >= 1.0 - PARALLEL {
+            return Quaternion::identity();
+        }
+        if dot <= -1.0 + PARALLEL {
+            let other = if from[0].abs() < 0.9 { nalgebra::base::Vector3::x() } else { nalgebra::base::Vector3::y() };
+            let axis = (other - from * from.dot(&other)).normalize();
+            return Quaternion { x: axis[0], y: axis[1], z: axis[2], w: 0.0 };
+        }
+        // (cross, dot) turns twice as far as wanted, and adding the identity to it before
+        // normalizing is the rotation halfway there.
+        let axis = from.cross(&to);
+        let mut q = Quaternion { x: axis[0], y: axis[1], z: axis[2], w: 1.0 + dot };
+        q.normalize();
+        q
+    }
+
+    // Converts an orthonormal basis (the columns of a rotation matrix) into a quaternion.
+    fn from_basis(right: nalgebra::base::Vector3<f32>, up: nalgebra::base::Vector3<f32>, back: nalgebra::base::Vector3<f32>) -> Quaternion {
+        let trace = right[0] + up[1] + back[2];
+        let mut q = if trace > 0.0 {
+            let s = 0.5 / (trace + 1.0).sqrt();
+            Quaternion { x: (up[2] - back[1]) * s, y: (back[0] - right[2]) * s, z: (right[1] - up[0]) * s, w: 0.25 / s }
+        } else if right[0] > up[1] && right[0] > back[2] {
+            let s = 2.0 * (1.0 + right[0] - up[1] - back[2]).sqrt();
+            Quaternion { x: 0.25 * s, y: (up[0] + right[1]) / s, z: (back[0] + right[2]) / s, w: (up[2] - back[1]) / s }
+        } else if up[1] > back[2] {
+            let s = 2.0 * (1.0 + up[1] - right[0] - back[2]).sqrt();
+            Quaternion { x: (up[0] + right[1]) / s, y: 0.25 * s, z: (back[1] + up[2]) / s, w: (back[0] - right[2]) / s }
+        } else {
+            let s = 2.0 * (1.0 + back[2] - right[0] - up[1]).sqrt();
+            Quaternion { x: (back[0] + right[2]) / s, y: (back[1] + up[2]) / s, z: 0.25 * s, w: (right[1] - up[0]) / s }
+        };
+        q.normalize();
+        q
+    }
+
+    /// Returns the first two columns of the rotation matrix, where the local +X and +Y axes end
+    /// up, as `[x, y]`. This is the continuous 6D representation used to train neural networks,
+    /// which unlike quaternions has one value for each rotation and no jumps between them.
+    pub fn to_rotation_6d(&self) -> [f32; 6] {
+        let x = self.rotate_vector(nalgebra::base::Vector3::new(1.0, 0.0, 0.0));
+        let y = self.rotate_vector(nalgebra::base::Vector3::new(0.0, 1.0, 0.0));
+        [x[0], x[1], x[2], y[0], y[1], y[2]]
+    }
+
+    /// Builds the rotation from a 6D representation. Predicted columns are rarely orthonormal,
+    /// so they are Gram-Schmidt orthonormalized first, keeping the direction of the first column.
+    /// The two columns must not be parallel.
+    pub fn from_rotation_6d(r: [f32; 6]) -> Quaternion {
+        let x = nalgebra::base::Vector3::new(r[0], r[1], r[2]).normalize();
+        let y = nalgebra::base::Vector3::new(r[3], r[4], r[5]);
+        let y = (y - x * x.dot(&y)).normalize();
+        Quaternion::from_basis(x, y, x.cross(&y))
+    }
+
+    /// Returns the product of the rotations, multiplied left to right: `qs[0] * qs[1] * ...`.
+    /// The product is normalized as it goes, so a long list doesn't drift off the unit sphere,
+    /// and an empty slice composes to the identity.
+    pub fn compose(qs: &[Quaternion]) -> Quaternion {
+        qs.iter().fold(Quaternion::identity(), |mut product, &q| {
+            product *= q;
+            product
+        })
+    }
+
+    /// Returns the average of several rotations, by Markley's method: the eigenvector with the
+    /// largest eigenvalue of the sum of each quaternion's outer product with itself. q and -q add
+    /// the same outer product, so unlike a plain mean the result doesn't depend on the signs of
+    /// the inputs. The result is given the same sign as the first rotation, and an empty slice
+    /// averages to the identity.
+    pub fn average(qs: &[Quaternion]) -> Quaternion {
+        let first = match qs.first() {
+            Some(&q) => q,
+            None => return Quaternion::identity(),
+        };
+        let mut sum = nalgebra::core::Matrix4::zeros();
+        for q in qs {
+            let v = nalgebra::base::Vector4::new(q.x, q.y, q.z, q.w);
+            sum += v * v.transpose();
+        }
+
+        // The sum is symmetric and positive semi-definite, so its largest eigenvalue is also the
+        // largest in magnitude.
+        let eigen = sum.symmetric_eigen();
+        let v = eigen.eigenvectors.column(eigen.eigenvalues.iamax());
+        let mut q = Quaternion { x: v[0], y: v[1], z: v[2], w: v[3] };
+        if q.dot(first) < 0.0 {
+            q = -q;
+        }
+        q.normalize();
+        q
+    }
+
+    /// Returns whichever of this quaternion and its negation has `w >= 0`, so that every rotation
+    /// has one representative and turns by at most π. When `w` is zero, the first non-zero
+    /// component of the axis is made positive instead.
+    pub fn canonical(&self) -> Quaternion {
+        let sign = [self.w, self.x, self.y, self.z].iter().cloned().find(|&c| c != 0.0).unwrap_or(0.0);
+        if sign < 0.0 { -*self } else { *self }
+    }
+
+    /// Returns the conjugate, with the axis negated. For a unit quaternion it's the inverse
+    /// rotation, turning the same angle the other way.
+    pub fn conjugate(&self) -> Quaternion {
+        Quaternion { x: -self.x, y: -self.y, z: -self.z, w: self.w }
+    }
+
+    /// Returns the unit axis and angle (in radians) of this rotation.
+    /// The identity rotation has no unique axis, so +X is returned with a zero angle.
+    pub fn to_axis_angle(&self) -> (nalgebra::base::Vector3<f32>, f32) {
+        let w = self.w.max(-1.0).min(1.0);
+        let angle = 2.0 * w.acos();
+        let sin = (1.0 - w*w).sqrt();
+        if sin < 1.0e-6 {
+            return ([1.0, 0.0, 0.0].into(), 0.0);
+        }
+
+        ([self.x / sin, self.y / sin, self.z / sin].into(), angle)
+    }
+
+    /// Returns the `(yaw, pitch, roll)` angles (in radians) that `from_euler_angles` builds this
+    /// rotation from. Pitch is within ±π/2, and yaw and roll are within ±π. When pitch is at
+    /// ±π/2 the yaw and roll turn about the same axis, so the roll is returned as zero.
+    pub fn to_euler_angles(&self) -> (f32, f32, f32) {
+        let (x, y, z, w) = (self.x, self.y, self.z, self.w);
+        let sin_pitch = (-2.0 * (y*z - x*w)).max(-1.0).min(1.0);
+        let pitch = sin_pitch.asin();
+
+        if sin_pitch.abs() > 0.99999 {
+            let yaw = (-2.0 * (x*z - y*w)).atan2(1.0 - 2.0*y*y - 2.0*z*z);
+            return (yaw, pitch, 0.0);
+        }
+
+        let yaw = (2.0 * (x*z + y*w)).atan2(1.0 - 2.0*x*x - 2.0*y*y);
+        let roll = (2.0 * (x*y + z*w)).atan2(1.0 - 2.0*x*x - 2.0*z*z);
+        (yaw, pitch, roll)
+    }
+
+    /// Returns the `(roll, pitch, yaw)` angles (in radians) that `from_rpy` builds this rotation
+    /// from. Pitch is within ±π/2, and roll and yaw are within ±π. When pitch is at ±π/2 the roll
+    /// and yaw turn about the same axis, so the roll is returned as zero.
+    pub fn to_rpy(&self) -> (f32, f32, f32) {
+        let (x, y, z, w) = (self.x, self.y, self.z, self.w);
+        let sin_pitch = (2.0 * (w*y - x*z)).max(-1.0).min(1.0);
+        let pitch = sin_pitch.asin();
+
+        if sin_pitch.abs() > 0.99999 {
+            let yaw = (-2.0 * (x*y - w*z)).atan2(1.0 - 2.0*x*x - 2.0*z*z);
+            return (0.0, pitch, yaw);
+        }
+
+        let roll = (2.0 * (w*x + y*z)).atan2(1.0 - 2.0*x*x - 2.0*y*y);
+        let yaw = (2.0 * (w*z + x*y)).atan2(1.0 - 2.0*y*y - 2.0*z*z);
+        (roll, pitch, yaw)
+    }
+
+    
+    /// Returns the length (norm) of the quaternion, which is 1 for rotations.
+    pub fn len(&self) -> f32 {
+        (self.x*self.x + self.y*self.y + self.z*self.z + self.w*self.w).sqrt()
+    }
+    
+    /// Scales the quaternion to unit length. It must not be zero.
+    pub fn normalize(&mut self) {
+        let inv_len = 1.0 / self.len();
+        self.x *= inv_len;
+        self.y *= inv_len;
+        self.z *= inv_len;
+        self.w *= inv_len;
+    }
+
+    /// Returns the rotation as a homogeneous 4x4 matrix.
+    pub fn into_matrix(self) -> nalgebra::core::Matrix4<f32> {
+        let mut m = nalgebra::core::Matrix4::identity();
+        let x = self.x;
+        let y = self.y;
+        let z = self.z;
+        let w = self.w;
+        m[(0,0)] = 1.0 - 2.0*y*y - 2.0*z*z;
+        m[(0,1)] = 2.0*x*y - 2.0*z*w;
+        m[(0,2)] = 2.0*x*z + 2.0*y*w;
+
+        m[(1,0)] = 2.0*x*y + 2.0*z*w;
+        m[(1,1)] = 1.0 - 2.0*x*x - 2.0*z*z;
+        m[(1,2)] = 2.0*y*z - 2.0*x*w;
+
+        m[(2,0)] = 2.0*x*z - 2.0*y*w;
+        m[(2,1)] = 2.0*y*z + 2.0*x*w;
+        m[(2,2)] = 1.0 - 2.0*x*x - 2.0*y*y;
+
+        m
+    }
+
+    /// Rotates a vector by this rotation, assuming this is a unit quaternion.
+    pub fn rotate_vector(&self, v: nalgebra::base::Vector3<f32>) -> nalgebra::base::Vector3<f32> {
+        let u = nalgebra::base::Vector3::new(self.x, self.y, self.z);
+        let t = 2.0 * u.cross(&v);
+        v + self.w * t + u.cross(&t)
+    }
+
+    /// Returns `t` of this rotation, from none of it at 0 to all of it at 1, by slerping from the
+    /// identity. Like `slerp` it goes the short way round, so half of a three-quarter turn is an
+    /// eighth of a turn the opposite way.
+    pub fn scaled(&self, t: f32) -> Quaternion {
+        Quaternion::identity().slerp(*self, t)
+    }
+
+    /// Returns this rotation to the power `t`, which is the rotation about the same axis by `t`
+    /// times its angle. The angle is taken the short way round, so it's at most π. Unlike `scaled`,
+    /// `t` can be above 1 to turn further, or negative to turn the other way.
+    pub fn pow(&self, t: f32) -> Quaternion {
+        // The angle comes from atan2 rather than the acos in `to_axis_angle`, which loses
+        // precision for small angles, where w is close to 1.
+        let q = self.canonical();
+        let sin = (q.x*q.x + q.y*q.y + q.z*q.z).sqrt();
+        if sin < 1.0e-12 {
+            return Quaternion::identity();
+        }
+        let angle = 2.0 * sin.atan2(q.w);
+        Quaternion::from_axis_angle(q.x / sin, q.y / sin, q.z / sin, angle * t)
+    }
+
+    /// Advances this orientation by `dt` seconds of turning at the angular velocity `omega`, in
+    /// radians per second about world space axes, with the 4th order Runge-Kutta method. The
+    /// result is normalized, so it stays a rotation.
+    pub fn integrate_rk4(&self, omega: nalgebra::base::Vector3<f32>, dt: f32) -> Quaternion {
+        let step = |k: Quaternion, h: f32| Quaternion {
+            x: self.x + h * k.x,
+            y: self.y + h * k.y,
+            z: self.z + h * k.z,
+            w: self.w + h * k.w,
+        };
+
+        let k1 = self.derivative(omega);
+        let k2 = step(k1, dt / 2.0).derivative(omega);
+        let k3 = step(k2, dt / 2.0).derivative(omega);
+        let k4 = step(k3, dt).derivative(omega);
+        let mut result = Quaternion {
+            x: self.x + dt / 6.0 * (k1.x + 2.0*k2.x + 2.0*k3.x + k4.x),
+            y: self.y + dt / 6.0 * (k1.y + 2.0*k2.y + 2.0*k3.y + k4.y),
+            z: self.z + dt / 6.0 * (k1.z + 2.0*k2.z + 2.0*k3.z + k4.z),
+            w: self.w + dt / 6.0 * (k1.w + 2.0*k2.w + 2.0*k3.w + k4.w),
+        };
+        result.normalize();
+        result
+    }
+
+    /// Returns the rate of change of this orientation while it turns at the angular velocity
+    /// `omega`, in radians per second about world space axes: `0.5 * (0, omega) * self`.
+    ///
+    /// This is a rate rather than a rotation, so it isn't normalized and usually isn't a unit
+    /// quaternion.
+    pub fn derivative(&self, omega: nalgebra::base::Vector3<f32>) -> Quaternion {
+        // The product is written out because `Mul` normalizes its result.
+        Quaternion {
+            x: 0.5 * (omega[0]*self.w + omega[1]*self.z - omega[2]*self.y),
+            y: 0.5 * (-omega[0]*self.z + omega[1]*self.w + omega[2]*self.x),
+            z: 0.5 * (omega[0]*self.y - omega[1]*self.x + omega[2]*self.w),
+            w: -0.5 * (omega[0]*self.x + omega[1]*self.y + omega[2]*self.z),
+        }
+    }
+
+    /// Returns the rotation halfway between this one and `other`, the short way round.
+    pub fn midpoint(&self, other: Quaternion) -> Quaternion {
+        self.slerp(other, 0.5)
+    }
+
+    /// Linearly interpolates `t` of the way from this rotation to `dst` and normalizes, along the
+    /// shortest path. Cheaper than `slerp`, but it turns faster in the middle than at the ends.
+    pub fn nlerp(&self, mut dst: Quaternion, t: f32) -> Quaternion {
+        if self.dot(dst) < 0.0 {
+            dst = -dst;
+        }
+        let mut q = Quaternion {
+            x: self.x + t*(dst.x - self.x),
+            y: self.y + t*(dst.y - self.y),
+            z: self.z + t*(dst.z - self.z),
+            w: self.w + t*(dst.w - self.w),
+        };
+        q.normalize();
+        q
+    }
+
+    /// Blends rotations by their weights, e.g. to mix animation poses. The weights are scaled to
+    /// add up to one, and each quaternion is flipped to the same side as the first so that q and
+    /// -q blend alike. Weights adding up to zero, or rotations cancelling out, give the identity.
+    pub fn weighted_blend(qs: &[(Quaternion, f32)]) -> Quaternion {
+        let total: f32 = qs.iter().map(|&(_, weight)| weight).sum();
+        if total.abs() < 1.0e-6 {
+            return Quaternion::identity();
+        }
+
+        let first = qs[0].0;
+        let mut sum = Quaternion { x: 0.0, y: 0.0, z: 0.0, w: 0.0 };
+        for &(q, weight) in qs {
+            let weight = if q.dot(first) < 0.0 { -weight / total } else { weight / total };
+            sum.x += weight * q.x;
+            sum.y += weight * q.y;
+            sum.z += weight * q.z;
+            sum.w += weight * q.w;
+        }
+        if sum.len() < 1.0e-6 {
+            return Quaternion::identity();
+        }
+        sum.normalize();
+        sum
+    }
+
+    /// Spherically interpolates `t` of the way from this rotation to `dst`, along the shortest
+    /// path. Rotations too close together to interpolate precisely are linearly interpolated.
+    pub fn slerp(&self, mut dst: Quaternion, t: f32) -> Quaternion {
+        let mut dot = self.dot(dst);
+
+        if dot < 0.0 {
+            dst.x = -dst.x;
+            dst.y = -dst.y;
+            dst.z = -dst.z;
+            dst.w = -dst.w;
+            dot = -dot;
+        }
+
+        if dot >= 0.999 {
+            let mut q = Quaternion {
+                x: self.x + t*(dst.x - self.x),
+                y: self.y + t*(dst.y - self.y),
+                z: self.z + t*(dst.z - self.z),
+                w: self.w + t*(dst.w - self.w),
+            };
+            q.normalize();
+            return q;
+        }
+
+        let theta0 = dot.acos();
+        let theta = theta0*t;
+        let sin_theta = theta.sin();
+        let sin_theta0 = theta0.sin();
+
+        let s0 = theta.cos() - dot * sin_theta / sin_theta0;
+        let s1 = sin_theta / sin_theta0;
+
+        let mut q = Quaternion {
+            x: s0 * self.x + s1 * dst.x,
+            y: s0 * self.y + s1 * dst.y,
+            z: s0 * self.z + s1 * dst.z,
+            w: s0 * self.w + s1 * dst.w,
+        };
+        q.normalize();
+        q
+    }
+
+    /// Returns the four-dimensional dot product with `other`. For rotations it's the cosine of half
+    /// the angle between them, and it's negative when `other` is the long way around from this one.
+    pub fn dot(&self, other: Quaternion) -> f32 {
+        self.x*other.x + self.y*other.y + self.z*other.z + self.w*other.w
+    }
+
+    /// Returns the angle in radians, from 0 to π, of the smallest rotation from this one to `other`.
+    pub fn angle_between(&self, other: Quaternion) -> f32 {
+        2.0 * self.dot(other).abs().min(1.0).acos()
+    }
+}
+
+/// Normalizes a rotation axis for `Quaternion::from_axis_angle`. An axis too short to have a
+/// direction falls back to +X, rather than dividing by zero and filling the rotation with NaNs.
+pub fn normalize_axis(axis: nalgebra::base::Vector3<f32>) -> nalgebra::base::Vector3<f32> {
+    const MIN_LENGTH: f32 = 1.0e-6;
+
+    let len = axis.norm();
+    if len < MIN_LENGTH {
+        return nalgebra::base::Vector3::x();
+    }
+    axis / len
+}
+
+/// Reflects `v` through the plane through the origin with the given `normal`, which needn't be
+/// normalized.
+///
+/// Unlike a rotation, a reflection turns a right-handed set of axes into a left-handed one. It
+/// isn't in SO(3), so no quaternion rotates `v` to where this puts it: `q * v * q⁻¹` can only
+/// give proper rotations.
+pub fn reflect_vector(normal: nalgebra::base::Vector3<f32>, v: nalgebra::base::Vector3<f32>) -> nalgebra::base::Vector3<f32> {
+    let normal = normalize_axis(normal);
+    v - normal * (2.0 * v.dot(&normal))
+}
+
+impl ops::Mul<Quaternion> for Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, rhs: Quaternion) -> Quaternion {
+        let mut q = Quaternion {
+            x: self.x * rhs.w + self.y * rhs.z - self.z * rhs.y + self.w * rhs.x,
+            y: -self.x * rhs.z + self.y * rhs.w + self.z * rhs.x + self.w * rhs.y,
+            z: self.x * rhs.y - self.y * rhs.x + self.z * rhs.w + self.w * rhs.z,
+            w: -self.x * rhs.x - self.y * rhs.y - self.z * rhs.z + self.w * rhs.w,
+        };
+        q.normalize();
+        q
+    }
+}
+
+impl ops::MulAssign<Quaternion> for Quaternion {
+    fn mul_assign(&mut self, rhs: Quaternion) {
+        *self = Quaternion {
+            x: self.x * rhs.w + self.y * rhs.z - self.z * rhs.y + self.w * rhs.x,
+            y: -self.x * rhs.z + self.y * rhs.w + self.z * rhs.x + self.w * rhs.y,
+            z: self.x * rhs.y - self.y * rhs.x + self.z * rhs.w + self.w * rhs.z,
+            w: -self.x * rhs.x - self.y * rhs.y - self.z * rhs.z + self.w * rhs.w,
+        };
+        self.normalize();
+    }
+}
+
+/// Negating every component gives the same rotation, from the other half of the double cover.
+impl ops::Neg for Quaternion {
+    type Output = Quaternion;
+
+    fn neg(self) -> Quaternion {
+        Quaternion { x: -self.x, y: -self.y, z: -self.z, w: -self.w }
+    }
+}
+
+/// Converts from nalgebra's unit quaternion, to use its results with the rest of this crate.
+impl From<nalgebra::geometry::UnitQuaternion<f32>> for Quaternion {
+    fn from(q: nalgebra::geometry::UnitQuaternion<f32>) -> Quaternion {
+        let coords = q.quaternion().coords;
+        Quaternion { x: coords[0], y: coords[1], z: coords[2], w: coords[3] }
+    }
+}
+
+/// Converts to nalgebra's unit quaternion, renormalizing in case the components have drifted.
+impl From<Quaternion> for nalgebra::geometry::UnitQuaternion<f32> {
+    fn from(q: Quaternion) -> nalgebra::geometry::UnitQuaternion<f32> {
+        nalgebra::geometry::UnitQuaternion::from_quaternion(nalgebra::geometry::Quaternion::new(q.w, q.x, q.y, q.z))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::std::f32::consts::PI;
+
+    const EPSILON: f32 = 1.0e-6;
+
+    // Check if two floats are approximately equal.
+    fn assert_feq(a: f32, b: f32) {
+        if (a-b).abs() >= EPSILON {
+            panic!("Not equal: {} {}", a, b);
+        }
+    }
+
+    // Check if two Quaternions are approximately equal.
+    fn assert_quat_eq(a: Quaternion, b: Quaternion) {
+        if (a.x-b.x).abs() >= EPSILON || (a.y-b.y).abs() >= EPSILON || (a.z- b.z).abs() >= EPSILON || (a.w-b.w).abs() >= EPSILON {
+            panic!("Not equal: {:?} {:?}", a, b);
+        }
+    }
+
+    // Check if two Quaternion represent approximately the same rotation.
+    // Both q and -q represent the same rotation.
+    fn assert_rot_eq(a: Quaternion, b: Quaternion) {
+        if (a.x-b.x).abs() >= EPSILON || (a.y-b.y).abs() >= EPSILON || (a.z- b.z).abs() >= EPSILON || (a.w-b.w).abs() >= EPSILON {
+            if (a.x+b.x).abs() >= EPSILON || (a.y+b.y).abs() >= EPSILON || (a.z+b.z).abs() >= EPSILON || (a.w+b.w).abs() >= EPSILON {
+                panic!("Not equal: {:?} {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_identity() {
+        assert_eq!(Quaternion::identity(), Quaternion { x: 0.0, y: 0.0, z: 0.0, w: 1.0 });
+    }
+
+    #[test]
+    fn test_len() {
+        assert_eq!(Quaternion::identity().len(), 1.0);
+        assert_eq!(Quaternion { x: 2.0, y: 2.0, z: 2.0, w: 2.0 }.len(), 4.0);
+
+        // Rotations should be unit quaternions.
+        let q = Quaternion::from_euler_angles(0.1, 0.4, 123.0);
+        if (q.len() - 1.0).abs() >= EPSILON {
+            panic!("Incorrect length");
+        }
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut q = Quaternion { x: 2.0, y: -2.0, z: 2.0, w: -2.0 };
+        q.normalize();
+        assert_feq(q.len(), 1.0);
+        assert_quat_eq(q, Quaternion { x: 0.5, y: -0.5, z: 0.5, w: -0.5 });
+    }
+
+    #[test]
+    fn test_euler_angles() {
+        let q = Quaternion::from_euler_angles(0.0, 0.0, PI);
+        assert_rot_eq(q, Quaternion { x: 0.0, y: 1.0, z: 0.0, w: 0.0 });
+
+        let q = Quaternion::from_euler_angles(0.0, PI * 2.0, 0.0);
+        assert_rot_eq(q, Quaternion { x: 0.0, y: 0.0, z: 0.0, w: 1.0 });
+
+        let p = Quaternion::from_euler_angles(PI / 2.0, PI / 2.0, PI / 2.0);
+        let q =  Quaternion::from_euler_angles(0.0, PI / 2.0, 0.0);
+        assert_rot_eq(p, q);
+    }
+
+    #[test]
+    fn test_to_euler_angles() {
+        let angles = [(0.3, -0.4, 1.2), (-2.5, 1.0, -0.1), (0.0, 0.0, 0.0), (PI / 2.0, 0.2, -PI / 2.0)];
+        for &(yaw, pitch, roll) in &angles {
+            let (y, p, r) = Quaternion::from_euler_angles(yaw, pitch, roll).to_euler_angles();
+            assert!((y - yaw).abs() < 1.0e-5 && (p - pitch).abs() < 1.0e-5 && (r - roll).abs() < 1.0e-5,
+                "{:?} became {:?}", (yaw, pitch, roll), (y, p, r));
+        }
+
+        // Looking straight up, the yaw and roll can't be told apart, but the rotation is kept.
+        let q = Quaternion::from_euler_angles(0.5, PI / 2.0, 0.25);
+        let (yaw, pitch, roll) = q.to_euler_angles();
+        assert_feq(roll, 0.0);
+        assert!((pitch - PI / 2.0).abs() < 1.0e-3);
+        let p = Quaternion::from_euler_angles(yaw, pitch, roll);
+        assert!((p.x-q.x).abs() < 1.0e-3 && (p.y-q.y).abs() < 1.0e-3 && (p.z-q.z).abs() < 1.0e-3 && (p.w-q.w).abs() < 1.0e-3,
+            "{:?} became {:?}", q, p);
+    }
+
+    #[test]
+    fn test_unit_quaternion_conversions() {
+        use nalgebra::geometry::UnitQuaternion;
+        let a = Quaternion::from_axis_angle(1.0, 0.0, 0.0, 0.7);
+        let b = Quaternion::from_euler_angles(0.3, -0.4, 1.2);
+        assert_quat_eq(Quaternion::from(UnitQuaternion::from(a)), a);
+        // Both multiply the same way round.
+        assert_rot_eq(Quaternion::from(UnitQuaternion::from(a) * UnitQuaternion::from(b)), a * b);
+        let (axis, angle) = UnitQuaternion::from(a).axis_angle().unwrap();
+        assert_feq(axis[0], 1.0);
+        assert_feq(angle, 0.7);
+    }
+
+    #[test]
+    fn test_rpy() {
+        let (roll, pitch, yaw) = (0.3, -0.4, 1.2);
+        let q = Quaternion::from_rpy(roll, pitch, yaw);
+        let fixed_axes = Quaternion::from_axis_angle(0.0, 0.0, 1.0, yaw)
+            * Quaternion::from_axis_angle(0.0, 1.0, 0.0, pitch)
+            * Quaternion::from_axis_angle(1.0, 0.0, 0.0, roll);
+        assert!((q.x-fixed_axes.x).abs() < 1.0e-5 && (q.y-fixed_axes.y).abs() < 1.0e-5
+            && (q.z-fixed_axes.z).abs() < 1.0e-5 && (q.w-fixed_axes.w).abs() < 1.0e-5,
+            "{:?} isn't {:?}", q, fixed_axes);
+
+        let angles = [(0.3, -0.4, 1.2), (-2.5, 1.0, -0.1), (0.0, 0.0, 0.0), (PI / 2.0, 0.2, -PI / 2.0)];
+        for &(roll, pitch, yaw) in &angles {
+            let (r, p, y) = Quaternion::from_rpy(roll, pitch, yaw).to_rpy();
+            assert!((r - roll).abs() < 1.0e-5 && (p - pitch).abs() < 1.0e-5 && (y - yaw).abs() < 1.0e-5,
+                "{:?} became {:?}", (roll, pitch, yaw), (r, p, y));
+        }
+
+        // Pitched straight up, the roll and yaw can't be told apart, but the rotation is kept.
+        let q = Quaternion::from_rpy(0.25, PI / 2.0, 0.5);
+        let (roll, pitch, yaw) = q.to_rpy();
+        assert_feq(roll, 0.0);
+        let p = Quaternion::from_rpy(roll, pitch, yaw);
+        assert!((p.x-q.x).abs() < 1.0e-3 && (p.y-q.y).abs() < 1.0e-3 && (p.z-q.z).abs() < 1.0e-3 && (p.w-q.w).abs() < 1.0e-3,
+            "{:?} became {:?}", q, p);
+    }
+
+    #[test]
+    fn test_euler_degrees() {
+        let p = Quaternion::from_euler_degrees(30.0, 45.0, 90.0);
+        let q = Quaternion::from_euler_angles(PI / 6.0, PI / 4.0, PI / 2.0);
+        assert_quat_eq(p, q);
+    }
+
+    #[test]
+    fn test_axis_angle() {
+        let q = Quaternion::from_axis_angle(1.0, 0.0, 0.0, PI * 3.0);
+        assert_rot_eq(q, Quaternion { x: 1.0, y: 0.0, z: 0.0, w: 0.0 });
+
+        let sqrt2_2 = 0.707107;
+        let q = Quaternion::from_axis_angle(0.0, sqrt2_2, -sqrt2_2, PI / 2.0);
+        assert_rot_eq(q, Quaternion { x: 0.0, y: 0.5, z: -0.5, w: sqrt2_2 });
+
+        // Rotating 360 around any axis should be equivalent.
+        let p = Quaternion::from_axis_angle(1.0, 0.0, 0.0, PI * 2.0);
+        let q = Quaternion::from_axis_angle(sqrt2_2, sqrt2_2, 0.0, -PI * 4.0);
+        assert_rot_eq(p, q);
+    }
+
+    #[test]
+    fn test_to_axis_angle() {
+        let (axis, angle) = Quaternion::identity().to_axis_angle();
+        assert_feq(angle, 0.0);
+        assert_feq(axis.norm(), 1.0);
+
+        let (axis, angle) = Quaternion::from_axis_angle(0.0, 1.0, 0.0, PI / 2.0).to_axis_angle();
+        assert_feq(angle, PI / 2.0);
+        assert_feq(axis[0], 0.0);
+        assert_feq(axis[1], 1.0);
+        assert_feq(axis[2], 0.0);
+
+        let sqrt2_2 = ::std::f32::consts::FRAC_1_SQRT_2;
+        let q = Quaternion::from_axis_angle(sqrt2_2, 0.0, -sqrt2_2, 3.0);
+        let (axis, angle) = q.to_axis_angle();
+        assert_rot_eq(Quaternion::from_axis_angle(axis[0], axis[1], axis[2], angle), q);
+    }
+
+    #[test]
+    fn test_normalize_axis() {
+        let axis = normalize_axis([0.0, 3.0, 4.0].into());
+        assert_feq(axis[0], 0.0);
+        assert_feq(axis[1], 0.6);
+        assert_feq(axis[2], 0.8);
+
+        // A zero axis still gives a finite unit axis, and so a valid rotation.
+        let axis = normalize_axis([0.0, 0.0, 0.0].into());
+        assert!(axis.iter().all(|v| v.is_finite()));
+        assert_feq(axis.norm(), 1.0);
+        let q = Quaternion::from_axis_angle(axis[0], axis[1], axis[2], PI / 2.0);
+        assert_feq(q.len(), 1.0);
+
+        // Short axes keep their direction, down to where rounding would swamp it.
+        let axis = normalize_axis([0.0, 3.0e-5, 4.0e-5].into());
+        assert_feq(axis[1], 0.6);
+        assert_feq(axis[2], 0.8);
+        let axis = normalize_axis([1.0e-9, -1.0e-9, 0.0].into());
+        assert_eq!(axis, nalgebra::base::Vector3::x());
+    }
+
+    #[test]
+    fn test_reflect_vector() {
+        use nalgebra::base::Vector3;
+
+        let normal = Vector3::new(1.0, -2.0, 0.5);
+        let v = Vector3::new(0.3, 0.8, -1.1);
+        let twice = reflect_vector(normal, reflect_vector(normal, v));
+        assert!((twice - v).norm() < 1.0e-5);
+
+        let mirrored = reflect_vector(Vector3::z(), v);
+        assert!((mirrored - Vector3::new(0.3, 0.8, 1.1)).norm() < 1.0e-6);
+
+        // Reflecting the axes flips their handedness, which a rotation never does.
+        let [x, y, z] = [Vector3::x(), Vector3::y(), Vector3::z()];
+        let handedness = |x: Vector3<f32>, y: Vector3<f32>, z: Vector3<f32>| x.cross(&y).dot(&z);
+        let reflect = |v| reflect_vector(normal, v);
+        assert_feq(handedness(reflect(x), reflect(y), reflect(z)), -1.0);
+        let q = Quaternion::from_euler_angles(0.4, 1.1, -2.0);
+        let rotate = |v| q.rotate_vector(v);
+        assert_feq(handedness(rotate(x), rotate(y), rotate(z)), 1.0);
+    }
+
+    #[test]
+    fn test_compose() {
+        let a = Quaternion::from_axis_angle(1.0, 0.0, 0.0, PI / 2.0);
+        let b = Quaternion::from_axis_angle(0.0, 1.0, 0.0, PI / 3.0);
+        let c = Quaternion::from_euler_angles(0.2, -0.4, 1.3);
+        assert_quat_eq(Quaternion::compose(&[a, b, c]), a * b * c);
+        assert_feq(Quaternion::compose(&[a, b, c]).len(), 1.0);
+
+        // The order matters, as rotations about different axes don't commute.
+        assert_quat_eq(Quaternion::compose(&[b, a]), b * a);
+        assert!(Quaternion::compose(&[a, b]).angle_between(Quaternion::compose(&[b, a])) > 0.1);
+
+        assert_quat_eq(Quaternion::compose(&[]), Quaternion::identity());
+        assert_quat_eq(Quaternion::compose(&[c]), c);
+    }
+
+    #[test]
+    fn test_mul() {
+        let id = Quaternion::identity();
+        let zero = Quaternion { x: 0.0, y: 0.0, z: 0.0, w: 0.0 };
+        assert_quat_eq(id*zero, zero);
+        assert_quat_eq(id*id, id);
+
+        let p = Quaternion { x: 1.0, y: 0.0, z: 0.0, w: 0.0 };
+        let q = Quaternion { x: -1.0, y: 0.0, z: 0.0, w: 0.0 };
+        assert_quat_eq(q*id, q);
+        assert_quat_eq(p*q, id);
+
+        let mut q = Quaternion::identity();
+        let p = Quaternion::from_axis_angle(1.0, 0.0, 0.0, PI);
+        q *= p;
+        assert_rot_eq(q, p);
+        q *= p;
+        assert_rot_eq(q, id);
+
+        let q = Quaternion::from_axis_angle(0.0, -1.0, 0.0, 0.2);
+        let p = Quaternion::from_euler_angles(0.5, 0.1, 0.9);
+        assert_rot_eq(p*q, Quaternion { x: 0.17043722, y: 0.3202073, z: 0.185343, w: 0.9132724 });
+    }
+
+    #[test]
+    fn test_into_matrix() {
+        fn assert_matrix_eq(a: [[f32; 4]; 4], b: [[f32; 4]; 4]) {
+            for i in 0..4 {
+                for j in 0..4 {
+                    assert_feq(a[i][j], b[i][j]);
+                }
+            }
+        }
+
+        let q = Quaternion::identity();
+        let m: [[f32; 4]; 4] = q.into_matrix().into();
+        assert_matrix_eq(m, [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0]]);
+
+        // Upside down rotation
+        let q = Quaternion::from_axis_angle(0.0, 1.0, 0.0, PI/2.0);
+        let m: [[f32; 4]; 4] = q.into_matrix().into();
+        assert_matrix_eq(m, [[0.0, 0.0, -1.0, 0.0], [0.0, 1.0, 0.0, 0.0], [1.0, 0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0]]);
+    }
+
+    #[test]
+    fn test_rotate_vector() {
+        use nalgebra::base::Vector3;
+
+        let v = Vector3::new(1.0, 2.0, 3.0);
+        assert_eq!(Quaternion::identity().rotate_vector(v), v);
+
+        // A quarter turn around Y takes +X to -Z.
+        let q = Quaternion::from_axis_angle(0.0, 1.0, 0.0, PI / 2.0);
+        let r = q.rotate_vector(Vector3::new(1.0, 0.0, 0.0));
+        assert_feq(r[0], 0.0);
+        assert_feq(r[1], 0.0);
+        assert_feq(r[2], -1.0);
+
+        // Should agree with the rotation matrix.
+        let q = Quaternion::from_euler_angles(0.3, -1.2, 2.0);
+        let r = q.rotate_vector(v);
+        let m = q.into_matrix();
+        for i in 0..3 {
+            assert_feq(r[i], m[(i,0)] * v[0] + m[(i,1)] * v[1] + m[(i,2)] * v[2]);
+        }
+    }
+
+    #[test]
+    fn test_look_rotation() {
+        use nalgebra::base::Vector3;
+
+        let q = Quaternion::look_rotation(Vector3::new(0.0, 0.0, -1.0), Vector3::new(0.0, 1.0, 0.0));
+        assert_rot_eq(q, Quaternion::identity());
+
+        // Looking straight down, with -Z towards the top of the view.
+        let q = Quaternion::look_rotation(Vector3::new(0.0, -2.0, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        assert_rot_eq(q, Quaternion::from_axis_angle(1.0, 0.0, 0.0, -PI / 2.0));
+
+        let forward = Vector3::new(1.0, 2.0, -0.5).normalize();
+        let q = Quaternion::look_rotation(forward, Vector3::new(0.0, 1.0, 0.0));
+        let v = q.rotate_vector(Vector3::new(0.0, 0.0, -1.0));
+        for i in 0..3 {
+            assert_feq(v[i], forward[i]);
+        }
+        // The right axis stays horizontal.
+        assert_feq(q.rotate_vector(Vector3::new(1.0, 0.0, 0.0))[1], 0.0);
+    }
+
+    #[test]
+    fn test_from_two_vectors() {
+        use nalgebra::base::Vector3;
+
+        let q = Quaternion::from_two_vectors(Vector3::new(0.0, 0.0, 1.0), Vector3::new(2.0, 0.0, 0.0));
+        assert_rot_eq(q, Quaternion::from_axis_angle(0.0, 1.0, 0.0, PI / 2.0));
+
+        let from = Vector3::new(1.0, 2.0, -0.5);
+        let to = Vector3::new(-3.0, 0.5, 1.0);
+        let q = Quaternion::from_two_vectors(from, to);
+        assert!((q.rotate_vector(from.normalize()) - to.normalize()).norm() < 1.0e-5);
+        // The smallest rotation turns no further than the angle between them.
+        assert!((Quaternion::identity().angle_between(q) - from.angle(&to)).abs() < 1.0e-5);
+
+        assert_rot_eq(Quaternion::from_two_vectors(from, from * 3.0), Quaternion::identity());
+        // Opposite directions still turn one to the other, by a half turn.
+        for &from in &[from, Vector3::new(-1.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 1.0)] {
+            let q = Quaternion::from_two_vectors(from, -from);
+            assert!((q.rotate_vector(from.normalize()) + from.normalize()).norm() < 1.0e-5, "{:?}", from);
+            assert_feq(q.len(), 1.0);
+        }
+    }
+
+    #[test]
+    fn test_slerp() {
+        let p = Quaternion::identity();
+        let q = Quaternion::from_euler_angles(0.0, 0.0, 5.0 * PI);
+        let r = Quaternion::from_axis_angle(0.0, 1.0, 0.0, PI/2.0);
+        assert_rot_eq(p.slerp(q, 0.5), r);
+
+        let p = Quaternion::from_euler_angles(0.2, 0.4, 3.1 * PI);
+        let p = Quaternion::from_euler_angles(0.9, 0.2, -120.0);
+        assert_rot_eq(p.slerp(q, 0.9), Quaternion { x: 0.0060857176, y: 0.9921862, z: -0.05777763, w: -0.11041405 });
+    }
+
+    #[test]
+    fn test_dot() {
+        let q = Quaternion::from_axis_angle(0.0, 1.0, 0.0, PI/2.0);
+        assert_feq(q.dot(q), 1.0);
+        assert_feq(Quaternion::identity().dot(q), (PI/4.0).cos());
+        let negated = -q;
+        assert_feq(q.dot(negated), -1.0);
+    }
+
+    #[test]
+    fn test_angle_between() {
+        let p = Quaternion::from_axis_angle(1.0, 0.0, 0.0, 0.5);
+        let q = Quaternion::from_axis_angle(1.0, 0.0, 0.0, 2.0);
+        assert_feq(p.angle_between(p), 0.0);
+        assert!((p.angle_between(q) - 1.5).abs() < 1.0e-5);
+        // -q is the same rotation, so it's no further away.
+        let negated = -q;
+        assert!((p.angle_between(negated) - 1.5).abs() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_rotation_6d() {
+        let r = Quaternion::identity().to_rotation_6d();
+        for (a, b) in r.iter().zip([1.0, 0.0, 0.0, 0.0, 1.0, 0.0].iter()) {
+            assert_feq(*a, *b);
+        }
+
+        let q = Quaternion::from_euler_angles(0.3, -1.2, 2.0);
+        assert_rot_eq(Quaternion::from_rotation_6d(q.to_rotation_6d()), q);
+
+        // Columns that are scaled and skewed are straightened out.
+        let q = Quaternion::from_rotation_6d([2.0, 0.0, 0.0, 1.0, 3.0, 0.0]);
+        assert_rot_eq(q, Quaternion::identity());
+    }
+
+    #[test]
+    fn test_canonical() {
+        use nalgebra::base::Vector3;
+
+        let v = Vector3::new(1.0, 2.0, 3.0);
+        let rotations = [
+            Quaternion::identity(),
+            Quaternion::from_axis_angle(0.0, 0.0, 1.0, 1.5 * PI),
+            Quaternion::from_euler_angles(0.3, -1.2, 2.0),
+            -Quaternion::from_euler_angles(0.3, -1.2, 2.0),
+            Quaternion { x: -1.0, y: 0.0, z: 0.0, w: 0.0 },
+        ];
+        for q in &rotations {
+            let canonical = q.canonical();
+            assert!(canonical.w >= 0.0, "{:?}", canonical);
+            assert!((canonical.rotate_vector(v) - q.rotate_vector(v)).norm() < 1.0e-5);
+            assert_quat_eq(canonical.canonical(), canonical);
+        }
+        // q and -q share one representative.
+        assert_quat_eq(rotations[2].canonical(), rotations[3].canonical());
+        assert_quat_eq(rotations[4].canonical(), Quaternion { x: 1.0, y: 0.0, z: 0.0, w: 0.0 });
+    }
+
+    #[test]
+    fn test_scaled() {
+        let q = Quaternion::from_axis_angle(0.0, 0.0, 1.0, 1.2);
+        assert_rot_eq(q.scaled(0.0), Quaternion::identity());
+        assert_rot_eq(q.scaled(1.0), q);
+        assert_rot_eq(q.scaled(0.5), Quaternion::from_axis_angle(0.0, 0.0, 1.0, 0.6));
+
+        let q = Quaternion::from_axis_angle(0.0, 0.0, 1.0, 1.5 * PI);
+        assert_rot_eq(q.scaled(1.0), q);
+        assert_rot_eq(q.scaled(0.5), Quaternion::from_axis_angle(0.0, 0.0, 1.0, -0.25 * PI));
+    }
+
+    #[test]
+    fn test_pow() {
+        let q = Quaternion::from_axis_angle(0.0, 1.0, 0.0, 1.2);
+        assert_rot_eq(q.pow(0.5), q.scaled(0.5));
+        assert_rot_eq(q.pow(2.0), Quaternion::from_axis_angle(0.0, 1.0, 0.0, 2.4));
+        assert_rot_eq(q.pow(-1.0), Quaternion::from_axis_angle(0.0, 1.0, 0.0, -1.2));
+
+        // Halving over and over closes in on the identity, and doubling undoes each halving.
+        let mut halved = q;
+        for _ in 0..10 {
+            let half = halved.scaled(0.5);
+            assert_rot_eq(half.pow(2.0), halved);
+            halved = half;
+        }
+        assert!(halved.angle_between(Quaternion::identity()) < 1.2 / 1000.0);
+        assert_rot_eq(Quaternion::identity().pow(2.0), Quaternion::identity());
+    }
+
+    #[test]
+    fn test_integrate_rk4() {
+        use nalgebra::base::Vector3;
+
+        let omega = Vector3::new(0.3, -1.0, 2.0);
+        let start = Quaternion::from_euler_angles(0.3, -1.2, 2.0);
+        let dt = 0.1;
+        // Turning at a constant rate is a rotation about a fixed axis, applied in world space.
+        let axis = omega.normalize();
+        let exact = Quaternion::from_axis_angle(axis[0], axis[1], axis[2], omega.norm() * dt) * start;
+
+        // Ten explicit Euler steps of q += dq/dt * h, for comparison.
+        const EULER_STEPS: usize = 10;
+        let h = dt / EULER_STEPS as f32;
+        let mut euler = start;
+        for _ in 0..EULER_STEPS {
+            let rate = euler.derivative(omega);
+            euler.x += h * rate.x;
+            euler.y += h * rate.y;
+            euler.z += h * rate.z;
+            euler.w += h * rate.w;
+            euler.normalize();
+        }
+
+        let rk4 = start.integrate_rk4(omega, dt);
+        assert_feq(rk4.len(), 1.0);
+        let (rk4_error, euler_error) = (rk4.angle_between(exact), euler.angle_between(exact));
+        assert!(rk4_error <= euler_error, "RK4 is off by {}, Euler by {}", rk4_error, euler_error);
+        assert!(rk4_error < 1.0e-3, "RK4 is off by {}", rk4_error);
+
+        assert_quat_eq(start.integrate_rk4(Vector3::zeros(), dt), start);
+    }
+
+    #[test]
+    fn test_derivative() {
+        use nalgebra::base::Vector3;
+
+        let omega = Vector3::new(-0.7, 0.4, 1.5);
+        let q = Quaternion::from_euler_angles(1.0, 0.5, -0.3);
+        let rate = q.derivative(omega);
+        // Half the angular speed, as a unit quaternion turns through twice its own angle.
+        assert_feq(rate.len(), 0.5 * omega.norm());
+
+        // A forward difference over a short step of the integrator.
+        let dt = 1.0e-3;
+        let next = q.integrate_rk4(omega, dt);
+        assert!(((next.x - q.x) / dt - rate.x).abs() < 1.0e-2);
+        assert!(((next.y - q.y) / dt - rate.y).abs() < 1.0e-2);
+        assert!(((next.z - q.z) / dt - rate.z).abs() < 1.0e-2);
+        assert!(((next.w - q.w) / dt - rate.w).abs() < 1.0e-2);
+
+        assert_feq(q.derivative(Vector3::zeros()).len(), 0.0);
+    }
+
+    #[test]
+    fn test_midpoint() {
+        let pairs = [
+            (Quaternion::identity(), Quaternion::from_axis_angle(0.0, 1.0, 0.0, 1.0)),
+            (Quaternion::from_euler_angles(0.3, -1.2, 2.0), Quaternion::from_euler_angles(-2.0, 0.4, 0.1)),
+            // Far apart on the 3-sphere, and too close together to slerp precisely.
+            (Quaternion::identity(), Quaternion::from_axis_angle(1.0, 0.0, 0.0, 1.9 * PI)),
+            (Quaternion::identity(), Quaternion::from_axis_angle(1.0, 0.0, 0.0, 1.0e-4)),
+        ];
+        for &(p, q) in &pairs {
+            let midpoint = p.midpoint(q);
+            assert!([midpoint.x, midpoint.y, midpoint.z, midpoint.w].iter().all(|c| c.is_finite()));
+            let (to_p, to_q) = (midpoint.angle_between(p), midpoint.angle_between(q));
+            assert!((to_p - to_q).abs() < 1.0e-3, "{} {}", to_p, to_q);
+            assert!((to_p + to_q - p.angle_between(q)).abs() < 1.0e-3);
+        }
+    }
+
+    #[test]
+    fn test_weighted_blend() {
+        let p = Quaternion::from_euler_angles(0.3, -1.2, 2.0);
+        let q = Quaternion::from_axis_angle(0.0, 1.0, 0.0, 1.0);
+        assert_quat_eq(Quaternion::weighted_blend(&[(p, 2.0), (q, 2.0)]), p.nlerp(q, 0.5));
+        assert_quat_eq(Quaternion::weighted_blend(&[(p, 1.0), (q, 3.0)]), p.nlerp(q, 0.75));
+
+        // The sign of each quaternion doesn't change the blend.
+        let negated = -q;
+        assert_quat_eq(Quaternion::weighted_blend(&[(p, 1.0), (negated, 1.0)]), p.nlerp(q, 0.5));
+
+        assert_quat_eq(Quaternion::weighted_blend(&[]), Quaternion::identity());
+        assert_quat_eq(Quaternion::weighted_blend(&[(p, 0.0), (q, 0.0)]), Quaternion::identity());
+        assert_quat_eq(Quaternion::weighted_blend(&[(p, 1.0), (q, -1.0)]), Quaternion::identity());
+    }
+
+    #[test]
+    fn test_average() {
+        assert_quat_eq(Quaternion::average(&[]), Quaternion::identity());
+
+        let q = Quaternion::from_euler_angles(0.3, -1.2, 2.0);
+        assert_quat_eq(Quaternion::average(&[q, q, q]), q);
+
+        // -q is the same rotation, so it doesn't cancel q out.
+        let negated = -q;
+        assert_quat_eq(Quaternion::average(&[q, negated]), q);
+
+        // Turns either side of a rotation about the same axis average to it.
+        let turns = [0.4, 0.6, 0.45, 0.55];
+        let qs: Vec<Quaternion> = turns.iter().map(|&angle| Quaternion::from_axis_angle(0.0, 1.0, 0.0, angle)).collect();
+        assert_quat_eq(Quaternion::average(&qs), Quaternion::from_axis_angle(0.0, 1.0, 0.0, 0.5));
+    }
+
+    // Property tests, run over random inputs by quickcheck.
+    mod properties {
+        use super::super::*;
+        use ::std::f32::consts::PI;
+        use nalgebra::base::Vector3;
+        use quickcheck::{quickcheck, TestResult};
+
+        const TOLERANCE: f32 = 1.0e-4;
+
+        // quickcheck picks floats within ±100, so this maps them onto an arbitrary range instead.
+        fn in_range(x: f32, min: f32, max: f32) -> f32 {
+            min + (max - min) * (x.sin() * 0.5 + 0.5)
+        }
+
+        // Builds a unit quaternion, or returns `None` if the axis is too short to have a direction.
+        fn rotation(x: f32, y: f32, z: f32, angle: f32) -> Option<Quaternion> {
+            let axis = Vector3::new(x, y, z);
+            if axis.norm() < 1.0e-3 {
+                return None;
+            }
+            let axis = axis.normalize();
+            Some(Quaternion::from_axis_angle(axis[0], axis[1], axis[2], angle))
+        }
+
+        fn near(a: Quaternion, b: Quaternion) -> bool {
+            (a.x-b.x).abs() < TOLERANCE && (a.y-b.y).abs() < TOLERANCE
+                && (a.z-b.z).abs() < TOLERANCE && (a.w-b.w).abs() < TOLERANCE
+        }
+
+        // Both q and -q represent the same rotation.
+        fn same_rotation(a: Quaternion, b: Quaternion) -> bool {
+            near(a, b) || near(a, -b)
+        }
+
+        #[test]
+        fn prop_normalize_is_idempotent() {
+            fn prop(x: f32, y: f32, z: f32, w: f32) -> TestResult {
+                let mut once = Quaternion { x, y, z, w };
+                if once.len() < 1.0e-3 {
+                    return TestResult::discard();
+                }
+                once.normalize();
+                let mut twice = once;
+                twice.normalize();
+                TestResult::from_bool((once.len() - 1.0).abs() < TOLERANCE && near(once, twice))
+            }
+            quickcheck(prop as fn(f32, f32, f32, f32) -> TestResult);
+        }
+
+        #[test]
+        fn prop_mul_by_conjugate_is_identity() {
+            fn prop(x: f32, y: f32, z: f32, angle: f32) -> TestResult {
+                match rotation(x, y, z, angle) {
+                    Some(q) => {
+                        let conjugate = q.conjugate();
+                        TestResult::from_bool(near(q * conjugate, Quaternion::identity())
+                            && near(conjugate * q, Quaternion::identity()))
+                    }
+                    None => TestResult::discard(),
+                }
+            }
+            quickcheck(prop as fn(f32, f32, f32, f32) -> TestResult);
+        }
+
+        #[test]
+        fn prop_rotate_vector_preserves_length() {
+            fn prop(x: f32, y: f32, z: f32, angle: f32, vector: (f32, f32, f32)) -> TestResult {
+                match rotation(x, y, z, angle) {
+                    Some(q) => {
+                        let vector = Vector3::new(vector.0, vector.1, vector.2);
+                        let error = (q.rotate_vector(vector).norm() - vector.norm()).abs();
+                        TestResult::from_bool(error < TOLERANCE * (1.0 + vector.norm()))
+                    }
+                    None => TestResult::discard(),
+                }
+            }
+            quickcheck(prop as fn(f32, f32, f32, f32, (f32, f32, f32)) -> TestResult);
+        }
+
+        #[test]
+        fn prop_slerp_endpoints() {
+            fn prop(a: (f32, f32, f32, f32), b: (f32, f32, f32, f32)) -> TestResult {
+                match (rotation(a.0, a.1, a.2, a.3), rotation(b.0, b.1, b.2, b.3)) {
+                    (Some(p), Some(q)) => TestResult::from_bool(same_rotation(p.slerp(q, 0.0), p)
+                        && same_rotation(p.slerp(q, 1.0), q)),
+                    _ => TestResult::discard(),
+                }
+            }
+            quickcheck(prop as fn((f32, f32, f32, f32), (f32, f32, f32, f32)) -> TestResult);
+        }
+
+        #[test]
+        fn prop_euler_angles_round_trip() {
+            // Away from the ±π wrap of yaw and roll, and the gimbal lock at a pitch of ±π/2.
+            fn prop(yaw: f32, pitch: f32, roll: f32) -> bool {
+                let (yaw, pitch, roll) = (in_range(yaw, -3.1, 3.1), in_range(pitch, -1.5, 1.5), in_range(roll, -3.1, 3.1));
+                let (new_yaw, new_pitch, new_roll) = Quaternion::from_euler_angles(yaw, pitch, roll).to_euler_angles();
+                const ANGLE_TOLERANCE: f32 = 1.0e-3;
+                (new_yaw - yaw).abs() < ANGLE_TOLERANCE && (new_pitch - pitch).abs() < ANGLE_TOLERANCE
+                    && (new_roll - roll).abs() < ANGLE_TOLERANCE
+            }
+            quickcheck(prop as fn(f32, f32, f32) -> bool);
+        }
+
+        #[test]
+        fn prop_rotation_6d_round_trip() {
+            fn prop(x: f32, y: f32, z: f32, angle: f32) -> TestResult {
+                match rotation(x, y, z, angle) {
+                    Some(q) => TestResult::from_bool(same_rotation(Quaternion::from_rotation_6d(q.to_rotation_6d()), q)),
+                    None => TestResult::discard(),
+                }
+            }
+            quickcheck(prop as fn(f32, f32, f32, f32) -> TestResult);
+        }
+
+        #[test]
+        fn prop_axis_angle_round_trip() {
+            // Away from the identity, where the axis is lost.
+            fn prop(x: f32, y: f32, z: f32, angle: f32) -> TestResult {
+                let angle = in_range(angle, 0.1, 2.0 * PI - 0.1);
+                match rotation(x, y, z, angle) {
+                    Some(q) => {
+                        let (axis, new_angle) = q.to_axis_angle();
+                        let expected = Vector3::new(x, y, z).normalize();
+                        TestResult::from_bool((new_angle - angle).abs() < TOLERANCE
+                            && (axis - expected).norm() < TOLERANCE)
+                    }
+                    None => TestResult::discard(),
+                }
+            }
+            quickcheck(prop as fn(f32, f32, f32, f32) -> TestResult);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,509 @@
+use nalgebra::base::Vector3;
+use quaternion::Quaternion;
+use rotation::Rotation;
+
+/// Length of each rotation's segment of the animation, in seconds.
+pub const SEGMENT_DURATION: f32 = 1.0;
+
+/// How the animation blends between keyframes.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Interpolation {
+    /// At a constant angular speed, along the great arc between the keyframes.
+    Slerp,
+    /// Linearly, then normalized. It follows the same arc, but faster in the middle than at
+    /// the ends, by more the further apart the keyframes are.
+    Nlerp,
+}
+
+impl Interpolation {
+    pub fn interpolate<R: Rotation>(self, from: R, to: R, t: f32) -> R {
+        match self {
+            Interpolation::Slerp => from.slerp(to, t),
+            Interpolation::Nlerp => from.nlerp(to, t),
+        }
+    }
+}
+
+/// How far the animation has played, as the segment being animated and the fraction through it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Playback {
+    pub playing: bool,
+    pub index: usize,
+    pub t: f32,
+}
+
+impl Playback {
+    pub fn new() -> Playback {
+        Playback { playing: false, index: 0, t: 0.0 }
+    }
+
+    /// Plays from the start of the animation. With no segments there's nothing to play,
+    /// so the playback finishes straight away.
+    pub fn start(&mut self, num_segments: usize) {
+        self.playing = num_segments > 0;
+        self.index = 0;
+        self.t = 0.0;
+        debug!("Started the animation with {} segments", num_segments);
+    }
+
+    /// Stops playing wherever the animation has got to.
+    pub fn stop(&mut self) {
+        if self.playing {
+            debug!("Stopped the animation in segment {}", self.index);
+        }
+        self.playing = false;
+    }
+
+    /// Seconds since the start of the animation.
+    pub fn elapsed(&self) -> f32 {
+        (self.index as f32 + self.t) * SEGMENT_DURATION
+    }
+
+    /// Advances by `dt` seconds, and stops once the last of `num_segments` segments is done.
+    /// The number of segments may have shrunk since the playback started.
+    pub fn advance(&mut self, dt: f32, num_segments: usize) {
+        if !self.playing {
+            return;
+        }
+
+        let index = self.index;
+        self.t += dt / SEGMENT_DURATION;
+        while self.t >= 1.0 {
+            self.index += 1;
+            self.t -= 1.0;
+        }
+        if self.index >= num_segments {
+            debug!("Finished the animation");
+            self.playing = false;
+        } else if self.index != index {
+            debug!("Animating segment {} of {}", self.index + 1, num_segments);
+        }
+    }
+
+    /// Returns the orientation of `rotations` at this point of the animation, or all of them
+    /// composed when not playing. A list shorter than the animation stays at its final
+    /// orientation.
+    pub fn current<R: Rotation>(&self, rotations: &[R]) -> Quaternion {
+        self.current_with(rotations, Interpolation::Slerp)
+    }
+
+    /// Like `current`, blending the segment being animated with `interpolation`.
+    pub fn current_with<R: Rotation>(&self, rotations: &[R], interpolation: Interpolation) -> Quaternion {
+        let segments = segments(rotations);
+        if self.playing && self.index < segments.len() {
+            return interpolated_orientation_at(&segments, self.index, self.t, interpolation);
+        }
+
+        R::compose(rotations).to_quaternion()
+    }
+
+    /// Whether the segment of `rotations` being animated takes the short way round, with its
+    /// slerp negating the keyframe it ends at. See `keyframe_dots`.
+    pub fn negates_endpoint<R: Rotation>(&self, rotations: &[R]) -> bool {
+        self.playing && keyframe_dots(rotations).get(self.index).map_or(false, |&dot| dot < 0.0)
+    }
+}
+
+/// Returns the rotations that make up the animation's segments. Identity rotations, like the one
+/// left at the end of the list by "Add Rotation", would only animate as pauses and are skipped,
+/// whichever sign they're stored with.
+pub fn segments<R: Rotation>(rotations: &[R]) -> Vec<R> {
+    rotations.iter().cloned().filter(|&q| !q.is_identity()).collect()
+}
+
+/// Returns `q`, or its negation if `q` is on the far side of the 3-sphere from `previous`. Either
+/// way it's the same rotation.
+pub fn align_sign<R: Rotation>(q: R, previous: R) -> R {
+    if previous.dot(q) < 0.0 { q.negated() } else { q }
+}
+
+/// Flips the signs of the rotations so each has a non-negative dot product with the one before
+/// it, keeping the list on one side of the 3-sphere without changing any of its rotations. The
+/// rotations flagged in `pinned` are left as they are, and any past its end aren't pinned.
+pub fn align_signs<R: Rotation>(rotations: &mut [R], pinned: &[bool]) {
+    for i in 1..rotations.len() {
+        if !pinned.get(i).cloned().unwrap_or(false) {
+            rotations[i] = align_sign(rotations[i], rotations[i - 1]);
+        }
+    }
+}
+
+/// Returns the composed orientation while animating segment `index`, `t` of the way through.
+/// The rotations before `index` are fully applied, and rotation `index` is slerped in.
+pub fn orientation_at<R: Rotation>(rotations: &[R], index: usize, t: f32) -> Quaternion {
+    interpolated_orientation_at(rotations, index, t, Interpolation::Slerp)
+}
+
+/// Like `orientation_at`, blending rotation `index` in with `interpolation`.
+pub fn interpolated_orientation_at<R: Rotation>(rotations: &[R], index: usize, t: f32, interpolation: Interpolation) -> Quaternion {
+    interpolation.interpolate(R::compose(&rotations[..index]), R::compose(&rotations[..=index]), t).to_quaternion()
+}
+
+/// Returns the dot product between each pair of consecutive orientations that the animation
+/// slerps between, starting from the identity. A negative one means the orientations are more than
+/// half the 3-sphere apart, so the slerp negates one of them and turns the short way round instead.
+pub fn keyframe_dots<R: Rotation>(rotations: &[R]) -> Vec<f32> {
+    let mut orientation = R::identity();
+    segments(rotations).iter()
+        .map(|&q| {
+            let next = orientation.then(q);
+            let dot = orientation.dot(next);
+            orientation = next;
+            dot
+        })
+        .collect()
+}
+
+/// Returns the axis and angle that each of `rotations` turns the model about in the world. Each
+/// rotation turns about its axis in the frame the rotations before it leave the model in, so its
+/// axis is turned by the orientation composed up to it.
+pub fn world_axes<R: Rotation>(rotations: &[R]) -> Vec<(Vector3<f32>, f32)> {
+    let mut orientation = R::identity();
+    rotations.iter()
+        .map(|&q| {
+            let (axis, angle) = q.to_axis_angle();
+            let world_axis = orientation.rotate_vector(axis);
+            orientation = orientation.then(q);
+            (world_axis, angle)
+        })
+        .collect()
+}
+
+/// Returns what the rotation at `index` becomes to aim the model, turning its local `forward` to
+/// the world direction `target` by the smallest rotation, with the others left as they are. The
+/// rotations after it turn `forward` first, and the turn is made in the frame the rotations
+/// before it leave the model in, where `target` is turned back by their inverse.
+pub fn aimed_rotation<R: Rotation>(rotations: &[R], index: usize, forward: Vector3<f32>, target: Vector3<f32>) -> Quaternion {
+    let edited = rotations[index];
+    let prior_inverse = R::compose(&rotations[..index]).inverse();
+    let forward = R::compose(&rotations[index + 1..]).rotate_vector(forward);
+    let turn = Quaternion::from_two_vectors(edited.rotate_vector(forward), prior_inverse.rotate_vector(target));
+    R::from(turn).then(edited).to_quaternion()
+}
+
+/// Returns `num_points` points, evenly spaced in `t`, along the path that `v` follows while
+/// slerping from `from` to `to`. For a unit `v` it's an arc of a great circle on the unit sphere.
+pub fn slerp_path(from: Quaternion, to: Quaternion, v: Vector3<f32>, num_points: usize) -> Vec<Vector3<f32>> {
+    (0..num_points)
+        .map(|i| {
+            let t = if num_points > 1 { i as f32 / (num_points - 1) as f32 } else { 0.0 };
+            from.slerp(to, t).rotate_vector(v)
+        })
+        .collect()
+}
+
+/// Samples the whole animation at `fps` frames per second, returning `(time, orientation)` pairs.
+/// The last sample is always the fully composed rotation.
+pub fn sample<R: Rotation>(rotations: &[R], fps: f32) -> Vec<(f32, Quaternion)> {
+    let rotations = &segments(rotations)[..];
+    if rotations.is_empty() {
+        return vec![(0.0, Quaternion::identity())];
+    }
+
+    let duration = rotations.len() as f32 * SEGMENT_DURATION;
+    let num_frames = (duration * fps).ceil() as usize;
+
+    let mut samples = Vec::with_capacity(num_frames + 1);
+    for frame in 0..num_frames {
+        let time = frame as f32 / fps;
+        let index = (time / SEGMENT_DURATION) as usize;
+        let t = (time - index as f32 * SEGMENT_DURATION) / SEGMENT_DURATION;
+        samples.push((time, orientation_at(rotations, index, t)));
+    }
+    samples.push((duration, orientation_at(rotations, rotations.len() - 1, 1.0)));
+    samples
+}
+
+/// Samples the slerp from `start` to `end` at `count` evenly spaced points, including both ends,
+/// returning `(t, orientation)` pairs.
+pub fn sample_slerp(start: Quaternion, end: Quaternion, count: usize) -> Vec<(f32, Quaternion)> {
+    (0..count)
+        .map(|i| {
+            let t = if count > 1 { i as f32 / (count - 1) as f32 } else { 0.0 };
+            (t, start.slerp(end, t))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::std::f32::consts::PI;
+
+    fn assert_quat_near(a: Quaternion, b: Quaternion) {
+        assert!((a.x-b.x).abs() < 1.0e-6 && (a.y-b.y).abs() < 1.0e-6
+            && (a.z-b.z).abs() < 1.0e-6 && (a.w-b.w).abs() < 1.0e-6, "Not equal: {:?} {:?}", a, b);
+    }
+
+    #[test]
+    fn test_orientation_at() {
+        let rotations = vec![
+            Quaternion::from_axis_angle(1.0, 0.0, 0.0, PI / 2.0),
+            Quaternion::from_axis_angle(0.0, 1.0, 0.0, PI / 2.0),
+        ];
+        assert_quat_near(orientation_at(&rotations, 0, 0.0), Quaternion::identity());
+        assert_quat_near(orientation_at(&rotations, 0, 0.5), Quaternion::from_axis_angle(1.0, 0.0, 0.0, PI / 4.0));
+        assert_quat_near(orientation_at(&rotations, 0, 1.0), rotations[0]);
+        assert_quat_near(orientation_at(&rotations, 1, 0.0), rotations[0]);
+        assert_quat_near(orientation_at(&rotations, 1, 0.5), rotations[0] * Quaternion::from_axis_angle(0.0, 1.0, 0.0, PI / 4.0));
+        assert_quat_near(orientation_at(&rotations, 1, 1.0), rotations[0] * rotations[1]);
+    }
+
+    #[test]
+    fn test_interpolation() {
+        let rotations = vec![Quaternion::from_axis_angle(0.0, 0.0, 1.0, 2.0 * PI / 3.0)];
+        let angle_at = |t, interpolation| {
+            Quaternion::identity().angle_between(interpolated_orientation_at(&rotations, 0, t, interpolation))
+        };
+        for &interpolation in &[Interpolation::Slerp, Interpolation::Nlerp] {
+            assert!(angle_at(0.0, interpolation).abs() < 1.0e-3);
+            assert!((angle_at(0.5, interpolation) - PI / 3.0).abs() < 1.0e-3);
+            assert!((angle_at(1.0, interpolation) - 2.0 * PI / 3.0).abs() < 1.0e-3);
+        }
+        // A quarter of the way through, nlerp is still behind slerp's steady quarter.
+        assert!((angle_at(0.25, Interpolation::Slerp) - PI / 6.0).abs() < 1.0e-3);
+        assert!(angle_at(0.25, Interpolation::Nlerp) < PI / 6.0 - 0.03);
+    }
+
+    #[test]
+    fn test_keyframe_dots() {
+        assert!(keyframe_dots(&[Quaternion::identity()]).is_empty());
+
+        let rotations = vec![
+            Quaternion::from_axis_angle(1.0, 0.0, 0.0, PI / 2.0),
+            Quaternion::identity(),
+            // Three quarters of a turn, which the slerp takes as a quarter turn back.
+            Quaternion::from_axis_angle(0.0, 1.0, 0.0, 3.0 * PI / 2.0),
+        ];
+        let dots = keyframe_dots(&rotations);
+        assert_eq!(dots.len(), 2);
+        assert!((dots[0] - (PI / 4.0).cos()).abs() < 1.0e-6);
+        assert!((dots[1] - (3.0 * PI / 4.0).cos()).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn test_world_axes() {
+        let rotations = vec![
+            Quaternion::from_axis_angle(0.0, 1.0, 0.0, PI / 2.0),
+            // About the model's X axis, which the first rotation has turned to -Z.
+            Quaternion::from_axis_angle(1.0, 0.0, 0.0, PI / 4.0),
+        ];
+        let axes = world_axes(&rotations);
+        assert_eq!(axes.len(), 2);
+        assert!((axes[0].0 - Vector3::y()).norm() < 1.0e-6 && (axes[0].1 - PI / 2.0).abs() < 1.0e-6);
+        assert!((axes[1].0 - Vector3::new(0.0, 0.0, -1.0)).norm() < 1.0e-6 && (axes[1].1 - PI / 4.0).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn test_aimed_rotation() {
+        let mut rotations = vec![
+            Quaternion::from_axis_angle(0.0, 1.0, 0.0, PI / 2.0),
+            Quaternion::from_euler_angles(0.3, -1.2, 2.0),
+        ];
+        let forward = Vector3::z();
+        for &target in &[Vector3::new(1.0, 2.0, -0.5).normalize(), Vector3::y()] {
+            let last = rotations.len() - 1;
+            rotations[last] = aimed_rotation(&rotations, last, forward, target);
+            let aimed = Quaternion::compose(&rotations).rotate_vector(forward);
+            assert!((aimed - target).norm() < 1.0e-5, "{:?}", target);
+        }
+
+        // Aiming it where it already points, or straight back, still ends up pointing there.
+        let pointing = Quaternion::compose(&rotations).rotate_vector(forward);
+        for &target in &[pointing, -pointing] {
+            let aimed = aimed_rotation(&rotations, 1, forward, target);
+            assert!((Quaternion::compose(&[rotations[0], aimed]).rotate_vector(forward) - target).norm() < 1.0e-5);
+        }
+
+        // Aiming one in the middle leaves the ones after it turning the model after it.
+        rotations.push(Quaternion::from_axis_angle(1.0, 0.0, 0.0, 0.7));
+        let target = Vector3::new(-0.3, 0.4, 1.0).normalize();
+        rotations[1] = aimed_rotation(&rotations, 1, forward, target);
+        assert!((Quaternion::compose(&rotations).rotate_vector(forward) - target).norm() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_slerp_path() {
+        let from = Quaternion::identity();
+        let to = Quaternion::from_axis_angle(0.0, 0.0, 1.0, PI / 2.0);
+        let path = slerp_path(from, to, Vector3::x(), 5);
+        assert_eq!(path.len(), 5);
+        assert!((path[0] - Vector3::x()).norm() < 1.0e-6);
+        assert!((path[4] - Vector3::y()).norm() < 1.0e-6);
+        // The arc stays on the sphere, where the chord between its ends would cut inside it.
+        assert!(path.iter().all(|point| (point.norm() - 1.0).abs() < 1.0e-5));
+        assert!((path[2] - Vector3::new(1.0, 1.0, 0.0).normalize()).norm() < 1.0e-5);
+        // A single point is the start of the path.
+        let path = slerp_path(from, to, Vector3::x(), 1);
+        assert_eq!(path.len(), 1);
+        assert!((path[0] - Vector3::x()).norm() < 1.0e-6);
+    }
+
+    #[test]
+    fn test_playback() {
+        let mut playback = Playback::new();
+        playback.start(2);
+        playback.advance(0.5, 2);
+        assert_eq!(playback, Playback { playing: true, index: 0, t: 0.5 });
+        playback.advance(1.0, 2);
+        assert_eq!(playback, Playback { playing: true, index: 1, t: 0.5 });
+        assert!((playback.elapsed() - 1.5 * SEGMENT_DURATION).abs() < 1.0e-6);
+        playback.advance(0.5, 2);
+        assert!(!playback.playing);
+
+        // The rotations were cleared while playing.
+        playback.start(2);
+        playback.advance(0.5, 0);
+        assert!(!playback.playing);
+
+        // Stopping leaves it where it got to.
+        playback.start(2);
+        playback.advance(1.25, 2);
+        playback.stop();
+        assert_eq!(playback, Playback { playing: false, index: 1, t: 0.25 });
+        playback.advance(0.5, 2);
+        assert_eq!(playback, Playback { playing: false, index: 1, t: 0.25 });
+    }
+
+    #[test]
+    fn test_playback_single_rotation() {
+        // A list holding only the initial identity has nothing to animate.
+        let rotations = vec![Quaternion::identity()];
+        let mut playback = Playback::new();
+        playback.start(segments(&rotations).len());
+        assert!(!playback.playing);
+
+        let rotations = vec![Quaternion::from_axis_angle(0.0, 1.0, 0.0, PI / 2.0)];
+        let num_segments = segments(&rotations).len();
+        playback.start(num_segments);
+        let mut frames = 0;
+        while playback.playing {
+            orientation_at(&rotations, playback.index, playback.t);
+            playback.advance(1.0 / 60.0, num_segments);
+            frames += 1;
+            assert!(frames <= 61, "still playing after {} frames", frames);
+        }
+    }
+
+    #[test]
+    fn test_playback_current() {
+        let first = Quaternion::from_axis_angle(1.0, 0.0, 0.0, PI / 2.0);
+        let second = Quaternion::from_axis_angle(0.0, 1.0, 0.0, PI / 2.0);
+        let rotations = vec![first, Quaternion::identity(), second];
+        let mut playback = Playback::new();
+        assert_quat_near(playback.current(&rotations), first * second);
+
+        playback.start(segments(&rotations).len());
+        assert_quat_near(playback.current(&rotations), Quaternion::identity());
+        playback.advance(0.5 * SEGMENT_DURATION, 2);
+        assert_quat_near(playback.current(&rotations), Quaternion::from_axis_angle(1.0, 0.0, 0.0, PI / 4.0));
+        // The identity in the middle takes no time.
+        playback.advance(SEGMENT_DURATION, 2);
+        assert_quat_near(playback.current(&rotations), first * Quaternion::from_axis_angle(0.0, 1.0, 0.0, PI / 4.0));
+
+        // A shorter list compared alongside has already finished.
+        assert_quat_near(playback.current(&[first]), first);
+
+        // Once it's done, it stays at the end.
+        playback.advance(SEGMENT_DURATION, 2);
+        assert!(!playback.playing);
+        assert_quat_near(playback.current(&rotations), first * second);
+        assert_quat_near(playback.current(&[Quaternion::identity()]), Quaternion::identity());
+    }
+
+    #[test]
+    fn test_negates_endpoint() {
+        let rotations = vec![
+            Quaternion::from_axis_angle(1.0, 0.0, 0.0, PI / 2.0),
+            Quaternion::from_axis_angle(0.0, 1.0, 0.0, 3.0 * PI / 2.0),
+        ];
+        let mut playback = Playback::new();
+        assert!(!playback.negates_endpoint(&rotations));
+        playback.start(2);
+        assert!(!playback.negates_endpoint(&rotations));
+        playback.advance(SEGMENT_DURATION, 2);
+        assert!(playback.negates_endpoint(&rotations));
+        // A list compared alongside may have fewer segments.
+        assert!(!playback.negates_endpoint(&rotations[..1]));
+    }
+
+    #[test]
+    fn test_segments() {
+        let turn = Quaternion::from_axis_angle(0.0, 0.0, 1.0, PI / 2.0);
+        let rotations = vec![Quaternion::identity(), turn, Quaternion::identity()];
+        assert_eq!(segments(&rotations), vec![turn]);
+
+        // The identities take no time in the sampled animation.
+        let samples = sample(&rotations, 10.0);
+        assert_eq!(samples.len(), 11);
+        assert_eq!(sample(&[Quaternion::identity()], 10.0), vec![(0.0, Quaternion::identity())]);
+
+        // A negated identity is still no rotation at all.
+        assert_eq!(segments(&[-Quaternion::identity(), turn]), vec![turn]);
+    }
+
+    #[test]
+    fn test_align_signs() {
+        let mut rotations = vec![
+            Quaternion::from_axis_angle(0.0, 0.0, 1.0, PI / 2.0),
+            -Quaternion::from_axis_angle(0.0, 0.0, 1.0, PI / 3.0),
+            Quaternion::from_axis_angle(1.0, 0.0, 0.0, 1.9 * PI),
+            Quaternion::from_euler_angles(0.3, -1.2, 2.0),
+            Quaternion::identity(),
+        ];
+        let original = rotations.clone();
+        align_signs(&mut rotations, &[]);
+        for pair in rotations.windows(2) {
+            assert!(pair[0].dot(pair[1]) >= 0.0, "{:?}", pair);
+        }
+        // Only the signs change, never the rotations.
+        let v = Vector3::new(1.0, 2.0, 3.0);
+        for (aligned, q) in rotations.iter().zip(&original) {
+            assert!((aligned.rotate_vector(v) - q.rotate_vector(v)).norm() < 1.0e-5);
+        }
+        assert_quat_near(rotations[0], original[0]);
+    }
+
+    #[test]
+    fn test_align_signs_pinned() {
+        let turn = Quaternion::from_axis_angle(0.0, 0.0, 1.0, PI / 2.0);
+        let mut rotations = vec![turn, -turn, -turn];
+        align_signs(&mut rotations, &[false, true]);
+        // The pinned one keeps its sign, and the one after it is aligned to that.
+        assert_quat_near(rotations[1], -turn);
+        assert_quat_near(rotations[2], -turn);
+
+        let mut rotations = vec![turn, -turn, turn];
+        align_signs(&mut rotations, &[false, false, true]);
+        assert_quat_near(rotations[1], turn);
+        assert_quat_near(rotations[2], turn);
+    }
+
+    #[test]
+    fn test_sample() {
+        let rotations = vec![
+            Quaternion::from_axis_angle(1.0, 0.0, 0.0, PI / 2.0),
+            Quaternion::from_axis_angle(0.0, 1.0, 0.0, PI / 2.0),
+        ];
+        let samples = sample(&rotations, 30.0);
+        assert_eq!(samples.len(), 61);
+        assert_eq!(samples[0], (0.0, Quaternion::identity()));
+        assert_eq!(samples[30].1, orientation_at(&rotations, 1, 0.0));
+        assert!((samples[60].0 - 2.0).abs() < 1.0e-6);
+        let (end, expected) = (samples[60].1, rotations[0] * rotations[1]);
+        assert!((end.x-expected.x).abs() < 1.0e-6 && (end.y-expected.y).abs() < 1.0e-6
+            && (end.z-expected.z).abs() < 1.0e-6 && (end.w-expected.w).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn test_sample_slerp() {
+        let end = Quaternion::from_axis_angle(0.0, 0.0, 1.0, PI / 2.0);
+        let samples = sample_slerp(Quaternion::identity(), end, 5);
+        assert_eq!(samples.len(), 5);
+        assert_eq!(samples[0], (0.0, Quaternion::identity()));
+        assert!((samples[2].0 - 0.5).abs() < 1.0e-6);
+        assert!((samples[2].1.angle_between(Quaternion::identity()) - PI / 4.0).abs() < 1.0e-5);
+        assert!(samples[4].1.angle_between(end) < 1.0e-3);
+        assert!(sample_slerp(Quaternion::identity(), end, 0).is_empty());
+    }
+}
@@ -0,0 +1,586 @@
+use std::io;
+use std::path::Path;
+use nalgebra::base::{Vector3, Vector4};
+use nalgebra::core::Matrix4;
+use toml;
+use export;
+use import;
+use quaternion::Quaternion;
+use transform::Transform;
+
+// Where "Save View" writes the camera state, in the working directory.
+pub const VIEW_PATH: &str = "view.toml";
+
+// The camera orbits its target point, looking at it from `distance` away.
+pub struct Camera {
+    // Everything that `view` or `projection` is worked out from is private, so that it's only
+    // changed through methods that keep them up to date.
+    transform: Transform,
+    // The inverse of `transform` turned by `extra_rotation`, or `None` if it can't be inverted.
+    view: Option<Matrix4<f32>>,
+    projection: Matrix4<f32>,
+    fov: f32,
+    // Size of the viewport in pixels, which sets the aspect ratio.
+    viewport_size: (u32, u32),
+    // Pixels from the bottom-left of the window to the viewport, e.g. past the panel.
+    pub viewport_origin: (u32, u32),
+    near: f32,
+    far: f32,
+    orthographic: bool,
+    target: Vector3<f32>,
+    yaw: f32,
+    pitch: f32,
+    // Turn about the view direction, between -π and π.
+    roll: f32,
+    distance: f32,
+    // Seconds taken to move to a new view, or zero to snap instantly.
+    pub transition_duration: f32,
+    pub transition: Option<Transition>,
+    // A turn of the whole camera about a point, on top of where the orbit puts it, as
+    // `(center, rotation)`. The orbit controls work as usual underneath it.
+    extra_rotation: (Vector3<f32>, Quaternion),
+}
+
+// An in-progress move of the camera between two transforms.
+#[derive(Copy, Clone, Debug)]
+pub struct Transition {
+    pub from: Transform,
+    pub to: Transform,
+    pub elapsed: f32,
+}
+
+// The settings that make up a view, saved to a file to come back to the same framing later.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CameraState {
+    pub target: [f32; 3],
+    pub yaw: f32,
+    pub pitch: f32,
+    // Views saved before the camera could roll don't have one.
+    #[serde(default)]
+    pub roll: f32,
+    pub distance: f32,
+    pub fov: f32,
+    pub near: f32,
+    pub far: f32,
+    pub orthographic: bool,
+}
+
+impl CameraState {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<CameraState, String> {
+        let contents = import::read_file(path).map_err(|e| e.to_string())?;
+        CameraState::parse(&contents)
+    }
+
+    // Rejects states that the camera can't be put in, like a hand-edited file with a zero near plane.
+    pub fn parse(contents: &str) -> Result<CameraState, String> {
+        let state: CameraState = toml::from_str(contents).map_err(|e| e.to_string())?;
+        let values = [state.target[0], state.target[1], state.target[2], state.yaw, state.pitch, state.roll, state.distance, state.fov, state.near, state.far];
+        if !values.iter().all(|value| value.is_finite()) {
+            return Err("every value must be a finite number".to_string());
+        }
+        if state.distance <= 0.0 {
+            return Err(format!("distance must be above 0, found {}", state.distance));
+        }
+        if !(state.fov > 0.0 && state.fov < std::f32::consts::PI) {
+            return Err(format!("fov must be between 0 and π, found {}", state.fov));
+        }
+        if state.near <= 0.0 || state.far <= state.near {
+            return Err(format!("near must be above 0 and below far, found {} and {}", state.near, state.far));
+        }
+        Ok(state)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let contents = toml::to_string(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        export::write_file(path, &contents)
+    }
+}
+
+impl Camera {
+    const MAX_PITCH: f32 = 89.0 * std::f32::consts::PI / 180.0;
+    // The range of distances that zooming keeps to.
+    const MIN_DISTANCE: f32 = 1.0;
+    const MAX_DISTANCE: f32 = 50.0;
+
+    pub fn new() -> Self {
+        let mut camera = Camera {
+            transform: Transform::new(),
+            view: None,
+            projection: Matrix4::identity(),
+            fov: std::f32::consts::PI / 4.0,
+            viewport_size: (1280, 720),
+            viewport_origin: (0, 0),
+            near: 0.1,
+            far: 1000.0,
+            orthographic: false,
+            target: [0.0, 0.0, 0.0].into(),
+            yaw: 0.0,
+            pitch: 0.0,
+            roll: 0.0,
+            distance: 5.0,
+            transition_duration: 0.5,
+            transition: None,
+            extra_rotation: (Vector3::zeros(), Quaternion::identity()),
+        };
+        camera.update_transform();
+        camera.update_projection();
+        camera
+    }
+
+    pub fn fov(&self) -> f32 {
+        self.fov
+    }
+
+    pub fn set_fov(&mut self, fov: f32) {
+        self.fov = fov;
+        self.update_projection();
+    }
+
+    pub fn viewport_size(&self) -> (u32, u32) {
+        self.viewport_size
+    }
+
+    // Matches the projection to the window size, if it has changed.
+    pub fn set_viewport_size(&mut self, width: u32, height: u32) {
+        // Minimized windows report a zero size.
+        if width == 0 || height == 0 {
+            return;
+        }
+        if (width, height) != self.viewport_size {
+            self.viewport_size = (width, height);
+            self.update_projection();
+        }
+    }
+
+    pub fn aspect(&self) -> f32 {
+        self.viewport_size.0 as f32 / self.viewport_size.1 as f32
+    }
+
+    pub fn near(&self) -> f32 {
+        self.near
+    }
+
+    pub fn far(&self) -> f32 {
+        self.far
+    }
+
+    // The far plane is always kept beyond the near plane.
+    pub fn set_clip_planes(&mut self, near: f32, far: f32) {
+        const MIN_NEAR: f32 = 0.01;
+        self.near = near.max(MIN_NEAR);
+        self.far = far.max(self.near + MIN_NEAR);
+        self.update_projection();
+    }
+
+    pub fn orthographic(&self) -> bool {
+        self.orthographic
+    }
+
+    pub fn set_orthographic(&mut self, orthographic: bool) {
+        self.orthographic = orthographic;
+        self.update_projection();
+    }
+
+    pub fn projection(&self) -> Matrix4<f32> {
+        self.projection
+    }
+
+    fn update_projection(&mut self) {
+        self.projection = if self.orthographic {
+            // Match the size of the perspective view at the target, so switching keeps the framing.
+            let half_height = self.distance * (self.fov / 2.0).tan();
+            let half_width = half_height * self.aspect();
+            Matrix4::new_orthographic(-half_width, half_width, -half_height, half_height, self.near, self.far)
+        } else {
+            Matrix4::new_perspective(self.aspect(), self.fov, self.near, self.far)
+        };
+    }
+
+    pub fn orbit(&mut self, delta_yaw: f32, delta_pitch: f32) {
+        self.cancel_transition();
+        self.yaw += delta_yaw;
+        self.pitch = (self.pitch + delta_pitch).max(-Camera::MAX_PITCH).min(Camera::MAX_PITCH);
+        self.update_transform();
+    }
+
+    pub fn target(&self) -> Vector3<f32> {
+        self.target
+    }
+
+    pub fn roll(&self) -> f32 {
+        self.roll
+    }
+
+    // Turns the camera about its view direction, keeping where it looks from.
+    pub fn set_roll(&mut self, roll: f32) {
+        self.cancel_transition();
+        self.roll = wrap_angle(roll);
+        self.update_transform();
+    }
+
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    // Looks at the target from `distance` away, straight away and without the zoom's limits.
+    pub fn set_distance(&mut self, distance: f32) {
+        self.cancel_transition();
+        self.distance = distance;
+        self.update_transform();
+        if self.orthographic {
+            self.update_projection();
+        }
+    }
+
+    // Moves the camera towards the model for positive amounts, and away for negative ones.
+    pub fn zoom(&mut self, amount: f32) {
+        self.cancel_transition();
+        const ZOOM_SPEED: f32 = 0.9;
+        // Framing a large or small model can leave the camera outside the range, and zooming
+        // shouldn't jump back into it.
+        let min_distance = Camera::MIN_DISTANCE.min(self.distance);
+        let max_distance = Camera::MAX_DISTANCE.max(self.distance);
+        self.distance = (self.distance * ZOOM_SPEED.powf(amount)).max(min_distance).min(max_distance);
+        self.update_transform();
+        if self.orthographic {
+            self.update_projection();
+        }
+    }
+
+    // Looks at the sphere around `center` from the current direction, from just far enough away to
+    // fit all of it in the viewport. The camera moves there over `transition_duration`.
+    pub fn frame(&mut self, center: Vector3<f32>, radius: f32) {
+        const MARGIN: f32 = 1.1;
+        const MIN_RADIUS: f32 = 1.0e-3;
+        // The sphere has to fit across the narrower of the viewport's width and height.
+        let half_angle = ((self.fov / 2.0).tan() * self.aspect().min(1.0)).atan();
+        let radius = radius.max(MIN_RADIUS);
+        self.cancel_transition();
+        self.target = center;
+        self.distance = MARGIN * radius / half_angle.sin();
+        if self.far < self.distance + radius {
+            let (near, far) = (self.near, 2.0 * (self.distance + radius));
+            self.set_clip_planes(near, far);
+        }
+        self.update_projection();
+        let to = self.orbit_transform();
+        self.move_to(to);
+    }
+
+    // Moves the target in the view plane, by an amount proportional to the distance from it.
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        self.cancel_transition();
+        let right = self.transform.rotation.rotate_vector([1.0, 0.0, 0.0].into());
+        let up = self.transform.rotation.rotate_vector([0.0, 1.0, 0.0].into());
+        self.target += (up * dy - right * dx) * self.distance;
+        self.update_transform();
+    }
+
+    // Looks at the target from the given direction, keeping the current distance.
+    // The camera moves there over `transition_duration`.
+    pub fn look_from(&mut self, direction: Vector3<f32>, up: Vector3<f32>) {
+        let direction = direction.normalize();
+        self.yaw = direction[0].atan2(direction[2]);
+        self.pitch = -direction[1].asin();
+        self.roll = 0.0;
+
+        let mut to = self.transform;
+        to.rotation = Quaternion::look_rotation(-direction, up);
+        to.position = self.target + direction * self.distance;
+        self.move_to(to);
+    }
+
+    pub fn move_to(&mut self, to: Transform) {
+        if self.transition_duration > 0.0 {
+            self.transition = Some(Transition { from: self.transform, to, elapsed: 0.0 });
+        } else {
+            self.transition = None;
+            self.transform = to;
+            self.update_view();
+        }
+    }
+
+    // Advances any transition by `dt` seconds.
+    pub fn update(&mut self, dt: f32) {
+        if let Some(mut transition) = self.transition {
+            transition.elapsed += dt;
+            let t = (transition.elapsed / self.transition_duration).min(1.0);
+            let (from, to) = (transition.from, transition.to);
+            self.transform.rotation = from.rotation.slerp(to.rotation, t);
+            self.transform.position = from.position + (to.position - from.position) * t;
+            self.transition = if t < 1.0 { Some(transition) } else { None };
+            self.update_view();
+        }
+    }
+
+    // The inverse of the camera's transform, which is only recalculated when the camera moves.
+    // If it can't be inverted, this falls back to the identity rather than bringing down the demo.
+    pub fn view_matrix(&self) -> Matrix4<f32> {
+        self.view.unwrap_or_else(Matrix4::identity)
+    }
+
+    // Must be called whenever `transform` changes.
+    fn update_view(&mut self) {
+        self.view = self.shown_transform().to_matrix().try_inverse();
+        if self.view.is_none() {
+            warn!("the camera transform isn't invertible, using an identity view");
+        }
+    }
+
+    // Projects a world space point to normalized device coordinates, where the viewport spans
+    // -1 to 1 on both axes with +y up. Returns `None` for points behind the camera.
+    pub fn project(&self, point: Vector3<f32>) -> Option<(f32, f32)> {
+        let view = self.view?;
+        let view_point = view * Vector4::new(point[0], point[1], point[2], 1.0);
+        if view_point[2] >= 0.0 {
+            return None;
+        }
+
+        let clip = self.projection * view_point;
+        Some((clip[0] / clip[3], clip[1] / clip[3]))
+    }
+
+    // The view being shown, or being moved to during a transition.
+    pub fn state(&self) -> CameraState {
+        CameraState {
+            target: self.target.into(),
+            yaw: self.yaw,
+            pitch: self.pitch,
+            roll: self.roll,
+            distance: self.distance,
+            fov: self.fov,
+            near: self.near,
+            far: self.far,
+            orthographic: self.orthographic,
+        }
+    }
+
+    // Moves to a saved view over `transition_duration`, like the view buttons.
+    pub fn set_state(&mut self, state: &CameraState) {
+        self.cancel_transition();
+        self.target = state.target.into();
+        self.yaw = state.yaw;
+        self.pitch = state.pitch;
+        self.roll = wrap_angle(state.roll);
+        self.distance = state.distance;
+        self.fov = state.fov;
+        self.near = state.near;
+        self.far = state.far;
+        self.orthographic = state.orthographic;
+        self.update_projection();
+        let to = self.orbit_transform();
+        self.move_to(to);
+    }
+
+    // Where the camera is looking from, including any extra rotation.
+    pub fn position(&self) -> Vector3<f32> {
+        self.shown_transform().position
+    }
+
+    pub fn rotation(&self) -> Quaternion {
+        self.shown_transform().rotation
+    }
+
+    // Turns the camera by `rotation` about `center`, on top of the orbit. An object at `center`
+    // then looks turned by the inverse of `rotation`: the view matrix is the inverse of the
+    // camera's transform, so whatever turns the camera turns the scene the other way in the view.
+    // Turning the camera by a rotation's inverse is the same view as turning the object by it.
+    pub fn set_extra_rotation(&mut self, center: Vector3<f32>, rotation: Quaternion) {
+        if (center, rotation) != self.extra_rotation {
+            self.extra_rotation = (center, rotation);
+            self.update_view();
+        }
+    }
+
+    // The orbit's transform, turned by the extra rotation.
+    fn shown_transform(&self) -> Transform {
+        let (center, rotation) = self.extra_rotation;
+        let mut transform = self.transform;
+        transform.rotation = rotation * self.transform.rotation;
+        transform.position = center + rotation.rotate_vector(self.transform.position - center);
+        transform
+    }
+
+    // Looks at the target from `distance` away with the given orientation, straight away. The orbit
+    // angles are left as they are, so this is for cameras that only follow another one.
+    pub fn set_rotation(&mut self, rotation: Quaternion) {
+        self.transition = None;
+        self.transform.rotation = rotation;
+        self.transform.position = self.target + rotation.rotate_vector([0.0, 0.0, self.distance].into());
+        self.update_view();
+    }
+
+    // Stops any transition where it is, so that the orbit controls continue from the current view.
+    pub fn cancel_transition(&mut self) {
+        if self.transition.take().is_some() {
+            let offset = self.transform.position - self.target;
+            self.distance = offset.norm();
+            let direction = offset / self.distance;
+            self.yaw = direction[0].atan2(direction[2]);
+            self.pitch = -direction[1].asin();
+        }
+    }
+
+    pub fn update_transform(&mut self) {
+        self.transform = self.orbit_transform();
+        self.update_view();
+    }
+
+    // Where the orbit angles, distance and target put the camera. The roll comes last, so it turns
+    // about the camera's own view axis rather than the world's.
+    fn orbit_transform(&self) -> Transform {
+        let rotation = Quaternion::from_axis_angle(0.0, 1.0, 0.0, self.yaw)
+            * Quaternion::from_axis_angle(1.0, 0.0, 0.0, self.pitch)
+            * Quaternion::from_axis_angle(0.0, 0.0, 1.0, self.roll);
+        let mut transform = self.transform;
+        transform.rotation = rotation;
+        transform.position = self.target + rotation.rotate_vector([0.0, 0.0, self.distance].into());
+        transform
+    }
+}
+
+// Wraps an angle in radians into -π to π.
+fn wrap_angle(angle: f32) -> f32 {
+    use std::f32::consts::PI;
+    angle - 2.0 * PI * ((angle + PI) / (2.0 * PI)).floor()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project() {
+        let camera = Camera::new();
+        let (x, y) = camera.project([0.0, 0.0, 0.0].into()).unwrap();
+        assert!(x.abs() < 1.0e-6 && y.abs() < 1.0e-6);
+
+        let (x, y) = camera.project([1.0, 1.0, 0.0].into()).unwrap();
+        assert!(x > 0.0 && y > 0.0 && x < y);
+
+        // The camera starts on +Z looking towards the origin.
+        assert_eq!(camera.project([0.0, 0.0, 10.0].into()), None);
+    }
+
+    #[test]
+    fn test_view_matrix_follows_transform() {
+        let assert_view_current = |camera: &Camera| {
+            let expected = camera.transform.to_matrix().try_inverse().unwrap();
+            assert!((camera.view_matrix() - expected).iter().all(|e| e.abs() < 1.0e-5));
+        };
+
+        let mut camera = Camera::new();
+        assert_view_current(&camera);
+        camera.orbit(0.5, 0.25);
+        assert_view_current(&camera);
+        camera.pan(0.1, 0.2);
+        assert_view_current(&camera);
+        camera.zoom(2.0);
+        assert_view_current(&camera);
+
+        let duration = camera.transition_duration;
+        camera.look_from([0.0, 1.0, 1.0].into(), [0.0, 1.0, 0.0].into());
+        camera.update(duration / 2.0);
+        assert_view_current(&camera);
+        camera.update(duration);
+        assert_view_current(&camera);
+
+        camera.transition_duration = 0.0;
+        camera.look_from([1.0, 0.0, 0.0].into(), [0.0, 1.0, 0.0].into());
+        assert_view_current(&camera);
+    }
+
+    #[test]
+    fn test_extra_rotation() {
+        let rotation = Quaternion::from_axis_angle(0.0, 1.0, 0.0, 0.5);
+        let center = Vector3::new(0.5, 0.0, 0.0);
+        let point = Vector3::new(1.0, 0.5, -0.5);
+        let mut camera = Camera::new();
+        camera.orbit(0.3, 0.2);
+        let position = camera.position();
+        // Turning the camera about the center shows the point where it'd be turned the other way.
+        let turned_point = center + Quaternion::from_axis_angle(0.0, 1.0, 0.0, -0.5).rotate_vector(point - center);
+        let expected = camera.project(turned_point).unwrap();
+        camera.set_extra_rotation(center, rotation);
+        let (x, y) = camera.project(point).unwrap();
+        assert!((x - expected.0).abs() < 1.0e-5 && (y - expected.1).abs() < 1.0e-5);
+        assert!(((camera.position() - center).norm() - (position - center).norm()).abs() < 1.0e-5);
+
+        camera.set_extra_rotation(center, Quaternion::identity());
+        assert!((camera.position() - position).norm() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_frame() {
+        let center = Vector3::new(3.0, -1.0, 2.0);
+        for &(radius, aspect) in &[(0.5, 1.0), (40.0, 16.0 / 9.0), (2000.0, 0.5)] {
+            let mut camera = Camera::new();
+            camera.transition_duration = 0.0;
+            camera.set_viewport_size((1000.0 * aspect) as u32, 1000);
+            camera.orbit(0.7, -0.3);
+            camera.frame(center, radius);
+            assert!((camera.target - center).norm() < 1.0e-6);
+
+            // The sphere's edges are inside the viewport, and just inside its narrower side.
+            let right = camera.transform.rotation.rotate_vector(Vector3::x());
+            let up = camera.transform.rotation.rotate_vector(Vector3::y());
+            let (x, _) = camera.project(center + right * radius).unwrap();
+            let (_, y) = camera.project(center + up * radius).unwrap();
+            assert!(x < 1.0 && y < 1.0 && x.max(y) > 0.8, "{} {} for {}", x, y, radius);
+            assert!(camera.far > camera.distance + radius);
+
+            // Zooming out doesn't jump back to the usual range.
+            let distance = camera.distance;
+            camera.zoom(-1.0);
+            assert!(camera.distance >= distance.min(Camera::MAX_DISTANCE));
+        }
+    }
+
+    #[test]
+    fn test_roll() {
+        use std::f32::consts::PI;
+        let mut camera = Camera::new();
+        let position = camera.position();
+        camera.set_roll(PI / 2.0);
+        assert!((camera.position() - position).norm() < 1.0e-5);
+        // Rolling left a quarter turn about the view direction turns the view's up to the left.
+        let up = camera.rotation().rotate_vector([0.0, 1.0, 0.0].into());
+        assert!((up - Vector3::new(-1.0, 0.0, 0.0)).norm() < 1.0e-5);
+
+        camera.set_roll(1.5 * PI);
+        assert!((camera.roll + PI / 2.0).abs() < 1.0e-5);
+
+        camera.transition_duration = 0.0;
+        camera.look_from([1.0, 0.0, 0.0].into(), [0.0, 1.0, 0.0].into());
+        assert!(camera.roll.abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        let mut camera = Camera::new();
+        camera.orbit(0.5, 0.25);
+        camera.pan(0.1, 0.2);
+        camera.set_roll(0.3);
+        camera.set_orthographic(true);
+        let state = camera.state();
+        let contents = toml::to_string(&state).unwrap();
+        assert_eq!(CameraState::parse(&contents), Ok(state));
+
+        let mut restored = Camera::new();
+        restored.transition_duration = 0.0;
+        restored.set_state(&state);
+        assert!((restored.position() - camera.position()).norm() < 1.0e-5);
+        assert!((restored.projection - camera.projection).iter().all(|e| e.abs() < 1.0e-5));
+
+        let invalid = [
+            CameraState { near: 0.0, ..state },
+            CameraState { far: state.near, ..state },
+            CameraState { distance: -1.0, ..state },
+            CameraState { fov: 4.0, ..state },
+        ];
+        for state in &invalid {
+            assert!(CameraState::parse(&toml::to_string(state).unwrap()).is_err(), "{:?}", state);
+        }
+        assert!(CameraState::parse(&contents.replace("yaw = 0.5", "yaw = nan")).is_err());
+    }
+}
@@ -0,0 +1,134 @@
+use quaternion::Quaternion;
+use config;
+
+pub const USAGE: &str = "usage: quaternion-demo [--render OUTPUT.png] [--rotation W,X,Y,Z]... [--stdin] [--fps FPS] [--msaa SAMPLES] [--record EVENTS.jsonl] [--replay EVENTS.jsonl] [--verbose] [MODEL.obj]";
+
+/// The command line options.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Args {
+    /// An OBJ file to show instead of the axes cube.
+    pub model_path: Option<String>,
+    /// Renders a single frame to this PNG file without opening a window, then exits.
+    pub render_path: Option<String>,
+    /// Rotations to apply in order, each given as `w,x,y,z`.
+    pub rotations: Vec<Quaternion>,
+    /// Reads more rotations from standard input, as a JSON array of `{ "w", "x", "y", "z" }`
+    /// objects, and applies them after any given with `--rotation`.
+    pub read_stdin: bool,
+    /// Overrides the config's `target_fps`, with 0 for uncapped.
+    pub target_fps: Option<u32>,
+    /// Overrides the config's `msaa_samples`.
+    pub msaa_samples: Option<u16>,
+    /// Writes the window's input events to this file as they happen, to replay later.
+    pub record_path: Option<String>,
+    /// Plays back the input events recorded in this file, at the times they were recorded.
+    pub replay_path: Option<String>,
+    /// Logs what's loaded and saved, and what the animation is doing, to stderr.
+    pub verbose: bool,
+}
+
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--render" => {
+                let path = args.next().ok_or("--render needs an output path")?;
+                parsed.render_path = Some(path);
+            }
+            "--rotation" => {
+                let rotation = args.next().ok_or("--rotation needs a quaternion")?;
+                parsed.rotations.push(parse_quaternion(&rotation)?);
+            }
+            "--stdin" => parsed.read_stdin = true,
+            "--verbose" | "-v" => parsed.verbose = true,
+            "--fps" => {
+                let fps = args.next().ok_or("--fps needs a frame rate")?;
+                let fps = fps.parse().map_err(|_| format!("invalid frame rate \"{}\"", fps))?;
+                parsed.target_fps = Some(fps);
+            }
+            "--msaa" => {
+                let samples = args.next().ok_or("--msaa needs a sample count")?;
+                let samples = samples.parse().map_err(|_| format!("invalid sample count \"{}\"", samples))?;
+                config::check_msaa_samples(samples)?;
+                parsed.msaa_samples = Some(samples);
+            }
+            "--record" => {
+                let path = args.next().ok_or("--record needs an output path")?;
+                parsed.record_path = Some(path);
+            }
+            "--replay" => {
+                let path = args.next().ok_or("--replay needs a recording")?;
+                parsed.replay_path = Some(path);
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option \"{}\"", arg)),
+            _ if parsed.model_path.is_none() => parsed.model_path = Some(arg),
+            _ => return Err(format!("unexpected argument \"{}\"", arg)),
+        }
+    }
+    Ok(parsed)
+}
+
+/// Parses a quaternion written as `w,x,y,z`, and normalizes it into a rotation.
+pub fn parse_quaternion(s: &str) -> Result<Quaternion, String> {
+    let values = s.split(',')
+        .map(|value| value.trim().parse::<f32>().map_err(|_| format!("invalid number \"{}\" in \"{}\"", value, s)))
+        .collect::<Result<Vec<f32>, String>>()?;
+    if values.len() != 4 {
+        return Err(format!("expected 4 values (w,x,y,z) in \"{}\"", s));
+    }
+
+    let q = Quaternion { w: values[0], x: values[1], y: values[2], z: values[3] };
+    normalized_rotation(q).ok_or_else(|| format!("\"{}\" isn't a rotation", s))
+}
+
+/// Normalizes `q` into a rotation, or returns `None` if it's zero or has a NaN or infinite value.
+pub fn normalized_rotation(mut q: Quaternion) -> Option<Quaternion> {
+    let len = q.len();
+    if !len.is_finite() || len <= 0.0 {
+        return None;
+    }
+    q.normalize();
+    Some(q)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Result<Args, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(args(&[]), Ok(Args::default()));
+        assert_eq!(args(&["model.obj"]), Ok(Args { model_path: Some("model.obj".to_string()), ..Args::default() }));
+
+        let parsed = args(&["--rotation", "0,1,0,0", "--render", "out.png", "--rotation", "2,0,0,0"]).unwrap();
+        assert_eq!(parsed.render_path, Some("out.png".to_string()));
+        assert_eq!(parsed.rotations, vec![Quaternion { w: 0.0, x: 1.0, y: 0.0, z: 0.0 }, Quaternion::identity()]);
+
+        assert_eq!(args(&["--stdin"]), Ok(Args { read_stdin: true, ..Args::default() }));
+        assert_eq!(args(&["--fps", "144"]), Ok(Args { target_fps: Some(144), ..Args::default() }));
+        assert!(args(&["--fps", "-1"]).is_err());
+        assert_eq!(args(&["--msaa", "0"]), Ok(Args { msaa_samples: Some(0), ..Args::default() }));
+        assert!(args(&["--msaa", "3"]).is_err());
+        assert_eq!(args(&["-v"]), Ok(Args { verbose: true, ..Args::default() }));
+        assert!(args(&["--render"]).is_err());
+        let parsed = args(&["--record", "new.jsonl", "--replay", "old.jsonl"]).unwrap();
+        assert_eq!(parsed.record_path, Some("new.jsonl".to_string()));
+        assert_eq!(parsed.replay_path, Some("old.jsonl".to_string()));
+        assert!(args(&["--replay"]).is_err());
+        assert!(args(&["--frobnicate"]).is_err());
+        assert!(args(&["a.obj", "b.obj"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_quaternion() {
+        assert!(parse_quaternion("1,0,0").is_err());
+        assert!(parse_quaternion("1,0,x,0").is_err());
+        assert!(parse_quaternion("0,0,0,0").is_err());
+        assert!(parse_quaternion("NaN,0,0,0").is_err());
+    }
+}
@@ -0,0 +1,158 @@
+use std::io;
+use std::path::Path;
+use toml;
+use export;
+use import;
+use theme::Theme;
+
+pub const CONFIG_PATH: &str = "quaternion-demo.toml";
+
+/// The multisampling sample counts that can be asked for, where 0 turns it off.
+pub const MSAA_SAMPLE_COUNTS: [u16; 4] = [0, 2, 4, 8];
+
+pub fn check_msaa_samples(samples: u16) -> Result<(), String> {
+    if MSAA_SAMPLE_COUNTS.contains(&samples) {
+        Ok(())
+    } else {
+        Err(format!("msaa_samples must be 0, 2, 4 or 8, found {}", samples))
+    }
+}
+
+/// The next sample count to try when `samples` can't be had, or 0 when there's none lower.
+pub fn fewer_msaa_samples(samples: u16) -> u16 {
+    MSAA_SAMPLE_COUNTS.iter().cloned().filter(|&count| count < samples).max().unwrap_or(0)
+}
+
+/// Startup settings, read from `quaternion-demo.toml` in the working directory.
+/// Any setting missing from the file keeps its default value.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub width: u32,
+    pub height: u32,
+    /// The multisampling sample count, one of `MSAA_SAMPLE_COUNTS`. If the GL context can't
+    /// provide it, fewer are used.
+    pub msaa_samples: u16,
+    pub vsync: bool,
+    /// The most frames per second to draw, or 0 for as many as possible. With vsync the frames
+    /// are also limited to the display's refresh rate.
+    pub target_fps: u32,
+    /// Saved whenever it's changed in the panel.
+    pub theme: Theme,
+    /// The increment, in degrees, that the angle sliders snap to when snapping is on.
+    pub angle_snap_degrees: f32,
+    /// Flips vertical drags when orbiting the camera, so dragging up looks down. Saved whenever
+    /// it's changed in the panel.
+    pub invert_orbit_y: bool,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            width: 1280,
+            height: 720,
+            msaa_samples: 4,
+            vsync: true,
+            target_fps: 60,
+            theme: Theme::Dark,
+            angle_snap_degrees: 15.0,
+            invert_orbit_y: false,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config file, falling back to the defaults if it is missing.
+    /// A malformed file prints a warning instead of stopping the demo.
+    pub fn load<P: AsRef<Path>>(path: P) -> Config {
+        let path = path.as_ref();
+        let contents = match import::read_file(path) {
+            Ok(contents) => contents,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+                info!("No {}, using the default settings", path.display());
+                return Config::default();
+            }
+            Err(e) => {
+                warn!("couldn't read {}: {}", path.display(), e);
+                return Config::default();
+            }
+        };
+
+        match Config::parse(&contents) {
+            Ok(config) => {
+                info!("Loaded the settings from {}", path.display());
+                config
+            }
+            Err(e) => {
+                warn!("ignoring malformed {}: {}", path.display(), e);
+                Config::default()
+            }
+        }
+    }
+
+    pub fn parse(contents: &str) -> Result<Config, String> {
+        let config: Config = toml::from_str(contents).map_err(|e| e.to_string())?;
+        if config.width == 0 || config.height == 0 {
+            return Err("window width and height must be non-zero".to_string());
+        }
+        check_msaa_samples(config.msaa_samples)?;
+        if !(config.angle_snap_degrees > 0.0 && config.angle_snap_degrees <= 360.0) {
+            return Err(format!("angle_snap_degrees must be above 0 and at most 360, found {}", config.angle_snap_degrees));
+        }
+        Ok(config)
+    }
+
+    /// Writes every setting to the config file, so it's loaded again next time.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let contents = toml::to_string(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        export::write_file(path, &contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Config::parse(""), Ok(Config::default()));
+
+        let config = Config::parse("width = 800\nheight = 600\nmsaa_samples = 0\nvsync = false\ntheme = \"light\"\n").unwrap();
+        assert_eq!(config, Config { width: 800, height: 600, msaa_samples: 0, vsync: false, theme: Theme::Light, ..Config::default() });
+
+        let config = Config::parse("angle_snap_degrees = 5.0").unwrap();
+        assert_eq!(config, Config { angle_snap_degrees: 5.0, ..Config::default() });
+
+        let config = Config::parse("invert_orbit_y = true").unwrap();
+        assert_eq!(config, Config { invert_orbit_y: true, ..Config::default() });
+
+        let config = Config::parse("target_fps = 0").unwrap();
+        assert_eq!(config, Config { target_fps: 0, ..Config::default() });
+
+        let config = Config::parse("vsync = false").unwrap();
+        assert_eq!(config, Config { vsync: false, ..Config::default() });
+
+        assert!(Config::parse("width = \"wide\"").is_err());
+        assert!(Config::parse("width = 0").is_err());
+        assert!(Config::parse("msaa_samples = 3").is_err());
+        assert!(Config::parse("msaa_samples = 16").is_err());
+        assert!(Config::parse("theme = \"blue\"").is_err());
+        assert!(Config::parse("angle_snap_degrees = 0.0").is_err());
+        assert!(Config::parse("angle_snap_degrees = nan").is_err());
+    }
+
+    #[test]
+    fn test_fewer_msaa_samples() {
+        assert_eq!(fewer_msaa_samples(8), 4);
+        assert_eq!(fewer_msaa_samples(4), 2);
+        assert_eq!(fewer_msaa_samples(2), 0);
+        assert_eq!(fewer_msaa_samples(0), 0);
+    }
+
+    #[test]
+    fn test_save_round_trip() {
+        let config = Config { width: 640, theme: Theme::Light, ..Config::default() };
+        let contents = toml::to_string(&config).unwrap();
+        assert_eq!(Config::parse(&contents), Ok(config));
+    }
+}
@@ -0,0 +1,150 @@
+use glium::glutin::{ModifiersState, VirtualKeyCode};
+
+/// The common actions, which can be done from the keyboard as well as with their buttons.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Action {
+    AddRotation,
+    ClearRotations,
+    /// Starts the animation, or stops it if it's playing.
+    ToggleAnimation,
+    ResetCamera,
+    ToggleHelp,
+    /// Remembers the camera's view in a slot of `BOOKMARK_KEYS`, for the rest of the session.
+    StoreBookmark(usize),
+    /// Moves the camera to the view remembered in a slot, if there is one.
+    RecallBookmark(usize),
+}
+
+pub const SHORTCUT_KEYS: [(VirtualKeyCode, Action); 5] = [
+    (VirtualKeyCode::A, Action::AddRotation),
+    (VirtualKeyCode::C, Action::ClearRotations),
+    (VirtualKeyCode::Space, Action::ToggleAnimation),
+    (VirtualKeyCode::R, Action::ResetCamera),
+    (VirtualKeyCode::H, Action::ToggleHelp),
+];
+
+/// The number keys for the camera bookmarks. With Ctrl they store the view, and alone they go
+/// back to it.
+pub const BOOKMARK_KEYS: [VirtualKeyCode; 4] = [
+    VirtualKeyCode::Key1,
+    VirtualKeyCode::Key2,
+    VirtualKeyCode::Key3,
+    VirtualKeyCode::Key4,
+];
+
+/// Returns the action bound to `key`, if it's a shortcut.
+pub fn shortcut_action(key: VirtualKeyCode, modifiers: ModifiersState) -> Option<Action> {
+    if let Some(slot) = BOOKMARK_KEYS.iter().position(|&bookmark| bookmark == key) {
+        return Some(if modifiers.ctrl { Action::StoreBookmark(slot) } else { Action::RecallBookmark(slot) });
+    }
+    SHORTCUT_KEYS.iter().find(|&&(shortcut, _)| shortcut == key).map(|&(_, action)| action)
+}
+
+/// Every keyboard binding, as listed in the help overlay.
+pub const KEY_BINDINGS: [(VirtualKeyCode, &str); 14] = [
+    (VirtualKeyCode::A, "Add a rotation"),
+    (VirtualKeyCode::C, "Clear the rotations"),
+    (VirtualKeyCode::Space, "Start or stop the animation"),
+    (VirtualKeyCode::R, "Reset the camera"),
+    (VirtualKeyCode::H, "Show or hide this help"),
+    (VirtualKeyCode::P, "Print the rotation"),
+    (VirtualKeyCode::M, "Print the model's model, view and projection matrices"),
+    (VirtualKeyCode::F2, "Show or hide the frame time graph"),
+    (VirtualKeyCode::F3, "Show or hide the FPS"),
+    (VirtualKeyCode::F4, "Show the depth buffer instead of the colors"),
+    (VirtualKeyCode::F5, "Reload the shaders"),
+    (VirtualKeyCode::F11, "Toggle fullscreen"),
+    (VirtualKeyCode::F12, "Save a screenshot"),
+    (VirtualKeyCode::Escape, "Quit"),
+];
+
+/// The mouse controls over the 3D view.
+pub const MOUSE_CONTROLS: [(&str, &str); 4] = [
+    ("Left drag", "Orbit the camera"),
+    ("Middle drag", "Pan the camera"),
+    ("Scroll", "Zoom"),
+    ("Click the cube", "View from that side"),
+];
+
+/// What the main controls in the panel do, by their labels, shown while the mouse is over them.
+/// Controls with a shortcut give its key, which `tooltip` adds to the text as the help lists it.
+pub const TOOLTIPS: [(&str, &str, Option<VirtualKeyCode>); 19] = [
+    ("Euler Angles", "Edit the rotation as yaw, pitch and roll", None),
+    ("Axis Angle", "Edit the rotation as a turn about an axis", None),
+    ("Yaw", "Turn about the Y axis, applied first", None),
+    ("Pitch", "Turn about the X axis, applied after the yaw", None),
+    ("Roll", "Turn about the Z axis, applied last", None),
+    ("Spherical Axis", "Aim the axis by azimuth and elevation instead of X, Y and Z", None),
+    ("Axis Azimuth", "Turns the axis about Y, from +Z towards +X", None),
+    ("Axis Elevation", "Tilts the axis up from the XZ plane", None),
+    ("Axis X", "Sets the rotation axis's direction along X, auto-normalized", None),
+    ("Axis Y", "Sets the rotation axis's direction along Y, auto-normalized", None),
+    ("Axis Z", "Sets the rotation axis's direction along Z, auto-normalized", None),
+    ("Angle", "How far to turn about the axis", None),
+    ("Add Rotation", "Add a rotation to apply after the ones before it", Some(VirtualKeyCode::A)),
+    ("Insert Rotation Before Edited", "Add a rotation to apply just before the edited one", None),
+    ("Clear Rotations", "Remove every rotation, back to the identity", Some(VirtualKeyCode::C)),
+    ("Re-align Signs", "Flip quaternion signs so each is on the same side as the last", None),
+    ("Collapse Rotations", "Replace the list with the single rotation it composes to", None),
+    ("Animate Rotations", "Slerp through the rotations one after another", Some(VirtualKeyCode::Space)),
+    ("Reset Camera", "Go back to the starting view", Some(VirtualKeyCode::R)),
+];
+
+/// The tooltip for the control labeled `label`, if it has one.
+pub fn tooltip(label: &str) -> Option<String> {
+    let &(_, text, key) = TOOLTIPS.iter().find(|&&(control, _, _)| control == label)?;
+    Some(match key {
+        Some(key) => format!("{} ({:?})", text, key),
+        None => text.to_string(),
+    })
+}
+
+/// The text of the help overlay.
+pub fn help_text() -> String {
+    let mut lines = vec!["Mouse".to_string()];
+    lines.extend(MOUSE_CONTROLS.iter().map(|&(control, action)| format!("    {}: {}", control, action)));
+    lines.push(String::new());
+    lines.push("Keyboard".to_string());
+    // The key codes' debug names are the names on the keys, like "A" or "F12".
+    lines.extend(KEY_BINDINGS.iter().map(|&(key, action)| format!("    {:?}: {}", key, action)));
+    lines.push(format!("    1 to {}: Go to a bookmarked view", BOOKMARK_KEYS.len()));
+    lines.push(format!("    Ctrl + 1 to {}: Bookmark the view", BOOKMARK_KEYS.len()));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_help_lists_every_shortcut() {
+        for &(key, _) in &SHORTCUT_KEYS {
+            assert!(KEY_BINDINGS.iter().any(|&(bound, _)| bound == key), "{:?} isn't in the help", key);
+        }
+        let none = ModifiersState::default();
+        let ctrl = ModifiersState { ctrl: true, ..ModifiersState::default() };
+        assert_eq!(shortcut_action(VirtualKeyCode::Space, none), Some(Action::ToggleAnimation));
+        assert_eq!(shortcut_action(VirtualKeyCode::Q, none), None);
+        assert_eq!(shortcut_action(VirtualKeyCode::Key2, none), Some(Action::RecallBookmark(1)));
+        assert_eq!(shortcut_action(VirtualKeyCode::Key2, ctrl), Some(Action::StoreBookmark(1)));
+        assert_eq!(shortcut_action(VirtualKeyCode::Key5, ctrl), None);
+
+        let text = help_text();
+        assert!(text.contains("    Space: Start or stop the animation"));
+        assert!(text.contains("    Left drag: Orbit the camera"));
+        assert!(text.contains("    Ctrl + 1 to 4: Bookmark the view"));
+    }
+
+    #[test]
+    fn test_tooltips() {
+        // A tooltip's shortcut is one the help lists.
+        for &(label, _, key) in &TOOLTIPS {
+            if let Some(key) = key {
+                assert!(KEY_BINDINGS.iter().any(|&(bound, _)| bound == key), "{}'s key isn't in the help", label);
+            }
+        }
+        assert_eq!(tooltip("Add Rotation").unwrap(), "Add a rotation to apply after the ones before it (A)");
+        assert_eq!(tooltip("Yaw").unwrap(), "Turn about the Y axis, applied first");
+        assert_eq!(tooltip("Export Matrix"), None);
+    }
+}
@@ -0,0 +1,120 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use conrod;
+use glium;
+use image;
+
+/// What to check when there's no usable GL context, e.g. on a server without a display.
+const GL_HINT: &str = "The demo needs a display and graphics drivers supporting OpenGL 3.1 or newer.";
+
+/// Everything that can go wrong while setting up or drawing the demo.
+#[derive(Debug)]
+pub enum DemoError {
+    /// There's no display to open the window on.
+    NoDisplay,
+    /// The window or its GL context couldn't be created.
+    Display(glium::backend::glutin::DisplayCreationError),
+    /// The GL context for rendering without a window couldn't be created.
+    HeadlessContext(glium::glutin::CreationError),
+    IncompatibleOpenGl(glium::IncompatibleOpenGl),
+    /// A shader failed to compile or link.
+    Shader(glium::ProgramCreationError),
+    /// An asset or other file couldn't be read or written.
+    Io(io::Error),
+    /// A file or generated mesh can't be used, e.g. a malformed OBJ file.
+    InvalidData(String),
+    /// An image file couldn't be decoded.
+    Image(image::ImageError),
+    VertexBuffer(glium::vertex::BufferCreationError),
+    IndexBuffer(glium::index::BufferCreationError),
+    Texture(glium::texture::TextureCreationError),
+    Framebuffer(glium::framebuffer::ValidationError),
+    /// A buffer couldn't be read back from the GPU.
+    BufferRead(glium::buffer::ReadError),
+    Draw(glium::DrawError),
+    /// The UI's renderer couldn't be set up on the GL context.
+    UiRenderer(conrod::backend::glium::RendererCreationError),
+    /// The UI couldn't be drawn.
+    UiDraw(conrod::backend::glium::DrawError),
+    SwapBuffers(glium::SwapBuffersError),
+}
+
+impl fmt::Display for DemoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DemoError::NoDisplay => write!(f, "couldn't connect to a display\n{}", GL_HINT),
+            DemoError::Display(ref e) => write!(f, "couldn't create the window with an OpenGL 3.1 context: {}\n{}", e, GL_HINT),
+            DemoError::HeadlessContext(ref e) => write!(f, "couldn't create an OpenGL 3.1 context: {}\n{}", e, GL_HINT),
+            DemoError::IncompatibleOpenGl(ref e) => write!(f, "{}", e),
+            DemoError::Shader(ref e) => write!(f, "couldn't build the shaders: {}", e),
+            DemoError::Io(ref e) => write!(f, "{}", e),
+            DemoError::InvalidData(ref message) => write!(f, "{}", message),
+            DemoError::Image(ref e) => write!(f, "couldn't decode an image: {}", e),
+            DemoError::VertexBuffer(ref e) => write!(f, "couldn't create a vertex buffer: {}", e),
+            DemoError::IndexBuffer(ref e) => write!(f, "couldn't create an index buffer: {}", e),
+            DemoError::Texture(ref e) => write!(f, "couldn't create a texture: {}", e),
+            DemoError::Framebuffer(ref e) => write!(f, "couldn't create a framebuffer: {}", e),
+            DemoError::BufferRead(ref e) => write!(f, "couldn't read a buffer: {}", e),
+            DemoError::Draw(ref e) => write!(f, "couldn't draw: {}", e),
+            DemoError::UiRenderer(ref e) => write!(f, "couldn't set up the UI's renderer: {}", e),
+            DemoError::UiDraw(ref e) => write!(f, "couldn't draw the UI: {}", e),
+            DemoError::SwapBuffers(ref e) => write!(f, "couldn't show the frame: {}", e),
+        }
+    }
+}
+
+impl Error for DemoError {
+    fn source(&self) -> Option<&(Error + 'static)> {
+        match *self {
+            DemoError::NoDisplay => None,
+            DemoError::Display(ref e) => Some(e),
+            DemoError::HeadlessContext(ref e) => Some(e),
+            DemoError::IncompatibleOpenGl(ref e) => Some(e),
+            DemoError::Shader(ref e) => Some(e),
+            DemoError::Io(ref e) => Some(e),
+            DemoError::InvalidData(_) => None,
+            DemoError::Image(ref e) => Some(e),
+            DemoError::VertexBuffer(ref e) => Some(e),
+            DemoError::IndexBuffer(ref e) => Some(e),
+            DemoError::Texture(ref e) => Some(e),
+            DemoError::Framebuffer(ref e) => Some(e),
+            DemoError::BufferRead(ref e) => Some(e),
+            DemoError::Draw(ref e) => Some(e),
+            DemoError::UiRenderer(ref e) => Some(e),
+            DemoError::UiDraw(ref e) => Some(e),
+            DemoError::SwapBuffers(ref e) => Some(e),
+        }
+    }
+}
+
+macro_rules! impl_from {
+    ($($variant:ident($error:ty)),*) => {
+        $(
+            impl From<$error> for DemoError {
+                fn from(e: $error) -> DemoError {
+                    DemoError::$variant(e)
+                }
+            }
+        )*
+    };
+}
+
+impl_from!(
+    Display(glium::backend::glutin::DisplayCreationError),
+    HeadlessContext(glium::glutin::CreationError),
+    IncompatibleOpenGl(glium::IncompatibleOpenGl),
+    Shader(glium::ProgramCreationError),
+    Io(io::Error),
+    InvalidData(String),
+    Image(image::ImageError),
+    VertexBuffer(glium::vertex::BufferCreationError),
+    IndexBuffer(glium::index::BufferCreationError),
+    Texture(glium::texture::TextureCreationError),
+    Framebuffer(glium::framebuffer::ValidationError),
+    BufferRead(glium::buffer::ReadError),
+    Draw(glium::DrawError),
+    UiRenderer(conrod::backend::glium::RendererCreationError),
+    UiDraw(conrod::backend::glium::DrawError),
+    SwapBuffers(glium::SwapBuffersError)
+);
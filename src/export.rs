@@ -0,0 +1,348 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use image;
+use nalgebra::base::Vector3;
+use nalgebra::core::Matrix4;
+use quaternion::Quaternion;
+
+/// Formats a matrix as CSV in column-major order: each line holds one column of the matrix,
+/// which is the same layout that is uploaded to the shader.
+pub fn format_matrix_csv(m: &Matrix4<f32>) -> String {
+    let mut csv = String::new();
+    for j in 0..4 {
+        let column: Vec<String> = (0..4).map(|i| m[(i, j)].to_string()).collect();
+        csv.push_str(&column.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Formats the upper-left 3x3 rotation block as a NumPy array literal, one list per row.
+pub fn format_numpy_array(m: &Matrix4<f32>) -> String {
+    let rows: Vec<String> = (0..3)
+        .map(|i| format!("[{:?}, {:?}, {:?}]", m[(i, 0)], m[(i, 1)], m[(i, 2)]))
+        .collect();
+    format!("np.array([{}])", rows.join(", "))
+}
+
+/// Formats a matrix as a GLSL `mat4` constructor. GLSL fills a matrix a column at a time, so the
+/// values are listed in column-major order, each with a decimal point as GLSL floats need.
+pub fn format_glsl_mat4(m: &Matrix4<f32>) -> String {
+    let values: Vec<String> = (0..4)
+        .flat_map(|j| (0..4).map(move |i| (i, j)))
+        .map(|index| format!("{:?}", m[index]))
+        .collect();
+    format!("mat4({})", values.join(", "))
+}
+
+/// Formats a rotation as a URDF `rpy` attribute, with the fixed axis roll, pitch and yaw in radians.
+pub fn format_urdf_rpy(q: Quaternion) -> String {
+    let (roll, pitch, yaw) = q.to_rpy();
+    format!("rpy=\"{:?} {:?} {:?}\"", roll, pitch, yaw)
+}
+
+/// Formats a rotation as the `rotation` property of a glTF node. glTF lists the quaternion's
+/// components as `[x, y, z, w]`, with `w` last rather than first as in `Quaternion`.
+pub fn format_gltf_rotation(q: Quaternion) -> String {
+    format!("\"rotation\": [{:?}, {:?}, {:?}, {:?}]", q.x, q.y, q.z, q.w)
+}
+
+/// Formats a rotation's 6D representation as a Python list, for pasting into training code.
+pub fn format_rotation_6d(q: Quaternion) -> String {
+    let values: Vec<String> = q.to_rotation_6d().iter().map(|value| format!("{:?}", value)).collect();
+    format!("[{}]", values.join(", "))
+}
+
+/// Formats `(time, orientation)` samples as a JSON object with a list of keyframes.
+pub fn format_keyframes_json(fps: f32, samples: &[(f32, Quaternion)]) -> String {
+    let keyframes: Vec<String> = samples.iter()
+        .map(|&(time, q)| format!(
+            "    {{ \"time\": {}, \"rotation\": {{ \"w\": {}, \"x\": {}, \"y\": {}, \"z\": {} }} }}",
+            time, q.w, q.x, q.y, q.z))
+        .collect();
+    format!("{{\n  \"fps\": {},\n  \"keyframes\": [\n{}\n  ]\n}}\n", fps, keyframes.join(",\n"))
+}
+
+/// Formats `(t, orientation)` samples along a slerp as CSV with a header, with each sample's angle
+/// in radians from `start`, so the angular speed along the path can be plotted.
+pub fn format_slerp_csv(start: Quaternion, samples: &[(f32, Quaternion)]) -> String {
+    let mut csv = "t,w,x,y,z,angle_from_start\n".to_string();
+    for &(t, q) in samples {
+        csv.push_str(&format!("{},{},{},{},{},{}\n", t, q.w, q.x, q.y, q.z, start.angle_between(q)));
+    }
+    csv
+}
+
+/// The most decimal places the numeric readouts can be set to show.
+pub const MAX_PRECISION: usize = 8;
+
+/// Formats a rotation as its quaternion and axis-angle components, with `precision` decimal places.
+/// The quaternion is shown canonical, so the same rotation always prints the same.
+pub fn format_rotation(q: Quaternion, precision: usize) -> String {
+    let q = q.canonical();
+    let (axis, angle) = q.to_axis_angle();
+    format!("quaternion w,x,y,z: {:.*},{:.*},{:.*},{:.*} axis-angle x,y,z,degrees: {:.*},{:.*},{:.*},{:.*}",
+        precision, q.w, precision, q.x, precision, q.y, precision, q.z,
+        precision, axis[0], precision, axis[1], precision, axis[2], precision, angle.to_degrees())
+}
+
+/// Formats a rotation's unit axis and its angle in degrees for the panel's readout. The identity
+/// has no axis of its own, so a placeholder is shown for it. The angle is at most 180°, taking
+/// the shorter way around.
+pub fn format_axis_angle(q: Quaternion, precision: usize) -> String {
+    let (axis, angle) = q.canonical().to_axis_angle();
+    if angle == 0.0 {
+        return format!("Axis: (-, -, -)\nAngle: {:.*}°", precision, 0.0);
+    }
+    format!("Axis: ({:.*}, {:.*}, {:.*})\nAngle: {:.*}°",
+        precision, axis[0], precision, axis[1], precision, axis[2], precision, angle.to_degrees())
+}
+
+/// The model's local forward axis, +Z, which is the way the front of the axes cube faces.
+pub const FORWARD: [f32; 3] = [0.0, 0.0, 1.0];
+
+/// Formats the world space direction that a rotation turns the model's `FORWARD` axis to, with
+/// its azimuth about the Y axis, from +Z towards +X, and its elevation above the XZ plane, in
+/// degrees. Straight up or down there's no azimuth, so a placeholder is shown for it.
+pub fn format_look_direction(q: Quaternion, precision: usize) -> String {
+    let direction = q.rotate_vector(FORWARD.into());
+    let elevation = direction[1].max(-1.0).min(1.0).asin().to_degrees();
+    let azimuth = if direction[0].hypot(direction[2]) < 1.0e-6 {
+        "-".to_string()
+    } else {
+        format!("{:.*}°", precision, direction[0].atan2(direction[2]).to_degrees())
+    };
+    format!("Forward: ({:.*}, {:.*}, {:.*})\nAzimuth: {}, elevation: {:.*}°",
+        precision, direction[0], precision, direction[1], precision, direction[2], azimuth, precision, elevation)
+}
+
+/// Formats how far a rotation is from a marked reference orientation, by the smallest rotation
+/// between them, or from the identity while there's no reference.
+pub fn format_reference_angle(q: Quaternion, reference: Option<Quaternion>, precision: usize) -> String {
+    match reference {
+        Some(reference) => format!("From reference: {:.*}°", precision, reference.angle_between(q).to_degrees()),
+        None => format!("From identity: {:.*}°", precision, Quaternion::identity().angle_between(q).to_degrees()),
+    }
+}
+
+/// Formats the angle between the direction a rotation turns the model's `FORWARD` axis to and a
+/// world space `target` direction, the distance along the great circle between them.
+pub fn format_target_angle(q: Quaternion, target: Vector3<f32>, precision: usize) -> String {
+    let forward = q.rotate_vector(FORWARD.into());
+    let cos = forward.dot(&target.normalize()).max(-1.0).min(1.0);
+    format!("To target: {:.*}°", precision, cos.acos().to_degrees())
+}
+
+/// Formats a rotation as a line of Rust that reconstructs it through `from_axis_angle`.
+pub fn format_rust_snippet(q: Quaternion) -> String {
+    let (axis, angle) = q.to_axis_angle();
+    if angle == 0.0 {
+        return "let q = Quaternion::identity();".to_string();
+    }
+    format!("let q = Quaternion::from_axis_angle({:?}, {:?}, {:?}, {:?});", axis[0], axis[1], axis[2], angle)
+}
+
+/// Formats the model, view and projection matrices as they're sent to the shaders, a row per line
+/// under each one's name. The shaders' arrays hold a column each, so they're printed transposed to
+/// read the usual way around, with the translation in the last column.
+pub fn format_mvp(model: &[[f32; 4]; 4], view: &[[f32; 4]; 4], projection: &[[f32; 4]; 4], precision: usize) -> String {
+    let format_matrix = |name: &str, columns: &[[f32; 4]; 4]| {
+        let rows: Vec<String> = (0..4)
+            .map(|i| {
+                let row: Vec<String> = columns.iter().map(|column| format!("{:>1$.2$}", column[i], precision + 5, precision)).collect();
+                format!("[{} ]", row.join(""))
+            })
+            .collect();
+        format!("{}:\n{}", name, rows.join("\n"))
+    };
+    [format_matrix("model", model), format_matrix("view", view), format_matrix("projection", projection)].join("\n")
+}
+
+pub fn write_file<P: AsRef<Path>>(path: P, contents: &str) -> io::Result<()> {
+    let path = path.as_ref();
+    let mut file = File::create(path)?;
+    file.write_all(contents.as_bytes())?;
+    debug!("Wrote {} bytes to {}", contents.len(), path.display());
+    Ok(())
+}
+
+/// Reverses the order of the rows in an image. GL framebuffers are read bottom row first,
+/// while image files store the top row first.
+pub fn flip_rows(pixels: &[u8], row_len: usize) -> Vec<u8> {
+    pixels.chunks(row_len).rev().flat_map(|row| row.iter().cloned()).collect()
+}
+
+/// Writes RGBA pixels, top row first, to a PNG file.
+pub fn write_png<P: AsRef<Path>>(path: P, pixels: &[u8], width: u32, height: u32) -> io::Result<()> {
+    let path = path.as_ref();
+    image::save_buffer(path, pixels, width, height, image::ColorType::RGBA(8))?;
+    debug!("Wrote a {}x{} image to {}", width, height, path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_matrix_csv() {
+        let m = Matrix4::identity();
+        assert_eq!(format_matrix_csv(&m), "1,0,0,0\n0,1,0,0\n0,0,1,0\n0,0,0,1\n");
+
+        // The translation lives in the last column, so it should be written on the last line.
+        let mut m = Matrix4::identity();
+        m[(0,3)] = 2.0;
+        m[(1,3)] = -3.5;
+        m[(2,3)] = 0.25;
+        assert_eq!(format_matrix_csv(&m), "1,0,0,0\n0,1,0,0\n0,0,1,0\n2,-3.5,0.25,1\n");
+    }
+
+    #[test]
+    fn test_format_numpy_array() {
+        // A quarter turn around Z, with a translation that should be left out.
+        let mut m = Matrix4::identity();
+        m[(0,0)] = 0.0;
+        m[(0,1)] = -1.0;
+        m[(1,0)] = 1.0;
+        m[(1,1)] = 0.0;
+        m[(0,3)] = 5.0;
+        assert_eq!(format_numpy_array(&m), "np.array([[0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]])");
+    }
+
+    #[test]
+    fn test_format_mvp() {
+        let identity: [[f32; 4]; 4] = Matrix4::identity().into();
+        let mut translation = Matrix4::identity();
+        translation[(0,3)] = -2.5;
+        let formatted = format_mvp(&identity, &translation.into(), &identity, 1);
+        let lines: Vec<&str> = formatted.lines().collect();
+        assert_eq!(lines.len(), 15);
+        assert_eq!(lines[0], "model:");
+        assert_eq!(lines[1], "[   1.0   0.0   0.0   0.0 ]");
+        assert_eq!(lines[5], "view:");
+        assert_eq!(lines[6], "[   1.0   0.0   0.0  -2.5 ]");
+        assert_eq!(lines[10], "projection:");
+    }
+
+    #[test]
+    fn test_format_glsl_mat4() {
+        // A quarter turn around Z, moved along X, so the first column is +Y and the last holds
+        // the translation.
+        let mut m = Matrix4::identity();
+        m[(0,0)] = 0.0;
+        m[(0,1)] = -1.0;
+        m[(1,0)] = 1.0;
+        m[(1,1)] = 0.0;
+        m[(0,3)] = 2.5;
+        assert_eq!(format_glsl_mat4(&m),
+            "mat4(0.0, 1.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 2.5, 0.0, 0.0, 1.0)");
+    }
+
+    #[test]
+    fn test_format_urdf_rpy() {
+        assert_eq!(format_urdf_rpy(Quaternion::identity()), "rpy=\"0.0 0.0 0.0\"");
+        // A turn about Z alone is all yaw.
+        let q = Quaternion::from_axis_angle(0.0, 0.0, 1.0, 0.5);
+        assert_eq!(format_urdf_rpy(q), format!("rpy=\"0.0 0.0 {:?}\"", q.to_rpy().2));
+        assert!((q.to_rpy().2 - 0.5).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn test_format_gltf_rotation() {
+        assert_eq!(format_gltf_rotation(Quaternion::identity()), "\"rotation\": [0.0, 0.0, 0.0, 1.0]");
+        let q = Quaternion { w: 0.5, x: 0.1, y: 0.2, z: 0.3 };
+        assert_eq!(format_gltf_rotation(q), "\"rotation\": [0.1, 0.2, 0.3, 0.5]");
+    }
+
+    #[test]
+    fn test_format_rotation_6d() {
+        assert_eq!(format_rotation_6d(Quaternion::identity()), "[1.0, 0.0, 0.0, 0.0, 1.0, 0.0]");
+    }
+
+    #[test]
+    fn test_format_slerp_csv() {
+        let start = Quaternion::identity();
+        let end = Quaternion { w: 0.0, x: 1.0, y: 0.0, z: 0.0 };
+        let csv = format_slerp_csv(start, &[(0.0, start), (1.0, end)]);
+        assert_eq!(csv, format!("t,w,x,y,z,angle_from_start\n0,1,0,0,0,0\n1,0,1,0,0,{}\n", ::std::f32::consts::PI));
+    }
+
+    #[test]
+    fn test_format_rotation() {
+        let q = Quaternion::from_axis_angle(0.0, 0.0, 1.0, ::std::f32::consts::PI / 2.0);
+        assert_eq!(format_rotation(q, 2), "quaternion w,x,y,z: 0.71,0.00,0.00,0.71 axis-angle x,y,z,degrees: 0.00,0.00,1.00,90.00");
+        assert_eq!(format_rotation(Quaternion::identity(), 0), "quaternion w,x,y,z: 1,0,0,0 axis-angle x,y,z,degrees: 1,0,0,0");
+        assert_eq!(format_rotation(-q, 2), format_rotation(q, 2));
+    }
+
+    #[test]
+    fn test_format_axis_angle() {
+        assert_eq!(format_axis_angle(Quaternion::identity(), 1), "Axis: (-, -, -)\nAngle: 0.0°");
+        let q = Quaternion::from_axis_angle(0.0, -1.0, 0.0, ::std::f32::consts::PI / 3.0);
+        assert_eq!(format_axis_angle(q, 2), "Axis: (0.00, -1.00, 0.00)\nAngle: 60.00°");
+        // Three quarters of a turn is shown as a quarter turn the other way.
+        let q = Quaternion::from_axis_angle(0.0, 0.0, 1.0, 1.5 * ::std::f32::consts::PI);
+        assert_eq!(format_axis_angle(q, 2), "Axis: (0.00, 0.00, -1.00)\nAngle: 90.00°");
+    }
+
+    #[test]
+    fn test_format_look_direction() {
+        use std::f32::consts::PI;
+        assert_eq!(format_look_direction(Quaternion::identity(), 1), "Forward: (0.0, 0.0, 1.0)\nAzimuth: 0.0°, elevation: 0.0°");
+        let q = Quaternion::from_axis_angle(0.0, 1.0, 0.0, -PI / 4.0);
+        assert_eq!(format_look_direction(q, 2), "Forward: (-0.71, 0.00, 0.71)\nAzimuth: -45.00°, elevation: 0.00°");
+        // Pitched up from there, the azimuth stays the same.
+        let q = q * Quaternion::from_axis_angle(1.0, 0.0, 0.0, -PI / 6.0);
+        assert_eq!(format_look_direction(q, 1), "Forward: (-0.6, 0.5, 0.6)\nAzimuth: -45.0°, elevation: 30.0°");
+        let q = Quaternion::from_axis_angle(1.0, 0.0, 0.0, PI / 2.0);
+        assert!(format_look_direction(q, 1).ends_with("Azimuth: -, elevation: -90.0°"));
+    }
+
+    #[test]
+    fn test_format_reference_angle() {
+        use std::f32::consts::PI;
+        let reference = Quaternion::from_axis_angle(0.0, 1.0, 0.0, PI / 2.0);
+        let q = reference * Quaternion::from_axis_angle(1.0, 0.0, 0.0, PI / 6.0);
+        assert_eq!(format_reference_angle(q, Some(reference), 1), "From reference: 30.0°");
+        // Either sign of the reference is the same orientation.
+        assert_eq!(format_reference_angle(q, Some(-reference), 1), "From reference: 30.0°");
+        assert_eq!(format_reference_angle(reference, None, 0), "From identity: 90°");
+    }
+
+    #[test]
+    fn test_format_target_angle() {
+        use std::f32::consts::PI;
+        let q = Quaternion::from_axis_angle(0.0, 1.0, 0.0, PI / 2.0);
+        assert_eq!(format_target_angle(q, Vector3::new(2.0, 0.0, 0.0), 1), "To target: 0.0°");
+        assert_eq!(format_target_angle(q, Vector3::new(0.0, 1.0, 0.0), 1), "To target: 90.0°");
+        assert_eq!(format_target_angle(q, Vector3::new(-1.0, 0.0, 0.0), 0), "To target: 180°");
+    }
+
+    #[test]
+    fn test_format_rust_snippet() {
+        assert_eq!(format_rust_snippet(Quaternion::identity()), "let q = Quaternion::identity();");
+        assert_eq!(format_rust_snippet(Quaternion { x: 0.0, y: 1.0, z: 0.0, w: 0.0 }),
+            "let q = Quaternion::from_axis_angle(0.0, 1.0, 0.0, 3.1415927);");
+    }
+
+    #[test]
+    fn test_format_keyframes_json() {
+        let samples = [(0.0, Quaternion::identity()), (0.5, Quaternion { x: 0.0, y: -1.0, z: 0.0, w: 0.0 })];
+        assert_eq!(format_keyframes_json(30.0, &samples), concat!(
+            "{\n",
+            "  \"fps\": 30,\n",
+            "  \"keyframes\": [\n",
+            "    { \"time\": 0, \"rotation\": { \"w\": 1, \"x\": 0, \"y\": 0, \"z\": 0 } },\n",
+            "    { \"time\": 0.5, \"rotation\": { \"w\": 0, \"x\": 0, \"y\": -1, \"z\": 0 } }\n",
+            "  ]\n",
+            "}\n"));
+    }
+
+    #[test]
+    fn test_flip_rows() {
+        let pixels = [1, 2, 3, 4, 5, 6];
+        assert_eq!(flip_rows(&pixels, 2), vec![5, 6, 3, 4, 1, 2]);
+        assert_eq!(flip_rows(&pixels, 6), pixels.to_vec());
+    }
+}
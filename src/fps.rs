@@ -0,0 +1,205 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use conrod::{Point, Rect};
+
+/// How many of the latest frames are averaged, so the readout doesn't flicker every frame.
+pub const AVERAGED_FRAMES: usize = 30;
+/// How many of the latest frames the frame time graph shows, a few seconds' worth.
+pub const GRAPHED_FRAMES: usize = 240;
+/// The least frame time, in seconds, that the top of the graph stands for, so that steady frames
+/// at 60 FPS sit halfway up rather than filling it with noise.
+pub const MIN_GRAPH_TOP: f32 = 1.0 / 30.0;
+
+/// A moving average of the frame times, for the FPS readout.
+#[derive(Clone, Debug)]
+pub struct FrameTimes {
+    times: VecDeque<f32>,
+    total: f32,
+}
+
+impl FrameTimes {
+    pub fn new() -> FrameTimes {
+        FrameTimes { times: VecDeque::with_capacity(AVERAGED_FRAMES), total: 0.0 }
+    }
+
+    /// Adds the time taken by the last frame, in seconds, dropping the oldest once there are enough.
+    pub fn add(&mut self, frame_time: f32) {
+        if self.times.len() == AVERAGED_FRAMES {
+            self.total -= self.times.pop_front().unwrap();
+        }
+        self.times.push_back(frame_time);
+        self.total += frame_time;
+    }
+
+    /// The average frame time in seconds, or `None` before the first frame.
+    pub fn average(&self) -> Option<f32> {
+        if self.times.is_empty() || self.total <= 0.0 {
+            None
+        } else {
+            Some(self.total / self.times.len() as f32)
+        }
+    }
+
+    /// Formats the readout, e.g. "60.0 FPS (16.7 ms)".
+    pub fn label(&self) -> String {
+        match self.average() {
+            Some(average) => format!("{:.1} FPS ({:.1} ms)", 1.0 / average, average * 1000.0),
+            None => "-- FPS".to_string(),
+        }
+    }
+}
+
+/// The latest frame times as a scrolling graph, to pick out single slow frames that the average
+/// smooths over.
+#[derive(Clone, Debug)]
+pub struct FrameGraph {
+    times: VecDeque<f32>,
+}
+
+impl FrameGraph {
+    pub fn new() -> FrameGraph {
+        FrameGraph { times: VecDeque::with_capacity(GRAPHED_FRAMES) }
+    }
+
+    /// Adds the time taken by the last frame, in seconds, dropping the oldest once it's full.
+    pub fn add(&mut self, frame_time: f32) {
+        if self.times.len() == GRAPHED_FRAMES {
+            self.times.pop_front();
+        }
+        self.times.push_back(frame_time);
+    }
+
+    /// The longest of the frame times, or `None` before the first frame.
+    pub fn max(&self) -> Option<f32> {
+        if self.times.is_empty() {
+            None
+        } else {
+            Some(self.times.iter().cloned().fold(0.0, f32::max))
+        }
+    }
+
+    /// The frame time, in seconds, at the top of the graph: the longest frame, or `MIN_GRAPH_TOP`.
+    pub fn top(&self) -> f32 {
+        self.max().unwrap_or(0.0).max(MIN_GRAPH_TOP)
+    }
+
+    /// Maps the frame times into `rect`, a frame apart across it with the latest at the right, and
+    /// from none at the bottom to `top` at the top.
+    pub fn points_in(&self, rect: Rect) -> Vec<Point> {
+        let step = rect.w() / (GRAPHED_FRAMES - 1) as f64;
+        let top = self.top();
+        let first_x = rect.right() - step * (self.times.len().max(1) - 1) as f64;
+        self.times.iter()
+            .enumerate()
+            .map(|(i, &time)| [first_x + step * i as f64, rect.bottom() + f64::from(time / top) * rect.h()])
+            .collect()
+    }
+}
+
+/// Keeps the frames to a target rate, by waiting at the end of each frame until the next is due.
+/// The frames are due at fixed intervals rather than an interval after each one ends, so a wait
+/// that oversleeps is made up for by a shorter one next frame.
+#[derive(Clone, Debug)]
+pub struct FrameLimiter {
+    /// When the upcoming frame is due to start, once there's been a frame to time it from.
+    next_frame: Option<Instant>,
+}
+
+impl FrameLimiter {
+    pub fn new() -> FrameLimiter {
+        FrameLimiter { next_frame: None }
+    }
+
+    /// Returns how long to wait at `now` for the next frame at `fps` frames per second, and
+    /// schedules the one after it. A frame that's already late isn't waited for, and the frames
+    /// after it are timed from it rather than hurrying to catch up. An `fps` of 0 is uncapped.
+    pub fn wait_time(&mut self, now: Instant, fps: u32) -> Duration {
+        if fps == 0 {
+            self.next_frame = None;
+            return Duration::from_secs(0);
+        }
+
+        let due = match self.next_frame {
+            Some(due) if due > now => due,
+            _ => now,
+        };
+        self.next_frame = Some(due + Duration::from_secs(1) / fps);
+        due - now
+    }
+
+    /// Sleeps until the next frame is due at `fps` frames per second.
+    pub fn wait(&mut self, fps: u32) {
+        let wait_time = self.wait_time(Instant::now(), fps);
+        if wait_time > Duration::from_secs(0) {
+            std::thread::sleep(wait_time);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_times() {
+        let mut frame_times = FrameTimes::new();
+        assert_eq!(frame_times.average(), None);
+        assert_eq!(frame_times.label(), "-- FPS");
+
+        frame_times.add(0.01);
+        frame_times.add(0.03);
+        assert!((frame_times.average().unwrap() - 0.02).abs() < 1.0e-6);
+        assert_eq!(frame_times.label(), "50.0 FPS (20.0 ms)");
+
+        // Only the latest frames count.
+        for _ in 0..AVERAGED_FRAMES {
+            frame_times.add(0.1);
+        }
+        assert!((frame_times.average().unwrap() - 0.1).abs() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_frame_graph() {
+        let mut graph = FrameGraph::new();
+        assert_eq!(graph.max(), None);
+        assert!(graph.points_in(Rect::from_corners([0.0, 0.0], [100.0, 50.0])).is_empty());
+
+        graph.add(0.01);
+        graph.add(0.1);
+        graph.add(0.05);
+        assert!((graph.max().unwrap() - 0.1).abs() < 1.0e-6);
+        let rect = Rect::from_corners([10.0, 20.0], [110.0, 70.0]);
+        let points = graph.points_in(rect);
+        assert_eq!(points.len(), 3);
+        // The slowest frame reaches the top, and the latest is at the right.
+        assert!((points[1][1] - 70.0).abs() < 1.0e-4);
+        assert!((points[2][0] - 110.0).abs() < 1.0e-4 && (points[2][1] - 45.0).abs() < 1.0e-4);
+
+        // Only the latest frames are kept, filling the width.
+        for _ in 0..GRAPHED_FRAMES {
+            graph.add(0.01);
+        }
+        assert!((graph.top() - MIN_GRAPH_TOP).abs() < 1.0e-6);
+        let points = graph.points_in(rect);
+        assert_eq!(points.len(), GRAPHED_FRAMES);
+        assert!((points[0][0] - 10.0).abs() < 1.0e-4);
+    }
+
+    #[test]
+    fn test_frame_limiter() {
+        let ms = Duration::from_millis;
+        let start = Instant::now();
+        let mut limiter = FrameLimiter::new();
+        assert_eq!(limiter.wait_time(start, 50), ms(0));
+        assert_eq!(limiter.wait_time(start + ms(5), 50), ms(15));
+        // Having overslept by 2 ms, the next frame is still due at 40 ms.
+        assert_eq!(limiter.wait_time(start + ms(22), 50), ms(18));
+
+        // A slow frame isn't waited for, and the next one is timed from it.
+        assert_eq!(limiter.wait_time(start + ms(100), 50), ms(0));
+        assert_eq!(limiter.wait_time(start + ms(110), 50), ms(10));
+
+        assert_eq!(limiter.wait_time(start + ms(111), 0), ms(0));
+        assert_eq!(limiter.wait_time(start + ms(112), 0), ms(0));
+    }
+}
@@ -0,0 +1,109 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use quaternion::Quaternion;
+use serde_json;
+use cli;
+
+pub fn read_file<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    let path = path.as_ref();
+    let mut file = File::open(path)?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+    debug!("Read {} bytes from {}", contents.len(), path.display());
+    Ok(contents)
+}
+
+/// Reads all of standard input, for rotations piped into the demo.
+pub fn read_stdin() -> io::Result<String> {
+    let mut contents = String::new();
+    io::stdin().read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+/// A quaternion as written in JSON, in the same form as the exported keyframes.
+#[derive(Deserialize)]
+struct JsonQuaternion {
+    w: f32,
+    x: f32,
+    y: f32,
+    z: f32,
+}
+
+/// Parses a JSON array of `{ "w": ..., "x": ..., "y": ..., "z": ... }` objects into a list of
+/// rotations, normalizing each one. Errors report the 0-based index of a zero quaternion.
+pub fn parse_rotations_json(json: &str) -> Result<Vec<Quaternion>, String> {
+    let quaternions: Vec<JsonQuaternion> = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    if quaternions.is_empty() {
+        return Err("no rotations found".to_string());
+    }
+
+    quaternions.iter()
+        .enumerate()
+        .map(|(i, q)| cli::normalized_rotation(Quaternion { w: q.w, x: q.x, y: q.y, z: q.z })
+            .ok_or_else(|| format!("rotation {} is zero", i)))
+        .collect()
+}
+
+/// Parses rows of `yaw,pitch,roll` in degrees into a list of rotations.
+/// Blank lines are skipped, and errors report the 1-based row number.
+pub fn parse_euler_csv(csv: &str) -> Result<Vec<Quaternion>, String> {
+    let mut rotations = Vec::new();
+    for (i, line) in csv.lines().enumerate() {
+        let row = i + 1;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let values: Vec<&str> = line.split(',').map(|value| value.trim()).collect();
+        if values.len() != 3 {
+            return Err(format!("row {}: expected 3 values (yaw,pitch,roll), found {}", row, values.len()));
+        }
+
+        let mut angles = [0.0; 3];
+        for (angle, value) in angles.iter_mut().zip(values) {
+            *angle = value.parse()
+                .map_err(|_| format!("row {}: invalid angle \"{}\"", row, value))?;
+        }
+        rotations.push(Quaternion::from_euler_degrees(angles[0], angles[1], angles[2]));
+    }
+
+    if rotations.is_empty() {
+        return Err("no rotations found".to_string());
+    }
+    Ok(rotations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_euler_csv() {
+        let rotations = parse_euler_csv("0,0,0\n\n  90, 0.5 ,-45\n").unwrap();
+        assert_eq!(rotations, vec![
+            Quaternion::from_euler_degrees(0.0, 0.0, 0.0),
+            Quaternion::from_euler_degrees(90.0, 0.5, -45.0),
+        ]);
+
+        assert_eq!(parse_euler_csv("0,0,0\n\n1,2\n"), Err("row 3: expected 3 values (yaw,pitch,roll), found 2".to_string()));
+        assert_eq!(parse_euler_csv("0,x,0"), Err("row 1: invalid angle \"x\"".to_string()));
+        assert!(parse_euler_csv("\n\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_rotations_json() {
+        let rotations = parse_rotations_json(r#"[
+            { "w": 1, "x": 0, "y": 0, "z": 0 },
+            { "w": 0, "x": 0, "y": 2.0, "z": 0 }
+        ]"#).unwrap();
+        assert_eq!(rotations, vec![Quaternion::identity(), Quaternion { w: 0.0, x: 0.0, y: 1.0, z: 0.0 }]);
+
+        assert_eq!(parse_rotations_json(r#"[{ "w": 1, "x": 0, "y": 0, "z": 0 }, { "w": 0, "x": 0, "y": 0, "z": 0 }]"#),
+            Err("rotation 1 is zero".to_string()));
+        assert_eq!(parse_rotations_json("[]"), Err("no rotations found".to_string()));
+        assert!(parse_rotations_json(r#"[{ "w": 1, "x": 0 }]"#).is_err());
+        assert!(parse_rotations_json("not json").is_err());
+    }
+}
@@ -0,0 +1,38 @@
+use env_logger::Builder;
+use log::LevelFilter;
+
+/// Starts logging to stderr. Quiet unless `verbose`, when the demo logs what it loads and saves
+/// and what the animation is doing. `RUST_LOG` can still pick other levels and modules.
+pub fn init(verbose: bool) {
+    // The logger can only be set once, which is all it's needed.
+    let _ = builder(verbose).try_init();
+}
+
+/// Only the demo's own records are let through, as the libraries' would drown them out.
+fn builder(verbose: bool) -> Builder {
+    let mut builder = Builder::new();
+    builder
+        .filter_module(module_path!().split("::").next().unwrap(), if verbose { LevelFilter::Debug } else { LevelFilter::Warn })
+        .default_format_timestamp(false);
+    if let Ok(filters) = std::env::var("RUST_LOG") {
+        builder.parse_filters(&filters);
+    }
+    builder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::{Level, Log, Metadata};
+
+    #[test]
+    fn test_logger() {
+        let metadata = |level, target| Metadata::builder().level(level).target(target).build();
+        let verbose = builder(true).build();
+        assert!(verbose.enabled(&metadata(Level::Debug, "quaternion_demo::animation")));
+        assert!(!verbose.enabled(&metadata(Level::Debug, "glium::context")));
+        let quiet = builder(false).build();
+        assert!(quiet.enabled(&metadata(Level::Warn, "quaternion_demo::camera")));
+        assert!(!quiet.enabled(&metadata(Level::Debug, "quaternion_demo::animation")));
+    }
+}
@@ -1,501 +1,3196 @@
+// `widget_ids!` recurses once for each widget id.
+#![recursion_limit = "256"]
+
 #[macro_use]
 extern crate conrod;
+extern crate env_logger;
 #[macro_use]
 extern crate glium;
+extern crate image;
+#[macro_use]
+extern crate log;
 extern crate nalgebra;
-
-mod quaternion;
+extern crate quaternion;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+extern crate toml;
+
+mod animation;
+mod camera;
+mod cli;
+mod config;
+mod controls;
+mod error;
+mod export;
+mod fps;
+mod import;
+mod logging;
+mod navcube;
+mod obj;
+mod plot;
+mod replay;
+mod rotation;
+mod shapes;
+mod sliders;
+mod theme;
+mod transform;
 
 use conrod::{widget, color, Colorable, Positionable, Labelable, Sizeable, Widget};
+use conrod::widget::Slider;
 use glium::{Program, Surface, IndexBuffer, VertexBuffer};
+use glium::backend::Facade;
+use camera::Camera;
+use config::Config;
+use controls::Action;
+use error::DemoError;
 use quaternion::Quaternion;
-use std::error::Error;
+use rotation::{Rotation, RotationList, StoredRotation};
+use sliders::RotationSliders;
+use transform::Transform;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 use nalgebra::base::Vector3;
-use nalgebra::core::Matrix4;
 
-#[derive(Copy, Clone)]
-struct Vertex {
+// Returns the contents of a file in `assets/`, as an `io::Result<Cow<'static, [u8]>>`. The assets
+// are built into the executable so it runs from anywhere, unless the `asset-files` feature is on.
+// Then they're read from the source tree when needed, so they can be edited without rebuilding.
+// Either way, `ASSETS_ENV` can point at another folder to read them from instead.
+#[cfg(not(feature = "asset-files"))]
+macro_rules! asset {
+    ($name:expr) => {
+        match assets_override() {
+            Some(dir) => read_asset(&dir.join($name)).map(std::borrow::Cow::Owned::<'static, [u8]>),
+            None => {
+                debug!("Using the built-in {}", $name);
+                Ok(std::borrow::Cow::Borrowed(
+                    &include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/", $name))[..]
+                ))
+            }
+        }
+    };
+}
+
+#[cfg(feature = "asset-files")]
+macro_rules! asset {
+    ($name:expr) => {
+        read_asset(&assets_dir().join($name)).map(std::borrow::Cow::Owned::<'static, [u8]>)
+    };
+}
+
+// The environment variable naming a folder of assets to use instead of the built-in ones.
+const ASSETS_ENV: &str = "QUATERNION_DEMO_ASSETS";
+
+// The folder named by `ASSETS_ENV`, if it's set.
+fn assets_override() -> Option<std::path::PathBuf> {
+    std::env::var_os(ASSETS_ENV).filter(|dir| !dir.is_empty()).map(std::path::PathBuf::from)
+}
+
+fn read_asset(path: &std::path::Path) -> std::io::Result<Vec<u8>> {
+    debug!("Reading {}", path.display());
+    std::fs::read(path)
+}
+
+// The folder that assets are read from at runtime: the one named by `ASSETS_ENV`, or else
+// `assets/` in the source tree.
+fn assets_dir() -> std::path::PathBuf {
+    assets_override().unwrap_or_else(|| std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("assets"))
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Vertex {
     position: [f32; 3],
     color: [f32; 4],
     normal: [f32; 3],
+    tex_coords: [f32; 2],
 }
-implement_vertex!(Vertex, position, color, normal);
+implement_vertex!(Vertex, position, color, normal, tex_coords);
 
-#[derive(Copy, Clone, Debug)]
-struct Transform {
-    position: Vector3<f32>,
-    rotation: Quaternion,
-    scale: Vector3<f32>,
+// A mesh's GPU buffers. They're created once, and shared by every model drawn with the mesh.
+struct Mesh {
+    vertex_buffer: VertexBuffer<Vertex>,
+    index_buffer: IndexBuffer<u16>,
+    // Multiplied with the vertex colors, if the mesh has one.
+    texture: Option<Rc<glium::texture::Texture2d>>,
 }
 
-impl Transform {
-    fn new() -> Transform {
-        Transform {
-            position: [0.0, 0.0, 0.0].into(),
-            rotation: Quaternion::identity(),
-            scale: [1.0, 1.0, 1.0].into(),
-        }
-    }
+struct Model {
+    mesh: Rc<Mesh>,
+    transform: Transform,
+}
 
-    fn to_matrix(&self) -> Matrix4<f32> {
-        let mut pos = Matrix4::identity();
-        pos[(0,3)] = self.position[0];
-        pos[(1,3)] = self.position[1];
-        pos[(2,3)] = self.position[2];
-        
-        let rot = self.rotation.into_matrix();
+impl Model {
+    fn new(mesh: Mesh) -> Model {
+        Model { mesh: Rc::new(mesh), transform: Transform::new() }
+    }
+}
 
-        let mut scale = Matrix4::identity();
-        scale[(0,0)] = self.scale[0];
-        scale[(1,1)] = self.scale[1];
-        scale[(2,2)] = self.scale[2];
+struct Light {
+    position: [f32; 3],
+    ambient: f32,
+    diffuse: f32,
+    // Strength of the highlight, and how tight it is. Higher shininess makes a smaller, sharper
+    // highlight.
+    specular: f32,
+    shininess: f32,
+    // Lights the back faces seen through openings or from inside a model, instead of leaving them
+    // at the ambient level.
+    double_sided: bool,
+    // Lights the colors in linear space rather than scaling their sRGB values.
+    gamma_correct: bool,
+    // Shades everything by its depth between the camera's clip planes instead, to see where
+    // they cut the scene.
+    show_depth: bool,
+}
 
-        pos * rot * scale
+impl Light {
+    fn new() -> Light {
+        Light {
+            position: [2.0, 2.0, 2.0],
+            ambient: 0.1,
+            diffuse: 1.0,
+            specular: 0.2,
+            shininess: 32.0,
+            double_sided: true,
+            gamma_correct: false,
+            show_depth: false,
+        }
     }
 }
 
-struct Model {
-    vertex_buffer: VertexBuffer<Vertex>,
-    index_buffer: IndexBuffer<u16>,
-    transform: Transform,
+// Starting width of the control panel on the left side of the window. Dragging its right edge
+// resizes it between the min and max widths.
+const PANEL_WIDTH: f64 = 300.0;
+const MIN_PANEL_WIDTH: f64 = 200.0;
+const MAX_PANEL_WIDTH: f64 = 600.0;
+// How far right of the panel's edge a drag still grabs it.
+const PANEL_GRIP_WIDTH: f64 = 6.0;
+// Width of the button left in place of the panel while it's collapsed.
+const COLLAPSED_PANEL_WIDTH: f64 = 20.0;
+// The margin around the panel's widgets, and between the overlays and the window's edges.
+const PAD: f64 = 10.0;
+
+// The 3D view fills the window to the right of the panel.
+fn shown_panel_width(collapsed: bool, width: f64) -> f64 {
+    if collapsed { COLLAPSED_PANEL_WIDTH } else { width }
 }
 
-struct Camera {
-    transform: Transform,
-    projection: Matrix4<f32>,
+// Generate the widget identifiers.
+widget_ids!(struct Ids {
+    canvas,
+
+    euler_angles_button,
+    axis_angle_button,
+
+    yaw, pitch, roll,
+    axis_x, axis_y, axis_z,
+    spherical_axis, axis_azimuth, axis_elevation,
+    axis_angle,
+
+    add_rotation,
+    insert_rotation,
+    clear_rotations,
+    animate_rotations,
+    export_matrix,
+    import_angles,
+    export_animation,
+    export_slerp_path,
+    print_rust_snippet,
+    print_numpy_array, print_glsl_mat4, print_urdf_rpy, print_gltf_rotation, print_rotation_6d,
+    reset_camera, frame_model, save_view, load_view,
+    front_view, top_view, right_view,
+    fov,
+    near_plane, far_plane,
+    orthographic,
+    transition_duration,
+    show_ghost,
+    show_outline,
+    rotate_camera,
+    highlight_edited,
+    show_slerp_path,
+    show_sphere_point, sphere_point_azimuth, sphere_point_elevation,
+    plot_angle, angle_plot, angle_plot_path, angle_plot_label,
+    short_way_highlight,
+    show_rotation_axes, rotation_list_label, rotation_list[], edit_rotation[], reset_rotation[], pin_rotation[], lock_body[], lock_shackle[],
+    keyframe_dots_label, keyframe_dots[],
+    background_red, background_green, background_blue,
+    light_x, light_y, light_z,
+    ambient, diffuse, specular, shininess, double_sided, gamma_correct,
+    compare, edit_first, edit_second,
+    compare_interpolation, slerp_label, nlerp_label,
+    label_x, label_y, label_z,
+    nav_cube_labels[],
+    mesh,
+    smooth_shading,
+    model_opacity,
+    scale_x, scale_y, scale_z,
+    position_x, position_y, position_z,
+    show_normals,
+    shader_error,
+    fps,
+    frame_graph, frame_graph_path, frame_graph_max, frame_graph_label,
+    help_button, help_overlay, help, close_help,
+    tooltip_background, tooltip,
+    collapse_panel, expand_panel,
+    light_theme,
+    precision,
+    axis_angle_readout,
+    look_direction_readout,
+    reference_angle_readout, set_reference, clear_reference,
+    aim_at_target, target_azimuth, target_elevation, target_angle_readout, aim,
+    snap_angles,
+    signed_angles,
+    align_signs,
+    collapse_rotations, list_notice,
+    halve_rotation, double_rotation,
+    camera_roll,
+    invert_orbit_y
+});
+
+// Everything the demo keeps from frame to frame, which its events, widgets and drawing each update
+// in turn. Each field is explained where `new()` sets it up. The window, the `Ui` and the widget ids
+// stay in `run()`, as the widgets borrow the `Ui` while they change the rest.
+struct Demo {
+    config: Config,
+    ui_theme: theme::Theme,
+    program: Program,
+    shader_error: Option<String>,
+    grid: Model,
+    world_axes: Model,
+    local_axes: Model,
+    axis_arrow: Model,
+    rotation_arrows: Vec<Model>,
+    trail: Model,
+    slerp_path: Model,
+    unit_sphere: Model,
+    wire_sphere: Model,
+    sphere_point: Model,
+    sphere_point_trail: Model,
+    camera: Camera,
+    nav_cube: Model,
+    nav_camera: Camera,
+    mesh: Option<usize>,
+    mesh_cache: Vec<Option<Rc<Mesh>>>,
+    model: Model,
+    quaternion_lists: [RotationList; 2],
+    active_list: usize,
+    comparing: bool,
+    compare_interpolation: bool,
+    list_notice: Option<String>,
+    sliders: RotationSliders,
+    euler_angles_mode: bool,
+    reference_orientation: Option<Quaternion>,
+    aim_at_target: bool,
+    target_angles: (f32, f32),
+    spherical_axis: bool,
+    snap_angles: bool,
+    angle_range: sliders::AngleRange,
+    show_ghost: bool,
+    show_outline: bool,
+    show_rotation_axes: bool,
+    rotate_camera: bool,
+    highlight_edited: bool,
+    show_slerp_path: bool,
+    show_sphere_point: bool,
+    sphere_point_angles: (f32, f32),
+    plot_angle: bool,
+    angle_history: plot::AngleHistory,
+    smooth_shading: bool,
+    model_opacity: f32,
+    show_normals: bool,
+    normals: Option<Model>,
+    background_color: [f32; 3],
+    precision: usize,
+    light: Light,
+    playback: animation::Playback,
+    animated_segment: Option<usize>,
+    short_way_highlight: Option<(f32, usize)>,
+    trail_points: VecDeque<Vector3<f32>>,
+    sphere_point_points: VecDeque<Vector3<f32>>,
+    cursor_position: (f64, f64),
+    orbiting: bool,
+    panning: bool,
+    show_fps: bool,
+    frame_times: fps::FrameTimes,
+    show_frame_graph: bool,
+    frame_graph: fps::FrameGraph,
+    frame_limiter: fps::FrameLimiter,
+    windowed_size: Option<(u32, u32)>,
+    target_fps: u32,
+    show_help: bool,
+    panel_collapsed: bool,
+    panel_width: f64,
+    resizing_panel: bool,
+    actions: Vec<Action>,
+    bookmarks: Vec<Option<camera::CameraState>>,
+    recorder: Option<replay::Recorder>,
+    replay: Option<replay::Replay>,
+    needs_redraw: bool,
 }
 
-impl Camera {
-    fn new() -> Self {
-        Camera {
-            transform: Transform::new(),
-            projection: Matrix4::new_perspective(16.0 / 9.0, 3.14 / 4.0, 1.0, 1000.0),
+impl Demo {
+    // Sets up the scene and the controls, with the model and rotations asked for on the command line.
+    fn new(display: &glium::Display, args: &cli::Args, config: Config) -> Result<Demo, DemoError> {
+        let ui_theme = config.theme;
+        let program = create_shader_program(display)?;
+        // Why the last shader reload failed, shown until a reload succeeds.
+        let shader_error: Option<String> = None;
+        let grid = create_grid_model(display)?;
+        let world_axes = create_world_axes_model(display)?;
+        let local_axes = create_local_axes_model(display)?;
+        let axis_arrow = create_axis_arrow_model(display)?;
+        // One arrow for each rotation in the list being edited, added as the list grows.
+        let rotation_arrows: Vec<Model> = Vec::new();
+        let trail = create_trail_model(display)?;
+        let slerp_path = create_slerp_path_model(display)?;
+        let unit_sphere = create_unit_sphere_model(display)?;
+        let wire_sphere = create_wire_sphere_model(display)?;
+        let sphere_point = create_sphere_point_model(display)?;
+        let sphere_point_trail = create_trail_model(display)?;
+        let mut camera = Camera::new();
+        // The navigation cube is seen in its corner through a camera that turns with the main one.
+        let nav_cube = create_nav_cube_model(display)?;
+        let mut nav_camera = Camera::new();
+        nav_camera.set_distance(navcube::CAMERA_DISTANCE);
+        nav_camera.set_orthographic(true);
+        // An OBJ file to show can be given on the command line, otherwise the axes cube is shown.
+        // Index of the mesh picked from `MESHES`, or `None` while showing an OBJ file.
+        let mut mesh = None;
+        // Each of `MESHES` is only uploaded the first time it's picked, and kept for switching back.
+        let mut mesh_cache: Vec<Option<Rc<Mesh>>> = vec![None; MESHES.len()];
+        let model = match args.model_path {
+            Some(ref path) => match load_obj(display, path) {
+                Ok(mut model) => {
+                    frame_model(display, &mut model, &mut camera)?;
+                    model
+                }
+                Err(e) => {
+                    warn!("failed to load {}: {}", path, e);
+                    let model = create_axes_model(display)?;
+                    mesh = Some(0);
+                    mesh_cache[0] = Some(model.mesh.clone());
+                    model
+                }
+            },
+            None => {
+                let model = create_axes_model(display)?;
+                mesh = Some(0);
+                mesh_cache[0] = Some(model.mesh.clone());
+                model
+            }
+        };
+
+        // The second list is only shown when comparing two sequences side by side.
+        // Rotations given on the command line start off the first list.
+        // The lists keep their rotations as `StoredRotation`s, and convert to and from `Quaternion`s
+        // to show, edit and export them.
+        let first_list = args.rotations.iter().cloned().map(StoredRotation::from).collect();
+        let quaternion_lists = [RotationList::new(first_list), RotationList::new(Vec::new())];
+        let active_list = 0;
+        let comparing = false;
+        // Animates the first list twice side by side, slerped and nlerped, in place of the second list.
+        let compare_interpolation = false;
+        // Clearing, collapsing and importing replace a whole list, pinned rotations too, so they're
+        // refused while any are pinned. Why the last was refused shows in the panel until none are.
+        let list_notice: Option<String> = None;
+        // The slider values for the edited rotation of the active list.
+        let sliders = RotationSliders::from_rotation(quaternion_lists[0].selected_rotation());
+
+        let euler_angles_mode = true;
+        // An orientation marked with "Set Reference", which the readout measures the angle from.
+        let reference_orientation: Option<Quaternion> = None;
+        // Measures how far the model's forward is from a target direction, and can turn it to point
+        // there. The target is in degrees of azimuth about Y from +Z towards +X, and of elevation.
+        let aim_at_target = false;
+        let target_angles: (f32, f32) = (90.0, 0.0);
+        // Aims the axis by its azimuth and elevation instead of its X, Y and Z.
+        let spherical_axis = false;
+        // Rounds the angle sliders to `config.angle_snap_degrees`, for setting exact common angles.
+        let snap_angles = false;
+        // Shows the angles from -180° to 180° instead of 0° to 360°.
+        let angle_range = sliders::AngleRange::Unsigned;
+        let show_ghost = false;
+        // Outlines the model, so it stands out in screenshots against a busy background.
+        let show_outline = false;
+        // Draws each rotation's own axis arrow, in its color from the panel's list.
+        let show_rotation_axes = false;
+        // Turns the camera by the rotation instead of the model, which stays as it was loaded. The
+        // camera turning one way looks like the model turning the other, since the view is the inverse
+        // of the camera's transform.
+        let rotate_camera = false;
+        // Shows the rotation being edited on its own, without the ones before it.
+        let highlight_edited = false;
+        // While animating, traces the current segment's slerp on a unit sphere around the model,
+        // next to the straight chord that lerping would cut through it.
+        let show_slerp_path = false;
+        // Shows what the rotation does to a point on a unit sphere around the model, with the point
+        // moving along the sphere as the animation turns it.
+        let show_sphere_point = false;
+        // Where the point starts, in degrees of azimuth about Y from +Z towards +X, and of elevation.
+        let sphere_point_angles: (f32, f32) = (45.0, 30.0);
+        // Graphs how far the animation has turned from its start, to show its changes of speed.
+        let plot_angle = false;
+        let angle_history = plot::AngleHistory::new();
+        // Whether the model's normals were last averaged for smooth shading, rather than made flat.
+        // Each mesh starts with its own normals, which are flat for the cube and for OBJ files.
+        let smooth_shading = false;
+        // Below one, the model is blended over the scene so the local axes inside it show through.
+        let model_opacity: f32 = 1.0;
+        let show_normals = false;
+        // Lines along the model's vertex normals, built when first shown.
+        let normals: Option<Model> = None;
+        let background_color: [f32; 3] = [0.0, 0.0, 0.0];
+        // Decimal places shown by the numeric readouts. Exported files always keep full precision.
+        let precision: usize = 6;
+        let light = Light::new();
+
+        let playback = animation::Playback::new();
+        // The segment being animated, to notice the frame each one starts on.
+        let animated_segment: Option<usize> = None;
+        // Seconds left of the highlight for a segment whose slerp negated its end keyframe, and the
+        // segment.
+        let short_way_highlight: Option<(f32, usize)> = None;
+        // Positions swept by a corner of the model during the animation, oldest first.
+        let trail_points: VecDeque<Vector3<f32>> = VecDeque::with_capacity(TRAIL_VERTICES);
+        // Positions the sphere's point has moved through during the animation, oldest first.
+        let sphere_point_points: VecDeque<Vector3<f32>> = VecDeque::with_capacity(TRAIL_VERTICES);
+
+        let cursor_position = (0.0, 0.0);
+        let orbiting = false;
+        let panning = false;
+
+        // The FPS readout is a diagnostic overlay, toggled with F3 so it stays out of screenshots.
+        let show_fps = false;
+        let frame_times = fps::FrameTimes::new();
+        // Graphs the latest frame times on F2, to catch the stalls that the FPS average hides.
+        let show_frame_graph = false;
+        let frame_graph = fps::FrameGraph::new();
+        let frame_limiter = fps::FrameLimiter::new();
+        // The window's size from before it went fullscreen with F11, to go back to. `None` when windowed.
+        let windowed_size: Option<(u32, u32)> = None;
+        let target_fps = args.target_fps.unwrap_or(config.target_fps);
+
+        let show_help = false;
+        // Hides the panel to give the 3D view the whole window, e.g. while presenting.
+        let panel_collapsed = false;
+        let panel_width = PANEL_WIDTH;
+        let resizing_panel = false;
+        // The actions asked for this frame by shortcut keys or their buttons, done after the widgets
+        // are set, so the shortcuts work even while their buttons are hidden.
+        let actions: Vec<Action> = Vec::new();
+        // Camera views stored with the number keys, which only last until the demo is closed.
+        let bookmarks: Vec<Option<camera::CameraState>> = vec![None; controls::BOOKMARK_KEYS.len()];
+
+        // With `--record`, the input events are written to a file as they happen, and with
+        // `--replay`, the ones in a file are played back along with the live ones.
+        let recorder = match args.record_path {
+            Some(ref path) => Some(replay::Recorder::create(path)?),
+            None => None,
+        };
+        let replay = match args.replay_path {
+            Some(ref path) => Some(replay::Replay::load(path).map_err(DemoError::InvalidData)?),
+            None => None,
+        };
+
+        // Set by input and while anything is moving, so that an idle demo doesn't redraw every frame.
+        let needs_redraw = true;
+
+        Ok(Demo {
+            config, ui_theme, program, shader_error, grid, world_axes, local_axes, axis_arrow,
+            rotation_arrows, trail, slerp_path, unit_sphere, wire_sphere, sphere_point,
+            sphere_point_trail, camera, nav_cube, nav_camera, mesh, mesh_cache, model,
+            quaternion_lists, active_list, comparing, compare_interpolation, list_notice, sliders,
+            euler_angles_mode, reference_orientation, aim_at_target, target_angles, spherical_axis,
+            snap_angles, angle_range, show_ghost, show_outline, show_rotation_axes, rotate_camera,
+            highlight_edited, show_slerp_path, show_sphere_point, sphere_point_angles, plot_angle,
+            angle_history, smooth_shading, model_opacity, show_normals, normals, background_color,
+            precision, light, playback, animated_segment, short_way_highlight, trail_points,
+            sphere_point_points, cursor_position, orbiting, panning, show_fps, frame_times,
+            show_frame_graph, frame_graph, frame_limiter, windowed_size, target_fps, show_help,
+            panel_collapsed, panel_width, resizing_panel, actions, bookmarks, recorder, replay,
+            needs_redraw
+        })
+    }
+
+    // Rounds an angle slider's value, in degrees, if the angles snap.
+    fn snapped_degrees(&self, degrees: f32) -> f32 {
+        if self.snap_angles {
+            sliders::snap_degrees(degrees, self.config.angle_snap_degrees, self.angle_range)
+        } else {
+            degrees
         }
     }
 }
-fn main() -> Result<(), Box<Error>> {
-    const WIDTH: u32 = 1280;
-    const HEIGHT: u32 = 720;
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<(), DemoError> {
+    let mut args = cli::parse_args(std::env::args().skip(1))
+        .map_err(|e| DemoError::InvalidData(format!("{}\n{}", e, cli::USAGE)))?;
+    logging::init(args.verbose);
+    let config = Config::load(config::CONFIG_PATH);
+    if cfg!(feature = "asset-files") || assets_override().is_some() {
+        info!("Using the assets in {}", assets_dir().display());
+    } else {
+        info!("Using the built-in assets");
+    }
+    if args.read_stdin {
+        let rotations = import::read_stdin()
+            .map_err(|e| e.to_string())
+            .and_then(|json| import::parse_rotations_json(&json));
+        match rotations {
+            Ok(rotations) => args.rotations.extend(rotations),
+            Err(e) => warn!("ignoring the rotations on standard input: {}", e),
+        }
+    }
+    if let Some(ref path) = args.render_path {
+        render_to_file(&config, &args, path)?;
+        info!("Rendered {}", path);
+        return Ok(());
+    }
 
     // Build the window.
-    let mut events_loop = glium::glutin::EventsLoop::new();
-    let window = glium::glutin::WindowBuilder::new()
-        .with_title("Quaternion Demo")
-        .with_dimensions(WIDTH, HEIGHT);
-    let context = glium::glutin::ContextBuilder::new()
-        .with_vsync(true)
-        .with_multisampling(4);
-    let display = glium::Display::new(window, context, &events_loop).unwrap();
+    let mut events_loop = create_events_loop()?;
+    let display = create_display(&events_loop, &config, args.msaa_samples.unwrap_or(config.msaa_samples))?;
 
     // construct our `Ui`.
-    let mut ui = conrod::UiBuilder::new([WIDTH as f64, HEIGHT as f64]).build();
+    let mut ui = conrod::UiBuilder::new([config.width as f64, config.height as f64])
+        .theme(config.theme.conrod_theme())
+        .build();
 
     // Generate the widget identifiers.
-    widget_ids!(struct Ids {
-        canvas,
-
-        euler_angles_button,
-        axis_angle_button,
-
-        yaw, pitch, roll,
-        axis_x, axis_y, axis_z,
-        axis_angle,
-
-        add_rotation,
-        clear_rotations,
-        animate_rotations
-    });
-    let ids = Ids::new(ui.widget_id_generator());
+    let mut ids = Ids::new(ui.widget_id_generator());
 
-    // Add a `Font` to the `Ui`'s `font::Map` from file.
-    const FONT_PATH: &'static str =
-        concat!(env!("CARGO_MANIFEST_DIR"), "/assets/NotoSans-Regular.ttf");
-    ui.fonts.insert_from_file(FONT_PATH).unwrap();
+    // Add a `Font` to the `Ui`'s `font::Map`.
+    let font = asset!("NotoSans-Regular.ttf")?;
+    let font = conrod::text::Font::from_bytes(font.into_owned())
+        .map_err(|e| DemoError::InvalidData(format!("invalid font: {}", e)))?;
+    let font_id = ui.fonts.insert(font);
 
     // A type used for converting `conrod::render::Primitives` into `Command`s that can be used
     // for drawing to the glium `Surface`.
-    let mut renderer = conrod::backend::glium::Renderer::new(&display).unwrap();
+    let mut renderer = conrod::backend::glium::Renderer::new(&display)?;
 
     // The image map describing each of our widget->image mappings (in our case, none).
     let image_map = conrod::image::Map::<glium::texture::Texture2d>::new();
 
-    let program = create_shader_program(&display)?;
-    let mut camera = Camera::new();
-    camera.transform.position[2] = 5.0;
-    let mut model = create_axes_model(&display)?;
+    let mut demo = Demo::new(&display, &args, config)?;
+    let mut last_frame = Instant::now();
+    loop {
+        let frame_start = Instant::now();
+        // A long stall, like dragging the window, shouldn't make the animation skip ahead.
+        const MAX_FRAME_TIME: f32 = 0.1;
+        let frame_time = duration_secs(frame_start - last_frame);
+        let dt = frame_time.min(MAX_FRAME_TIME);
+        last_frame = frame_start;
+        demo.frame_times.add(frame_time);
+        demo.frame_graph.add(frame_time);
+
+        if !handle_events(&mut demo, &mut ui, &display, &mut events_loop) {
+            break;
+        }
 
-    let mut quaternion_list: Vec<Quaternion> = vec![Quaternion::identity()];
-    let mut euler_angles: [f32; 3] = [0.0; 3];
-    let mut axis: Vector3<f32> = [1.0, 0.0, 0.0].into();
-    let mut axis_angle: f32 = 0.0;
+        // Set the widgets once per frame, after all of the frame's events have been handled.
+        // Frames without events still run the widgets, e.g. to keep the axis labels on the model.
+        set_widgets(&mut demo, &mut ui.set_widgets(), &mut ids, &display, font_id)?;
 
-    let mut euler_angles_mode = true;
+        let drawn = draw_frame(&mut demo, &mut ui, &display, &mut renderer, &image_map, dt)?;
 
-    let mut animating = false;
-    let mut animate_index = 0;
-    let mut animate_timer = 0.0;
-    
-    let mut events = Vec::new();
+        // The widgets are restyled when they're next set, so redraw once more to show them.
+        if demo.ui_theme != demo.config.theme {
+            ui.theme = demo.ui_theme.conrod_theme();
+            demo.needs_redraw = true;
+            demo.config.theme = demo.ui_theme;
+            save_config(&demo.config);
+        }
 
-    'render: loop {
-        events.clear();
-
-        // Get all the new events since the last frame.
-        events_loop.poll_events(|event| { events.push(event); });
-
-        // Process the events.
-        for event in events.drain(..) {
-
-            // Break from the loop upon `Escape` or closed window.
-            match event.clone() {
-                glium::glutin::Event::WindowEvent { event, .. } => {
-                    match event {
-                        glium::glutin::WindowEvent::Closed |
-                        glium::glutin::WindowEvent::KeyboardInput {
-                            input: glium::glutin::KeyboardInput {
-                                virtual_keycode: Some(glium::glutin::VirtualKeyCode::Escape),
-                                ..
-                            },
-                            ..
-                        } => break 'render,
-                        _ => (),
-                    }
+        // Events are still polled every frame while idle, so an idle demo is limited even when the
+        // frame rate is uncapped. The animation runs on the measured frame times, so its speed
+        // doesn't depend on the limit.
+        const IDLE_FPS: u32 = 60;
+        demo.frame_limiter.wait(if drawn || demo.target_fps != 0 { demo.target_fps } else { IDLE_FPS });
+    }
+
+    Ok(())
+}
+
+// Handles the events since the last frame, and those due from a replay. Returns `false` once the
+// demo should quit.
+fn handle_events(demo: &mut Demo, ui: &mut conrod::Ui, display: &glium::Display, events_loop: &mut glium::glutin::EventsLoop) -> bool {
+    let mut events = Vec::new();
+    // Get all the new events since the last frame.
+    events_loop.poll_events(|event| { events.push(event); });
+
+    // Each frame's events, as the demo's input and the UI's. Events the demo doesn't use only
+    // have the UI's input, and replayed ones have both built from the recording.
+    let mut inputs: Vec<(Option<replay::InputEvent>, Option<conrod::event::Input>)> = Vec::new();
+    let hidpi_factor = f64::from(display.gl_window().hidpi_factor());
+    for event in events.drain(..) {
+        let mut input_event = None;
+        if let glium::glutin::Event::WindowEvent { ref event, .. } = event {
+            match *event {
+                // Quit upon a closed window.
+                glium::glutin::WindowEvent::Closed => return false,
+                glium::glutin::WindowEvent::Resized(width, height) => {
+                    // Some platforms need the GL context resized along with the window.
+                    use glium::glutin::GlContext;
+                    display.gl_window().resize(width, height);
                 }
                 _ => (),
-            };
+            }
+            input_event = replay::InputEvent::from_window_event(event, hidpi_factor);
+        }
+        // Use the `winit` backend feature to convert the winit event to a conrod input.
+        inputs.push((input_event, conrod::backend::winit::convert_event(event, display)));
+    }
+    if let Some(ref mut replay) = demo.replay {
+        for input_event in replay.due() {
+            match input_event {
+                // Resizing the window sends its own event, which is handled like any other.
+                replay::InputEvent::Resized { width, height } => display.gl_window().set_inner_size(width, height),
+                _ => inputs.push((Some(input_event), input_event.to_conrod_input(ui.win_w, ui.win_h))),
+            }
+        }
+    }
+    if demo.replay.as_ref().map_or(false, |replay| replay.is_finished()) {
+        info!("Finished the replay");
+        demo.replay = None;
+    }
 
-            // Use the `winit` backend feature to convert the winit event to a conrod input.
-            let input = match conrod::backend::winit::convert_event(event, &display) {
-                None => continue,
-                Some(input) => input,
+    // Process the events.
+    for (input_event, input) in inputs.drain(..) {
+        if let Some(input_event) = input_event {
+            let record_error = match demo.recorder {
+                Some(ref mut recorder) => recorder.record(input_event).err(),
+                None => None,
             };
+            if let Some(e) = record_error {
+                warn!("failed to record the input, so the recording stops here: {}", e);
+                demo.recorder = None;
+            }
+            if !handle_input(demo, input_event, ui, display, events_loop) {
+                return false;
+            }
+        }
 
-            // Handle the input with the `Ui`.
+        // Handle the input with the `Ui`.
+        if let Some(input) = input {
             ui.handle_event(input);
+            demo.needs_redraw = true;
+        }
+    }
+    true
+}
+
+// Reacts to the demo's own input: keys, and the mouse over the 3D view. Returns `false` on
+// `Escape`, to quit.
+fn handle_input(demo: &mut Demo, input_event: replay::InputEvent, ui: &conrod::Ui, display: &glium::Display, events_loop: &glium::glutin::EventsLoop) -> bool {
+    match input_event {
+        replay::InputEvent::Key { key: glium::glutin::VirtualKeyCode::Escape, .. } => return false,
+        replay::InputEvent::Key { key, pressed: true, modifiers } => handle_key(demo, key, modifiers, ui, display, events_loop),
+        replay::InputEvent::MouseButton { button: glium::glutin::MouseButton::Left, pressed } => {
+            // Drags that start over the side panel belong to the UI, apart from the
+            // strip along its edge, which resizes it.
+            let past_edge = demo.cursor_position.0 - demo.panel_width;
+            demo.resizing_panel = pressed && !demo.panel_collapsed && past_edge >= 0.0 && past_edge < PANEL_GRIP_WIDTH;
+            // Clicking a face of the navigation cube looks at the model from that side.
+            let nav_cube_ndc = navcube::ndc_at(
+                navcube::rect(ui.win_w, ui.win_h),
+                [demo.cursor_position.0 - ui.win_w / 2.0, ui.win_h / 2.0 - demo.cursor_position.1],
+            );
+            if pressed {
+                if let Some(face) = nav_cube_ndc.and_then(|ndc| navcube::pick_face(&demo.nav_camera, ndc)) {
+                    demo.camera.look_from(face.normal.into(), face.up.into());
+                }
+            }
+            demo.orbiting = pressed && !demo.resizing_panel && nav_cube_ndc.is_none()
+                && demo.cursor_position.0 > shown_panel_width(demo.panel_collapsed, demo.panel_width);
+        }
+        replay::InputEvent::MouseButton { button: glium::glutin::MouseButton::Middle, pressed } => {
+            demo.panning = pressed && demo.cursor_position.0 > shown_panel_width(demo.panel_collapsed, demo.panel_width);
+        }
+        replay::InputEvent::CursorMoved { x, y } => {
+            let position = (x, y);
+            let dx = (position.0 - demo.cursor_position.0) as f32;
+            let dy = (position.1 - demo.cursor_position.1) as f32;
+            let past_edge = position.0 - demo.panel_width;
+            let over_grip = !demo.panel_collapsed && past_edge >= 0.0 && past_edge < PANEL_GRIP_WIDTH;
+            display.gl_window().set_cursor(if demo.resizing_panel || over_grip {
+                glium::glutin::MouseCursor::EwResize
+            } else {
+                glium::glutin::MouseCursor::Default
+            });
+            if demo.resizing_panel {
+                demo.panel_width = position.0.max(MIN_PANEL_WIDTH).min(MAX_PANEL_WIDTH);
+            }
+            if demo.orbiting {
+                const ORBIT_SPEED: f32 = 0.01;
+                let dy = if demo.config.invert_orbit_y { -dy } else { dy };
+                demo.camera.orbit(-dx * ORBIT_SPEED, -dy * ORBIT_SPEED);
+            }
+            if demo.panning {
+                const PAN_SPEED: f32 = 0.001;
+                demo.camera.pan(dx * PAN_SPEED, dy * PAN_SPEED);
+            }
+            demo.cursor_position = position;
+        }
+        // Scrolling over the side panel scrolls the panel instead.
+        replay::InputEvent::Scroll { y, pixels, .. } if demo.cursor_position.0 > shown_panel_width(demo.panel_collapsed, demo.panel_width) => {
+            const PIXELS_PER_LINE: f32 = 20.0;
+            demo.camera.zoom(if pixels { y / PIXELS_PER_LINE } else { y });
+        }
+        _ => (),
+    }
+    true
+}
 
-            // Set the widgets.
-            let ui = &mut ui.set_widgets();
+// The keys pressed over the 3D view or the panel. Shortcuts are left to the text box that has the
+// keyboard, if any.
+fn handle_key(demo: &mut Demo, key: glium::glutin::VirtualKeyCode, modifiers: glium::glutin::ModifiersState, ui: &conrod::Ui, display: &glium::Display, events_loop: &glium::glutin::EventsLoop) {
+    match key {
+        // Like the exports, this prints the list being edited rather than the model's.
+        glium::glutin::VirtualKeyCode::P =>
+            println!("{}", export::format_rotation(demo.playback.current(&demo.quaternion_lists[demo.active_list].rotations()), demo.precision)),
+        glium::glutin::VirtualKeyCode::M => {
+            // As the model was last drawn, which is without its rotation when the camera turns instead.
+            let mut drawn_transform = demo.model.transform;
+            if demo.rotate_camera {
+                drawn_transform.rotation = Quaternion::identity();
+            }
+            let (model_matrix, view_matrix, projection_matrix) = mvp_matrices(&drawn_transform, &demo.camera);
+            println!("{}", export::format_mvp(&model_matrix, &view_matrix, &projection_matrix, demo.precision));
+        }
+        glium::glutin::VirtualKeyCode::F12 =>
+            match save_screenshot(display) {
+                Ok(path) => info!("Saved screenshot to {}", path),
+                Err(e) => warn!("failed to save screenshot: {}", e),
+            },
+        glium::glutin::VirtualKeyCode::F2 =>
+            demo.show_frame_graph = !demo.show_frame_graph,
+        glium::glutin::VirtualKeyCode::F3 =>
+            demo.show_fps = !demo.show_fps,
+        glium::glutin::VirtualKeyCode::F4 =>
+            demo.light.show_depth = !demo.light.show_depth,
+        glium::glutin::VirtualKeyCode::F11 => {
+            // The window is resized to fit, and the next frame's viewports follow its
+            // new size.
+            let window = display.gl_window();
+            match demo.windowed_size.take() {
+                Some((width, height)) => {
+                    window.set_fullscreen(None);
+                    window.set_inner_size(width, height);
+                }
+                // Without a monitor, asking for the current one would panic.
+                None => if events_loop.get_available_monitors().next().is_none() {
+                    warn!("failed to go fullscreen: no monitor was found");
+                } else {
+                    demo.windowed_size = Some(window.get_inner_size().unwrap_or((demo.config.width, demo.config.height)));
+                    window.set_fullscreen(Some(window.get_current_monitor()));
+                },
+            }
+        }
+        // Rebuild the shaders from `assets/`, keeping the old ones if the new ones don't compile.
+        glium::glutin::VirtualKeyCode::F5 =>
+            match reload_shader_program(display) {
+                Ok(reloaded) => {
+                    info!("Reloaded the shaders");
+                    demo.program = reloaded;
+                    demo.shader_error = None;
+                }
+                Err(e) => {
+                    warn!("failed to reload the shaders: {}", e);
+                    demo.shader_error = Some(e.to_string());
+                }
+            },
+        _ => {
+            // Typing into a text box shouldn't trigger shortcuts.
+            if ui.global_input().current.widget_capturing_keyboard.is_none() {
+                demo.actions.extend(controls::shortcut_action(key, modifiers));
+            }
+        }
+    }
+}
 
-            widget::Canvas::new()
-                .color(color::DARK_GRAY)
-                .align_top()
-                .align_left()
-                .w(300.0)
-                .h(ui.win_h)
-                .set(ids.canvas, ui);
+// Sets the frame's widgets: the overlays on the 3D view, the panel, and the tooltip and help over
+// both.
+fn set_widgets(demo: &mut Demo, ui: &mut conrod::UiCell, ids: &mut Ids, display: &glium::Display, font_id: conrod::text::font::Id) -> Result<(), DemoError> {
+    let viewport_width = ui.win_w - shown_panel_width(demo.panel_collapsed, demo.panel_width);
+    set_view_labels(demo, ui, ids, viewport_width);
+    set_overlays(demo, ui, ids, viewport_width);
+
+    if widget::Button::new()
+        .label("?")
+        .w_h(30.0, 30.0)
+        .top_right_with_margin_on(ui.window, PAD)
+        .set(ids.help_button, ui)
+        .was_clicked()
+    {
+        demo.actions.push(Action::ToggleHelp);
+    }
 
-            const PAD: f64 = 10.0;
+    if let Some(ref message) = demo.shader_error {
+        widget::Text::new(message)
+            .color(color::RED)
+            .font_size(14)
+            .w((viewport_width - navcube::SIZE - navcube::MARGIN_RIGHT - 2.0 * PAD).max(PAD))
+            .top_left_with_margins_on(ui.window, PAD, ui.win_w - viewport_width + PAD)
+            .set(ids.shader_error, ui);
+    }
 
-            use widget::Slider;
+    if demo.panel_collapsed {
+        if widget::Button::new()
+            .label(">")
+            .top_left()
+            .w(COLLAPSED_PANEL_WIDTH)
+            .h(ui.win_h)
+            .set(ids.expand_panel, ui)
+            .was_clicked()
+        {
+            demo.panel_collapsed = false;
+        }
+    } else {
+        set_panel(demo, ui, ids, display)?;
+    }
 
-            if widget::Button::new()
-                .label("Euler Angles")
-                .label_color(if euler_angles_mode { color::RED } else { color::BLACK })
-                .top_left_with_margin_on(ids.canvas, PAD)
-                .w(120.0)
-                .h(30.0)
-                .padded_w_of(ids.canvas, PAD)
-                .set(ids.euler_angles_button, ui)
-                .was_clicked()
-            {
-                euler_angles_mode = true;
-            }
+    apply_actions(demo);
+
+    // Explain the control under the mouse, beside the mouse. The help covers the panel, so
+    // there's nothing to explain while it's shown.
+    let tooltip_controls = [
+        (ids.euler_angles_button, "Euler Angles"),
+        (ids.axis_angle_button, "Axis Angle"),
+        (ids.yaw, "Yaw"),
+        (ids.pitch, "Pitch"),
+        (ids.roll, "Roll"),
+        (ids.spherical_axis, "Spherical Axis"),
+        (ids.axis_azimuth, "Axis Azimuth"),
+        (ids.axis_elevation, "Axis Elevation"),
+        (ids.axis_x, "Axis X"),
+        (ids.axis_y, "Axis Y"),
+        (ids.axis_z, "Axis Z"),
+        (ids.axis_angle, "Angle"),
+        (ids.add_rotation, "Add Rotation"),
+        (ids.insert_rotation, "Insert Rotation Before Edited"),
+        (ids.clear_rotations, "Clear Rotations"),
+        (ids.align_signs, "Re-align Signs"),
+        (ids.collapse_rotations, "Collapse Rotations"),
+        (ids.animate_rotations, "Animate Rotations"),
+        (ids.reset_camera, "Reset Camera"),
+    ];
+    let hovered = tooltip_controls.iter().find(|&&(id, _)| ui.widget_input(id).mouse().is_some());
+    if let Some(text) = hovered.and_then(|&(_, label)| controls::tooltip(label)).filter(|_| !demo.show_help) {
+        const TOOLTIP_FONT_SIZE: u32 = 12;
+        const TOOLTIP_PAD: f64 = 4.0;
+        // Below and right of the pointer, or on its other side where that's off the
+        // window. It mustn't go under the pointer, which would then be over it instead.
+        const TOOLTIP_OFFSET: f64 = 16.0;
+        let text_width = ui.fonts.get(font_id)
+            .map_or(0.0, |font| conrod::text::line::width(&text, font, TOOLTIP_FONT_SIZE));
+        let size = [text_width + 2.0 * TOOLTIP_PAD, f64::from(TOOLTIP_FONT_SIZE) + 2.0 * TOOLTIP_PAD];
+        let [mouse_x, mouse_y] = ui.global_input().current.mouse.xy;
+        let x_offset = TOOLTIP_OFFSET + size[0] / 2.0;
+        let y_offset = TOOLTIP_OFFSET + size[1] / 2.0;
+        let x = if mouse_x + x_offset + size[0] / 2.0 <= ui.win_w / 2.0 { mouse_x + x_offset } else { mouse_x - x_offset };
+        let y = if mouse_y - y_offset - size[1] / 2.0 >= -ui.win_h / 2.0 { mouse_y - y_offset } else { mouse_y + y_offset };
+        widget::Canvas::new()
+            .color(color::rgba(0.0, 0.0, 0.0, 0.85))
+            .wh(size)
+            .x_y(x, y)
+            .floating(true)
+            .set(ids.tooltip_background, ui);
+        widget::Text::new(&text)
+            .color(color::WHITE)
+            .font_size(TOOLTIP_FONT_SIZE)
+            .middle_of(ids.tooltip_background)
+            .set(ids.tooltip, ui);
+    }
 
-            if widget::Button::new()
-                .label("Axis Angle")
-                .label_color(if !euler_angles_mode { color::RED } else { color::BLACK })
-                .h(30.0)
-                .set(ids.axis_angle_button, ui)
-                .was_clicked()
-            {
-                euler_angles_mode = false;
+    // Set last and floating, so it covers both the 3D view and the panel.
+    if demo.show_help {
+        widget::Canvas::new()
+            .color(color::rgba(0.0, 0.0, 0.0, 0.75))
+            .wh_of(ui.window)
+            .middle_of(ui.window)
+            .floating(true)
+            .set(ids.help_overlay, ui);
+
+        widget::Text::new(&controls::help_text())
+            .color(color::WHITE)
+            .font_size(16)
+            .middle_of(ids.help_overlay)
+            .set(ids.help, ui);
+
+        if widget::Button::new()
+            .label("Close")
+            .w_h(120.0, 30.0)
+            .mid_bottom_with_margin_on(ids.help_overlay, 4.0 * PAD)
+            .set(ids.close_help, ui)
+            .was_clicked()
+        {
+            demo.show_help = false;
+        }
+    }
+    Ok(())
+}
+
+// Labels on the 3D view, kept over the points they name.
+fn set_view_labels(demo: &mut Demo, ui: &mut conrod::UiCell, ids: &mut Ids, viewport_width: f64) {
+    let label_color = ui.theme().label_color;
+    // Label the ends of the world axes, at their positions projected onto the window.
+    let axis_labels = [
+        ("X", [WORLD_AXIS_LENGTH, 0.0, 0.0], color::RED, ids.label_x),
+        ("Y", [0.0, WORLD_AXIS_LENGTH, 0.0], color::GREEN, ids.label_y),
+        ("Z", [0.0, 0.0, WORLD_AXIS_LENGTH], color::BLUE, ids.label_z),
+    ];
+    for &(label, end, label_color, id) in &axis_labels {
+        if let Some((x, y)) = demo.camera.project(end.into()) {
+            widget::Text::new(label)
+                .color(label_color)
+                .font_size(16)
+                .x_y(
+                    (ui.win_w - viewport_width + f64::from(x) * viewport_width) / 2.0,
+                    f64::from(y) * ui.win_h / 2.0,
+                )
+                .parent(ui.window)
+                .set(id, ui);
+        }
+    }
+
+    // Name the interpolation above each of the models comparing them.
+    if demo.compare_interpolation {
+        const LABEL_HEIGHT: f32 = 1.2;
+        let above_model = demo.model.transform.position + Vector3::new(0.0, LABEL_HEIGHT, 0.0);
+        let labels = [
+            ("Slerp", above_model, ids.slerp_label),
+            ("Nlerp", above_model + Vector3::new(COMPARE_OFFSET, 0.0, 0.0), ids.nlerp_label),
+        ];
+        for &(label, position, id) in &labels {
+            if let Some((x, y)) = demo.camera.project(position) {
+                widget::Text::new(label)
+                    .color(label_color)
+                    .font_size(16)
+                    .x_y(
+                        (ui.win_w - viewport_width + f64::from(x) * viewport_width) / 2.0,
+                        f64::from(y) * ui.win_h / 2.0,
+                    )
+                    .parent(ui.window)
+                    .set(id, ui);
             }
+        }
+    }
 
-            if euler_angles_mode {
-                for value in Slider::new(euler_angles[0].to_degrees(), 0.0, 360.0)
-                    .label("Yaw")
-                    .label_color(color::RED)
-                    .padded_w_of(ids.canvas, PAD)
-                    .h(30.0)
-                    .set(ids.yaw, ui)
-                {
-                    euler_angles[0] = value.to_radians();
-                    let cur_quaternion = quaternion_list.last_mut().unwrap();
-                    *cur_quaternion = Quaternion::from_euler_angles(euler_angles[0], euler_angles[1], euler_angles[2]);
-                }
+    // Label the navigation cube's faces that are turned towards the camera.
+    let nav_rect = navcube::rect(ui.win_w, ui.win_h);
+    let towards_camera = demo.nav_camera.rotation().rotate_vector([0.0, 0.0, 1.0].into());
+    ids.nav_cube_labels.resize(navcube::FACES.len(), &mut ui.widget_id_generator());
+    for (i, face) in navcube::FACES.iter().enumerate() {
+        const MIN_FACING: f32 = 0.3;
+        let normal = Vector3::from(face.normal);
+        if normal.dot(&towards_camera) < MIN_FACING {
+            continue;
+        }
+        if let Some((x, y)) = demo.nav_camera.project(normal * 0.5) {
+            widget::Text::new(face.label)
+                .color(color::WHITE)
+                .font_size(12)
+                .x_y(
+                    nav_rect.x() + f64::from(x) * nav_rect.w() / 2.0,
+                    nav_rect.y() + f64::from(y) * nav_rect.h() / 2.0,
+                )
+                .parent(ui.window)
+                .set(ids.nav_cube_labels[i], ui);
+        }
+    }
+}
 
-                for value in Slider::new(euler_angles[1].to_degrees(), 0.0, 360.0)
-                    .label("Pitch")
-                    .label_color(color::RED)
-                    .padded_w_of(ids.canvas, PAD)
-                    .h(30.0)
-                    .set(ids.pitch, ui)
-                {
-                    euler_angles[1] = value.to_radians();
-                    let cur_quaternion = quaternion_list.last_mut().unwrap();
-                    *cur_quaternion = Quaternion::from_euler_angles(euler_angles[0], euler_angles[1], euler_angles[2]);
-                }
+// The graphs and notices in the corners of the 3D view.
+fn set_overlays(demo: &mut Demo, ui: &mut conrod::UiCell, ids: &mut Ids, viewport_width: f64) {
+    if demo.plot_angle && !demo.angle_history.samples.is_empty() {
+        const PLOT_SIZE: [f64; 2] = [240.0, 120.0];
+        widget::Rectangle::fill_with(PLOT_SIZE, color::rgba(0.0, 0.0, 0.0, 0.5))
+            .bottom_left_with_margins_on(ui.window, PAD, ui.win_w - viewport_width + PAD)
+            .set(ids.angle_plot, ui);
+        widget::Text::new("Angle from start, 0 to 180°")
+            .color(color::WHITE)
+            .font_size(12)
+            .up_from(ids.angle_plot, 4.0)
+            .align_left_of(ids.angle_plot)
+            .set(ids.angle_plot_label, ui);
+        if let Some(rect) = ui.rect_of(ids.angle_plot) {
+            let duration = animated_segments(&demo.quaternion_lists, demo.comparing) as f32 * animation::SEGMENT_DURATION;
+            widget::PointPath::new(demo.angle_history.points_in(rect, duration))
+                .wh_of(ids.angle_plot)
+                .middle_of(ids.angle_plot)
+                .color(color::YELLOW)
+                .thickness(2.0)
+                .set(ids.angle_plot_path, ui);
+        }
+    }
 
-                for value in Slider::new(euler_angles[2].to_degrees(), 0.0, 360.0)
-                    .label("Roll")
-                    .label_color(color::RED)
-
-                    .padded_w_of(ids.canvas, PAD)
-                    .h(30.0)
-                    .set(ids.roll, ui)
-                {
-                    euler_angles[2] = value.to_radians();
-                    let cur_quaternion = quaternion_list.last_mut().unwrap();
-                    *cur_quaternion = Quaternion::from_euler_angles(euler_angles[0], euler_angles[1], euler_angles[2]);
-                }
-            } else {
-                for value in Slider::new(axis[0], 0.0, 1.0)
-                    .label("Axis X")
-                    .label_color(color::RED)
-
-                    .padded_w_of(ids.canvas, PAD)
-                    .h(30.0)
-                    .set(ids.axis_x, ui)
-                {
-                    axis[0] = value;
-                    let n = axis.clone().normalize();
-                    let cur_quaternion = quaternion_list.last_mut().unwrap();
-                    *cur_quaternion = Quaternion::from_axis_angle(n[0], n[1], n[2], axis_angle);
-                }
+    // Where the double cover matters: the slerp picks the short way round by negating the
+    // keyframe, so the composed quaternion's signs jump.
+    if let Some((_, index)) = demo.short_way_highlight {
+        let text = format!("Short way round: keyframe {} negated", index + 1);
+        let text = widget::Text::new(&text)
+            .color(color::ORANGE)
+            .font_size(14);
+        let text = if demo.plot_angle && !demo.angle_history.samples.is_empty() {
+            text.up_from(ids.angle_plot_label, 4.0).align_left_of(ids.angle_plot_label)
+        } else {
+            text.bottom_left_with_margins_on(ui.window, PAD, ui.win_w - viewport_width + PAD)
+        };
+        text.set(ids.short_way_highlight, ui);
+    }
 
-                for value in Slider::new(axis[1], 0.0, 1.0)
-                    .label("Axis Y")
-                    .label_color(color::RED)
-
-                    .padded_w_of(ids.canvas, PAD)
-                    .h(30.0)
-                    .set(ids.axis_y, ui)
-                {
-                    axis[1] = value;
-                    let n = axis.clone().normalize();
-                    let cur_quaternion = quaternion_list.last_mut().unwrap();
-                    *cur_quaternion = Quaternion::from_axis_angle(n[0], n[1], n[2], axis_angle);
-                }
+    if demo.show_fps {
+        widget::Text::new(&demo.frame_times.label())
+            .color(color::WHITE)
+            .font_size(14)
+            .bottom_right_with_margin_on(ui.window, PAD)
+            .set(ids.fps, ui);
+    }
 
-                for value in Slider::new(axis[2], 0.0, 1.0)
-                    .label("Axis Z")
-                    .label_color(color::RED)
-
-                    .padded_w_of(ids.canvas, PAD)
-                    .h(30.0)
-                    .set(ids.axis_z, ui)
-                {
-                    axis[2] = value;
-                    let n = axis.clone().normalize();
-                    let cur_quaternion = quaternion_list.last_mut().unwrap();
-                    *cur_quaternion = Quaternion::from_axis_angle(n[0], n[1], n[2], axis_angle);
-                }
+    // Above the FPS readout, with a line across at the slowest frame.
+    if demo.show_frame_graph {
+        const GRAPH_SIZE: [f64; 2] = [240.0, 80.0];
+        widget::Rectangle::fill_with(GRAPH_SIZE, color::rgba(0.0, 0.0, 0.0, 0.5))
+            .bottom_right_with_margins_on(ui.window, PAD + 30.0, PAD)
+            .set(ids.frame_graph, ui);
+        let max_ms = demo.frame_graph.max().unwrap_or(0.0) * 1000.0;
+        let label = format!("Frame times, max {:.1} ms", max_ms);
+        widget::Text::new(&label)
+            .color(color::WHITE)
+            .font_size(12)
+            .up_from(ids.frame_graph, 4.0)
+            .align_left_of(ids.frame_graph)
+            .set(ids.frame_graph_label, ui);
+        if let Some(rect) = ui.rect_of(ids.frame_graph) {
+            let max_y = rect.bottom() + f64::from(max_ms / 1000.0 / demo.frame_graph.top()) * rect.h();
+            widget::PointPath::new(vec![[rect.left(), max_y], [rect.right(), max_y]])
+                .wh_of(ids.frame_graph)
+                .middle_of(ids.frame_graph)
+                .color(color::ORANGE)
+                .thickness(1.0)
+                .set(ids.frame_graph_max, ui);
+            widget::PointPath::new(demo.frame_graph.points_in(rect))
+                .wh_of(ids.frame_graph)
+                .middle_of(ids.frame_graph)
+                .color(color::GREEN)
+                .thickness(1.0)
+                .set(ids.frame_graph_path, ui);
+        }
+    }
+}
 
-                for value in Slider::new(axis_angle.to_degrees(), 0.0, 360.0)
-                    .label("Angle")
-                    .label_color(color::RED)
-
-                    .padded_w_of(ids.canvas, PAD)
-                    .h(30.0)
-                    .set(ids.axis_angle, ui)
-                {
-                    axis_angle = value.to_radians();
-                    let n = axis.clone().normalize();
-                    let cur_quaternion = quaternion_list.last_mut().unwrap();
-                    *cur_quaternion = Quaternion::from_axis_angle(n[0], n[1], n[2], axis_angle);
-                }
-            }
+// The control panel down the left of the window. Each group of controls goes below the one before,
+// so they're set in the order they're shown.
+fn set_panel(demo: &mut Demo, ui: &mut conrod::UiCell, ids: &mut Ids, display: &glium::Display) -> Result<(), DemoError> {
+    let panel_color = ui.theme().background_color;
+    widget::Canvas::new()
+        .color(panel_color)
+        .align_top()
+        .align_left()
+        .w(demo.panel_width)
+        .h(ui.win_h)
+        .scroll_kids_vertically()
+        .set(ids.canvas, ui);
+
+    if widget::Button::new()
+        .label("Hide Panel")
+        .top_left_with_margin_on(ids.canvas, PAD)
+        .padded_w_of(ids.canvas, PAD)
+        .h(30.0)
+        .set(ids.collapse_panel, ui)
+        .was_clicked()
+    {
+        demo.panel_collapsed = true;
+    }
 
-            if widget::Button::new()
-                .label("Add Rotation")
-                .set(ids.add_rotation, ui)
-                .was_clicked()
-            {
-                euler_angles = [0.0, 0.0, 0.0];
-                axis_angle = 0.0;
-                quaternion_list.push(Quaternion::identity());
-            }
+    set_rotation_mode_widgets(demo, ui, ids);
+    // Right-clicking or double-clicking a rotation slider sets it to zero.
+    if demo.euler_angles_mode {
+        set_euler_angle_sliders(demo, ui, ids);
+    } else {
+        set_axis_angle_sliders(demo, ui, ids);
+    }
 
-            if widget::Button::new()
-                .label("Clear Rotations")
-                .set(ids.clear_rotations, ui)
-                .was_clicked()
-            {
-                quaternion_list = vec![Quaternion::identity()];
-            }
+    // The rotation as shown, so it follows the animation while playing. The readouts
+    // and exports are of the list being edited, which may be the second one.
+    let shown_rotation = demo.playback.current(&demo.quaternion_lists[demo.active_list].rotations());
+    set_readouts(demo, ui, ids, shown_rotation);
+    set_list_widgets(demo, ui, ids);
+    set_rotation_rows(demo, ui, ids);
+    set_export_widgets(demo, ui, ids, shown_rotation);
+    set_print_widgets(demo, ui, ids, shown_rotation);
+    set_camera_widgets(demo, ui, ids, display);
+    set_display_widgets(demo, ui, ids);
+    set_lighting_widgets(demo, ui, ids);
+    set_model_widgets(demo, ui, ids, display)
+}
+
+fn set_rotation_mode_widgets(demo: &mut Demo, ui: &mut conrod::UiCell, ids: &mut Ids) {
+    let label_color = ui.theme().label_color;
+    if widget::Button::new()
+        .label("Euler Angles")
+        .label_color(if demo.euler_angles_mode { color::RED } else { label_color })
+        .w(120.0)
+        .h(30.0)
+        .padded_w_of(ids.canvas, PAD)
+        .set(ids.euler_angles_button, ui)
+        .was_clicked()
+    {
+        demo.euler_angles_mode = true;
+        demo.sliders = RotationSliders::from_rotation(demo.quaternion_lists[demo.active_list].selected_rotation());
+    }
+
+    if widget::Button::new()
+        .label("Axis Angle")
+        .label_color(if !demo.euler_angles_mode { color::RED } else { label_color })
+        .h(30.0)
+        .set(ids.axis_angle_button, ui)
+        .was_clicked()
+    {
+        demo.euler_angles_mode = false;
+        demo.sliders = RotationSliders::from_rotation(demo.quaternion_lists[demo.active_list].selected_rotation());
+    }
+
+    for value in widget::Toggle::new(demo.comparing)
+        .label("Compare Two Sequences")
+        .set(ids.compare, ui)
+    {
+        demo.comparing = value;
+        if demo.comparing {
+            demo.compare_interpolation = false;
+        } else {
+            demo.active_list = 0;
+        }
+    }
+
+    for value in widget::Toggle::new(demo.compare_interpolation)
+        .label("Compare Slerp and Nlerp")
+        .set(ids.compare_interpolation, ui)
+    {
+        demo.compare_interpolation = value;
+        if demo.compare_interpolation && demo.comparing {
+            demo.comparing = false;
+            demo.active_list = 0;
+            demo.sliders = RotationSliders::from_rotation(demo.quaternion_lists[demo.active_list].selected_rotation());
+        }
+    }
 
+    if demo.comparing {
+        let lists = [("Edit First", ids.edit_first), ("Edit Second", ids.edit_second)];
+        for (i, &(label, id)) in lists.iter().enumerate() {
             if widget::Button::new()
-                .label("Animate Rotations")
-                .set(ids.animate_rotations, ui)
+                .label(label)
+                .label_color(if demo.active_list == i { color::RED } else { label_color })
+                .set(id, ui)
                 .was_clicked()
+                && demo.active_list != i
             {
-                animating = true;
-                animate_index = 0;
-                animate_timer = 0.0;
+                demo.active_list = i;
+                demo.sliders = RotationSliders::from_rotation(demo.quaternion_lists[demo.active_list].selected_rotation());
             }
         }
+    }
 
-        // Draw the `Ui` if it has changed.
-        let primitives = ui.draw();
-        {
-            renderer.fill(&display, primitives, &image_map);
-            let mut target = display.draw();
-            target.clear_color_and_depth((0.0, 0.0, 0.0, 1.0), 1.0);
+    for value in widget::Toggle::new(demo.snap_angles)
+        .label(&format!("Snap Angles to {}°", demo.config.angle_snap_degrees))
+        .set(ids.snap_angles, ui)
+    {
+        demo.snap_angles = value;
+    }
+    for value in widget::Toggle::new(demo.angle_range == sliders::AngleRange::Signed)
+        .label("Signed Angles")
+        .set(ids.signed_angles, ui)
+    {
+        demo.angle_range = if value { sliders::AngleRange::Signed } else { sliders::AngleRange::Unsigned };
+    }
+}
 
-            // Draw the model.
-            let mut rotation = Quaternion::identity();
+fn set_euler_angle_sliders(demo: &mut Demo, ui: &mut conrod::UiCell, ids: &mut Ids) {
+    let (angle_min, angle_max) = demo.angle_range.bounds();
+    // A pinned rotation can't be edited, so its sliders are greyed out.
+    let edited_pinned = demo.quaternion_lists[demo.active_list].selected_entry().pinned;
+
+    for value in Slider::new(demo.angle_range.fit(demo.sliders.euler_angles[0].to_degrees()), angle_min, angle_max)
+        .label("Yaw")
+        .label_color(color::RED)
+        .padded_w_of(ids.canvas, PAD)
+        .h(30.0)
+        .enabled(!edited_pinned)
+        .set(ids.yaw, ui)
+        .into_iter()
+        .chain(reset_to_zero(ui, ids.yaw).filter(|_| !edited_pinned))
+    {
+        demo.sliders.euler_angles[0] = demo.snapped_degrees(value).to_radians();
+        demo.quaternion_lists[demo.active_list].set_selected(demo.sliders.euler_rotation());
+    }
 
-            if animating {
-                for q in quaternion_list.iter().take(animate_index) {
-                    rotation *= *q;
-                }
+    for value in Slider::new(demo.angle_range.fit(demo.sliders.euler_angles[1].to_degrees()), angle_min, angle_max)
+        .label("Pitch")
+        .label_color(color::RED)
+        .padded_w_of(ids.canvas, PAD)
+        .h(30.0)
+        .enabled(!edited_pinned)
+        .set(ids.pitch, ui)
+        .into_iter()
+        .chain(reset_to_zero(ui, ids.pitch).filter(|_| !edited_pinned))
+    {
+        demo.sliders.euler_angles[1] = demo.snapped_degrees(value).to_radians();
+        demo.quaternion_lists[demo.active_list].set_selected(demo.sliders.euler_rotation());
+    }
 
-                rotation = rotation.slerp(rotation*quaternion_list[animate_index], animate_timer);
+    for value in Slider::new(demo.angle_range.fit(demo.sliders.euler_angles[2].to_degrees()), angle_min, angle_max)
+        .label("Roll")
+        .label_color(color::RED)
+
+        .padded_w_of(ids.canvas, PAD)
+        .h(30.0)
+        .enabled(!edited_pinned)
+        .set(ids.roll, ui)
+        .into_iter()
+        .chain(reset_to_zero(ui, ids.roll).filter(|_| !edited_pinned))
+    {
+        demo.sliders.euler_angles[2] = demo.snapped_degrees(value).to_radians();
+        demo.quaternion_lists[demo.active_list].set_selected(demo.sliders.euler_rotation());
+    }
+}
 
-                animate_timer += 1.0 / 60.0;
-                if animate_timer >= 1.0 {
-                    animate_index += 1;
-                    animate_timer -= 1.0;
-                    if animate_index >= quaternion_list.len() {
-                        animating = false;
-                    }
-                }
-            } else {
-                for q in &quaternion_list {
-                    rotation *= *q;
-                }
-            }
+fn set_axis_angle_sliders(demo: &mut Demo, ui: &mut conrod::UiCell, ids: &mut Ids) {
+    let (angle_min, angle_max) = demo.angle_range.bounds();
+    // A pinned rotation can't be edited, so its sliders are greyed out.
+    let edited_pinned = demo.quaternion_lists[demo.active_list].selected_entry().pinned;
 
-            model.transform.rotation = rotation;
-            render_model(&model, &program, &camera, &mut target)?;
+    for value in widget::Toggle::new(demo.spherical_axis)
+        .label("Spherical Axis")
+        .set(ids.spherical_axis, ui)
+    {
+        demo.spherical_axis = value;
+    }
 
-            renderer.draw(&display, &mut target, &image_map)?;
-            target.finish()?;
+    if demo.spherical_axis {
+        for value in Slider::new(demo.sliders.axis_azimuth.to_degrees(), -180.0, 180.0)
+            .label("Axis Azimuth")
+            .label_color(color::RED)
+            .padded_w_of(ids.canvas, PAD)
+            .h(30.0)
+            .enabled(!edited_pinned)
+            .set(ids.axis_azimuth, ui)
+            .into_iter()
+            .chain(reset_to_zero(ui, ids.axis_azimuth).filter(|_| !edited_pinned))
+        {
+            let elevation = demo.sliders.axis_elevation;
+            demo.sliders.set_axis_spherical(value.to_radians(), elevation);
+            demo.quaternion_lists[demo.active_list].set_selected(demo.sliders.axis_angle_rotation());
         }
 
-        std::thread::sleep(std::time::Duration::from_millis(16));
-    }
+        for value in Slider::new(demo.sliders.axis_elevation.to_degrees(), -90.0, 90.0)
+            .label("Axis Elevation")
+            .label_color(color::RED)
+            .padded_w_of(ids.canvas, PAD)
+            .h(30.0)
+            .enabled(!edited_pinned)
+            .set(ids.axis_elevation, ui)
+            .into_iter()
+            .chain(reset_to_zero(ui, ids.axis_elevation).filter(|_| !edited_pinned))
+        {
+            let azimuth = demo.sliders.axis_azimuth;
+            demo.sliders.set_axis_spherical(azimuth, value.to_radians());
+            demo.quaternion_lists[demo.active_list].set_selected(demo.sliders.axis_angle_rotation());
+        }
+    } else {
+        for value in Slider::new(demo.sliders.axis[0], -1.0, 1.0)
+            .label("Axis X")
+            .label_color(color::RED)
+
+            .padded_w_of(ids.canvas, PAD)
+            .h(30.0)
+            .enabled(!edited_pinned)
+            .set(ids.axis_x, ui)
+            .into_iter()
+            .chain(reset_to_zero(ui, ids.axis_x).filter(|_| !edited_pinned))
+        {
+            demo.sliders.set_axis_component(0, value);
+            demo.quaternion_lists[demo.active_list].set_selected(demo.sliders.axis_angle_rotation());
+        }
 
-    Ok(())
-}
+        for value in Slider::new(demo.sliders.axis[1], -1.0, 1.0)
+            .label("Axis Y")
+            .label_color(color::RED)
 
-fn create_shader_program(display: &glium::Display) -> Result<Program, Box<Error>> {
-    use std::fs::File;
-    use std::io::Read;
+            .padded_w_of(ids.canvas, PAD)
+            .h(30.0)
+            .enabled(!edited_pinned)
+            .set(ids.axis_y, ui)
+            .into_iter()
+            .chain(reset_to_zero(ui, ids.axis_y).filter(|_| !edited_pinned))
+        {
+            demo.sliders.set_axis_component(1, value);
+            demo.quaternion_lists[demo.active_list].set_selected(demo.sliders.axis_angle_rotation());
+        }
 
-    let vertex_src = {
-        let mut file = File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/vertex.glsl"))?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
-        contents
-    };
+        for value in Slider::new(demo.sliders.axis[2], -1.0, 1.0)
+            .label("Axis Z")
+            .label_color(color::RED)
 
-    let fragment_src = {
-        let mut file = File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/fragment.glsl"))?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
-        contents
-    };
+            .padded_w_of(ids.canvas, PAD)
+            .h(30.0)
+            .enabled(!edited_pinned)
+            .set(ids.axis_z, ui)
+            .into_iter()
+            .chain(reset_to_zero(ui, ids.axis_z).filter(|_| !edited_pinned))
+        {
+            demo.sliders.set_axis_component(2, value);
+            demo.quaternion_lists[demo.active_list].set_selected(demo.sliders.axis_angle_rotation());
+        }
+    }
 
-    let program = Program::from_source(display, &vertex_src, &fragment_src, None)?;
-    Ok(program)
+    for value in Slider::new(demo.angle_range.fit(demo.sliders.axis_angle.to_degrees()), angle_min, angle_max)
+        .label("Angle")
+        .label_color(color::RED)
+
+        .padded_w_of(ids.canvas, PAD)
+        .h(30.0)
+        .enabled(!edited_pinned)
+        .set(ids.axis_angle, ui)
+        .into_iter()
+        .chain(reset_to_zero(ui, ids.axis_angle).filter(|_| !edited_pinned))
+    {
+        demo.sliders.axis_angle = demo.snapped_degrees(value).to_radians();
+        demo.quaternion_lists[demo.active_list].set_selected(demo.sliders.axis_angle_rotation());
+    }
 }
 
-fn create_axes_model(display: &glium::Display) -> Result<Model, Box<Error>> {
-    const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
-    const GREEN: [f32; 4] = [0.0, 1.0, 0.0, 1.0];
-    const BLUE: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
-    const GRAY: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+fn set_readouts(demo: &mut Demo, ui: &mut conrod::UiCell, ids: &mut Ids, shown_rotation: Quaternion) {
+    let label_color = ui.theme().label_color;
+    let edited_pinned = demo.quaternion_lists[demo.active_list].selected_entry().pinned;
+
+    widget::Text::new(&export::format_axis_angle(shown_rotation, demo.precision))
+        .color(label_color)
+        .font_size(14)
+        .padded_w_of(ids.canvas, PAD)
+        .set(ids.axis_angle_readout, ui);
+    widget::Text::new(&export::format_look_direction(shown_rotation, demo.precision))
+        .color(label_color)
+        .font_size(14)
+        .padded_w_of(ids.canvas, PAD)
+        .set(ids.look_direction_readout, ui);
+    widget::Text::new(&export::format_reference_angle(shown_rotation, demo.reference_orientation, demo.precision))
+        .color(label_color)
+        .font_size(14)
+        .padded_w_of(ids.canvas, PAD)
+        .set(ids.reference_angle_readout, ui);
+
+    if widget::Button::new()
+        .label("Set Reference")
+        .set(ids.set_reference, ui)
+        .was_clicked()
+    {
+        demo.reference_orientation = Some(shown_rotation);
+    }
 
-    const RIGHT: [f32; 3] = [1.0, 0.0, 0.0];
-    const LEFT: [f32; 3] = [-1.0, 0.0, 0.0];
-    const UP: [f32; 3] = [0.0, 1.0, 0.0];
-    const DOWN: [f32; 3] = [0.0, -1.0, 0.0];
-    const FRONT: [f32; 3] = [0.0, 0.0, 1.0];
-    const BACK: [f32; 3] = [0.0, 0.0, -1.0];
+    if demo.reference_orientation.is_some() && widget::Button::new()
+        .label("Clear Reference")
+        .set(ids.clear_reference, ui)
+        .was_clicked()
+    {
+        demo.reference_orientation = None;
+    }
 
-    let vertices = vec![
-        // -y
-        Vertex { position: [-0.5, -0.5, -0.5], color: GRAY, normal: DOWN },
-        Vertex { position: [-0.5, -0.5, 0.5], color: GRAY, normal: DOWN },
-        Vertex { position: [0.5, -0.5, 0.5], color: GRAY, normal: DOWN },
-        Vertex { position: [0.5, -0.5, -0.5], color: GRAY, normal: DOWN },
+    for value in widget::Toggle::new(demo.aim_at_target)
+        .label("Aim at a Target")
+        .set(ids.aim_at_target, ui)
+    {
+        demo.aim_at_target = value;
+    }
 
-        // +y
-        Vertex { position: [-0.5, 0.5, -0.5], color: GRAY, normal: UP },
-        Vertex { position: [-0.5, 0.5, 0.5], color: GRAY, normal: UP },
-        Vertex { position: [0.5, 0.5, 0.5], color: GRAY, normal: UP },
-        Vertex { position: [0.5, 0.5, -0.5], color: GRAY, normal: UP },
+    if demo.aim_at_target {
+        for value in Slider::new(demo.target_angles.0, -180.0, 180.0)
+            .label(&format!("Target Azimuth: {:.0}°", demo.target_angles.0))
+            .label_color(color::RED)
+            .padded_w_of(ids.canvas, PAD)
+            .h(30.0)
+            .set(ids.target_azimuth, ui)
+        {
+            demo.target_angles.0 = value;
+        }
 
-        // -z
-        Vertex { position: [-0.5, -0.5, -0.5], color: GRAY, normal: BACK },
-        Vertex { position: [-0.5, 0.5, -0.5], color: GRAY, normal: BACK },
-        Vertex { position: [0.5, 0.5, -0.5], color: GRAY, normal: BACK },
-        Vertex { position: [0.5, -0.5, -0.5], color: GRAY, normal: BACK },
+        for value in Slider::new(demo.target_angles.1, -90.0, 90.0)
+            .label(&format!("Target Elevation: {:.0}°", demo.target_angles.1))
+            .label_color(color::RED)
+            .padded_w_of(ids.canvas, PAD)
+            .h(30.0)
+            .set(ids.target_elevation, ui)
+        {
+            demo.target_angles.1 = value;
+        }
 
-        // +z
-        Vertex { position: [-0.5, -0.5, 0.5], color: GRAY, normal: FRONT },
-        Vertex { position: [-0.5, 0.5, 0.5], color: GRAY, normal: FRONT },
-        Vertex { position: [0.5, 0.5, 0.5], color: GRAY, normal: FRONT },
-        Vertex { position: [0.5, -0.5, 0.5], color: GRAY, normal: FRONT },
+        let target = shapes::point_on_sphere(demo.target_angles.0.to_radians(), demo.target_angles.1.to_radians());
+        widget::Text::new(&export::format_target_angle(shown_rotation, target, demo.precision))
+            .color(label_color)
+            .font_size(14)
+            .padded_w_of(ids.canvas, PAD)
+            .set(ids.target_angle_readout, ui);
+
+        // Turns the edited rotation just enough for the whole list to point the model
+        // at the target.
+        if widget::Button::new()
+            .label("Aim")
+            .set(ids.aim, ui)
+            .was_clicked()
+            && !edited_pinned
+        {
+            let list = &mut demo.quaternion_lists[demo.active_list];
+            let aimed = animation::aimed_rotation(&list.rotations(), list.selected(), export::FORWARD.into(), target);
+            list.set_selected(aimed);
+            demo.sliders = RotationSliders::from_rotation(list.selected_rotation());
+        }
+    }
+}
 
-        // -x
-        Vertex { position: [-0.5, -0.5, -0.5], color: GRAY, normal: LEFT },
-        Vertex { position: [-0.5, -0.5, 0.5], color: GRAY, normal: LEFT },
-        Vertex { position: [-0.5, 0.5, 0.5], color: GRAY, normal: LEFT },
-        Vertex { position: [-0.5, 0.5, -0.5], color: GRAY, normal: LEFT },
-        
-        // +x
-        Vertex { position: [0.5, -0.5, -0.5], color: GRAY, normal: RIGHT },
-        Vertex { position: [0.5, -0.5, 0.5], color: GRAY, normal: RIGHT },
-        Vertex { position: [0.5, 0.5, 0.5], color: GRAY, normal: RIGHT },
-        Vertex { position: [0.5, 0.5, -0.5], color: GRAY, normal: RIGHT },
-    ];
-    let indices = vec![
-        // y
-        0, 1, 2, 0, 2, 3,
-        4, 5, 6, 4, 6, 7,
+fn set_list_widgets(demo: &mut Demo, ui: &mut conrod::UiCell, ids: &mut Ids) {
+    let edited_pinned = demo.quaternion_lists[demo.active_list].selected_entry().pinned;
 
-        // z
-        8, 9, 10, 8, 10, 11,
-        12, 13, 14, 12, 14, 15,
+    if widget::Button::new()
+        .label("Add Rotation")
+        .set(ids.add_rotation, ui)
+        .was_clicked()
+    {
+        demo.actions.push(Action::AddRotation);
+    }
 
-        // x
-        16, 17, 18, 16, 18, 19,
-        20, 21, 22, 20, 22, 23,
-    ];
-    let model = Model {
-        vertex_buffer: VertexBuffer::new(display, &vertices)?,
-        index_buffer: IndexBuffer::new(display, glium::index::PrimitiveType::TrianglesList, &indices)?,
-        transform: Transform::new(),
-    };
+    // Puts a new rotation in the list just before the edited one, which is applied
+    // before it and becomes the one edited. As an identity the new rotation adds no
+    // segment to the animation, so a playing animation carries on where it was.
+    if widget::Button::new()
+        .label("Insert Rotation Before Edited")
+        .set(ids.insert_rotation, ui)
+        .was_clicked()
+    {
+        demo.quaternion_lists[demo.active_list].insert_before_selected();
+        demo.sliders = RotationSliders::from_rotation(Quaternion::identity());
+    }
+
+    if widget::Button::new()
+        .label("Clear Rotations")
+        .set(ids.clear_rotations, ui)
+        .was_clicked()
+    {
+        demo.actions.push(Action::ClearRotations);
+    }
+
+    // Lists from before rotations were aligned as they're edited, or from the command
+    // line or a file, can have entries on opposite sides of the 3-sphere.
+    if widget::Button::new()
+        .label("Re-align Signs")
+        .set(ids.align_signs, ui)
+        .was_clicked()
+    {
+        demo.quaternion_lists[demo.active_list].align_signs();
+    }
+
+    // Bakes the list into the one rotation that does all of it. The animation is
+    // stopped, as its segments are gone.
+    if widget::Button::new()
+        .label("Collapse Rotations")
+        .set(ids.collapse_rotations, ui)
+        .was_clicked()
+    {
+        match demo.quaternion_lists[demo.active_list].collapse() {
+            Ok(()) => {
+                demo.playback.stop();
+                demo.sliders = RotationSliders::from_rotation(demo.quaternion_lists[demo.active_list].selected_rotation());
+            }
+            Err(e) => demo.list_notice = Some(format!("Can't collapse the list: {}", e)),
+        }
+    }
+
+    if !demo.quaternion_lists[demo.active_list].any_pinned() {
+        demo.list_notice = None;
+    }
+    if let Some(ref notice) = demo.list_notice {
+        widget::Text::new(notice)
+            .color(color::ORANGE)
+            .font_size(12)
+            .set(ids.list_notice, ui);
+    }
+
+    // Halving and doubling the edited rotation keep its axis, so the readout shows its
+    // angle halving or doubling. Doubling stops at half a turn, as any further would
+    // go the short way round, the other way.
+    if widget::Button::new()
+        .label("Halve Rotation")
+        .set(ids.halve_rotation, ui)
+        .was_clicked()
+        && !edited_pinned
+    {
+        let halved = demo.quaternion_lists[demo.active_list].selected_rotation().scaled(0.5);
+        demo.sliders = RotationSliders::from_rotation(halved);
+        demo.quaternion_lists[demo.active_list].set_selected(halved);
+    }
+
+    if widget::Button::new()
+        .label("Double Rotation")
+        .set(ids.double_rotation, ui)
+        .was_clicked()
+        && !edited_pinned
+    {
+        let rotation = demo.quaternion_lists[demo.active_list].selected_rotation();
+        let angle = Quaternion::identity().angle_between(rotation);
+        let doubled = rotation.pow(if angle > 0.0 { (std::f32::consts::PI / angle).min(2.0) } else { 2.0 });
+        demo.sliders = RotationSliders::from_rotation(doubled);
+        demo.quaternion_lists[demo.active_list].set_selected(doubled);
+    }
+
+    if widget::Button::new()
+        .label("Animate Rotations")
+        .set(ids.animate_rotations, ui)
+        .was_clicked()
+    {
+        demo.playback.start(animated_segments(&demo.quaternion_lists, demo.comparing));
+        demo.trail_points.clear();
+        demo.angle_history.clear();
+    }
+}
+
+fn set_rotation_rows(demo: &mut Demo, ui: &mut conrod::UiCell, ids: &mut Ids) {
+    let label_color = ui.theme().label_color;
+    widget::Text::new("Rotations")
+        .color(label_color)
+        .font_size(14)
+        .set(ids.rotation_list_label, ui);
+    let num_rotations = demo.quaternion_lists[demo.active_list].len();
+    ids.rotation_list.resize(num_rotations, &mut ui.widget_id_generator());
+    ids.reset_rotation.resize(num_rotations, &mut ui.widget_id_generator());
+    ids.edit_rotation.resize(num_rotations, &mut ui.widget_id_generator());
+    ids.pin_rotation.resize(num_rotations, &mut ui.widget_id_generator());
+    ids.lock_body.resize(num_rotations, &mut ui.widget_id_generator());
+    ids.lock_shackle.resize(num_rotations, &mut ui.widget_id_generator());
+    let mut reset_index = None;
+    let mut pin_toggled = None;
+    let mut edit_index = None;
+    let selected = demo.quaternion_lists[demo.active_list].selected();
+    for (i, entry) in demo.quaternion_lists[demo.active_list].entries().iter().enumerate() {
+        let shown = entry.rotation.to_quaternion();
+        let [r, g, b, a] = rotation_color(entry.color_id);
+        let label = format!("{}: {:.*}, {:.*}, {:.*}, {:.*}", i, demo.precision, shown.w, demo.precision, shown.x, demo.precision, shown.y, demo.precision, shown.z);
+        let text = widget::Text::new(&label)
+            .color(color::rgba(r, g, b, a))
+            .font_size(12);
+        // Each row goes below the one before, rather than below its reset button.
+        let text = if i == 0 {
+            text
+        } else {
+            text.down_from(ids.rotation_list[i - 1], 12.0).align_left_of(ids.rotation_list_label)
+        };
+        text.set(ids.rotation_list[i], ui);
+
+        if widget::Button::new()
+            .label("Edit")
+            .label_font_size(12)
+            .label_color(if selected == i { color::RED } else { label_color })
+            .w_h(50.0, 20.0)
+            .right_from(ids.rotation_list[i], 10.0)
+            .set(ids.edit_rotation[i], ui)
+            .was_clicked()
+        {
+            edit_index = Some(i);
+        }
+
+        let is_pinned = entry.pinned;
+        if widget::Button::new()
+            .label(if is_pinned { "Unpin" } else { "Pin" })
+            .label_font_size(12)
+            .w_h(50.0, 20.0)
+            .right_from(ids.edit_rotation[i], 5.0)
+            .set(ids.pin_rotation[i], ui)
+            .was_clicked()
+        {
+            pin_toggled = Some(i);
+        }
+
+        // A pinned rotation can't be reset, so it shows a padlock in place of the button.
+        if is_pinned {
+            widget::Rectangle::fill_with([12.0, 9.0], label_color)
+                .right_from(ids.pin_rotation[i], 14.0)
+                .align_bottom_of(ids.pin_rotation[i])
+                .set(ids.lock_body[i], ui);
+            widget::Rectangle::outline_styled([8.0, 10.0], widget::line::Style::new().thickness(2.0).color(label_color))
+                .mid_top_with_margin_on(ids.lock_body[i], -8.0)
+                .set(ids.lock_shackle[i], ui);
+        } else if widget::Button::new()
+            .label("Reset")
+            .label_font_size(12)
+            .w_h(50.0, 20.0)
+            .right_from(ids.pin_rotation[i], 5.0)
+            .set(ids.reset_rotation[i], ui)
+            .was_clicked()
+        {
+            reset_index = Some(i);
+        }
+    }
+    if let Some(i) = edit_index {
+        demo.quaternion_lists[demo.active_list].select(i);
+        demo.sliders = RotationSliders::from_rotation(demo.quaternion_lists[demo.active_list].selected_rotation());
+    }
+    if let Some(i) = pin_toggled {
+        demo.quaternion_lists[demo.active_list].toggle_pin(i);
+    }
+    if let Some(i) = reset_index {
+        demo.quaternion_lists[demo.active_list].reset(i);
+        if i == selected {
+            demo.sliders = RotationSliders::from_rotation(Quaternion::identity());
+        }
+    }
+
+    let last_row = ids.rotation_list.last().cloned().unwrap_or(ids.rotation_list_label);
+    for value in widget::Toggle::new(demo.show_rotation_axes)
+        .label("Show Each Rotation's Axis")
+        .down_from(last_row, 20.0)
+        .align_left_of(ids.rotation_list_label)
+        .set(ids.show_rotation_axes, ui)
+    {
+        demo.show_rotation_axes = value;
+    }
+
+    // Negative dot products are where the slerp negates the next keyframe to take
+    // the short way round, so a big rotation animates backwards.
+    let dots = animation::keyframe_dots(&demo.quaternion_lists[demo.active_list].rotations());
+    if !dots.is_empty() {
+        widget::Text::new("Keyframe Dot Products")
+            .color(label_color)
+            .font_size(14)
+            .set(ids.keyframe_dots_label, ui);
+        ids.keyframe_dots.resize(dots.len(), &mut ui.widget_id_generator());
+        for (i, &dot) in dots.iter().enumerate() {
+            let (text, text_color) = if dot < 0.0 {
+                (format!("{} to {}: {:.*} (takes the short way)", i, i + 1, demo.precision, dot), color::ORANGE)
+            } else {
+                (format!("{} to {}: {:.*}", i, i + 1, demo.precision, dot), label_color)
+            };
+            widget::Text::new(&text)
+                .color(text_color)
+                .font_size(12)
+                .set(ids.keyframe_dots[i], ui);
+        }
+    }
+}
+
+fn set_export_widgets(demo: &mut Demo, ui: &mut conrod::UiCell, ids: &mut Ids, shown_rotation: Quaternion) {
+    if widget::Button::new()
+        .label("Export Matrix")
+        .set(ids.export_matrix, ui)
+        .was_clicked()
+    {
+        const MATRIX_PATH: &str = "rotation_matrix.csv";
+        let mut transform = demo.model.transform;
+        transform.rotation = shown_rotation;
+        let csv = export::format_matrix_csv(&transform.to_matrix());
+        match export::write_file(MATRIX_PATH, &csv) {
+            Ok(()) => info!("Wrote matrix to {}", MATRIX_PATH),
+            Err(e) => warn!("failed to write {}: {}", MATRIX_PATH, e),
+        }
+    }
+
+    if widget::Button::new()
+        .label("Import Angles")
+        .set(ids.import_angles, ui)
+        .was_clicked()
+    {
+        const ANGLES_PATH: &str = "euler_angles.csv";
+        let rotations = import::read_file(ANGLES_PATH)
+            .map_err(|e| e.to_string())
+            .and_then(|csv| import::parse_euler_csv(&csv));
+        match rotations {
+            Ok(rotations) => {
+                let count = rotations.len();
+                match demo.quaternion_lists[demo.active_list].replace_all(rotations.into_iter().map(StoredRotation::from).collect()) {
+                    Ok(()) => {
+                        info!("Imported {} rotations from {}", count, ANGLES_PATH);
+                        demo.sliders = RotationSliders::from_rotation(demo.quaternion_lists[demo.active_list].selected_rotation());
+                        demo.playback.stop();
+                    }
+                    Err(e) => demo.list_notice = Some(format!("Can't import {}: {}", ANGLES_PATH, e)),
+                }
+            }
+            Err(e) => warn!("failed to import {}: {}", ANGLES_PATH, e),
+        }
+    }
+
+    if widget::Button::new()
+        .label("Export Animation")
+        .set(ids.export_animation, ui)
+        .was_clicked()
+    {
+        const ANIMATION_PATH: &str = "animation.json";
+        const EXPORT_FPS: f32 = 30.0;
+        let samples = animation::sample(&demo.quaternion_lists[demo.active_list].rotations(), EXPORT_FPS);
+        let json = export::format_keyframes_json(EXPORT_FPS, &samples);
+        match export::write_file(ANIMATION_PATH, &json) {
+            Ok(()) => info!("Wrote {} keyframes to {}", samples.len(), ANIMATION_PATH),
+            Err(e) => warn!("failed to write {}: {}", ANIMATION_PATH, e),
+        }
+    }
+
+    if widget::Button::new()
+        .label("Export Slerp Path")
+        .set(ids.export_slerp_path, ui)
+        .was_clicked()
+    {
+        // The path between the list's first two rotations, to analyze the interpolation.
+        const SLERP_PATH: &str = "slerp_path.csv";
+        const SLERP_SAMPLES: usize = 101;
+        match demo.quaternion_lists[demo.active_list].rotations().get(..2) {
+            Some(&[start, end]) => {
+                let (start, end) = (start.to_quaternion(), end.to_quaternion());
+                let samples = animation::sample_slerp(start, end, SLERP_SAMPLES);
+                match export::write_file(SLERP_PATH, &export::format_slerp_csv(start, &samples)) {
+                    Ok(()) => info!("Wrote {} samples to {}", samples.len(), SLERP_PATH),
+                    Err(e) => warn!("failed to write {}: {}", SLERP_PATH, e),
+                }
+            }
+            _ => warn!("failed to write {}: the list needs at least two rotations", SLERP_PATH),
+        }
+    }
+}
+
+fn set_print_widgets(demo: &mut Demo, ui: &mut conrod::UiCell, ids: &mut Ids, shown_rotation: Quaternion) {
+    if widget::Button::new()
+        .label("Print Rust Snippet")
+        .set(ids.print_rust_snippet, ui)
+        .was_clicked()
+    {
+        println!("{}", export::format_rust_snippet(shown_rotation));
+    }
+
+    if widget::Button::new()
+        .label("Print NumPy Array")
+        .set(ids.print_numpy_array, ui)
+        .was_clicked()
+    {
+        println!("{}", export::format_numpy_array(&shown_rotation.into_matrix()));
+    }
+
+    if widget::Button::new()
+        .label("Print GLSL mat4")
+        .set(ids.print_glsl_mat4, ui)
+        .was_clicked()
+    {
+        println!("{}", export::format_glsl_mat4(&shown_rotation.into_matrix()));
+    }
+
+    if widget::Button::new()
+        .label("Print URDF rpy")
+        .set(ids.print_urdf_rpy, ui)
+        .was_clicked()
+    {
+        println!("{}", export::format_urdf_rpy(shown_rotation));
+    }
+
+    if widget::Button::new()
+        .label("Print glTF Rotation")
+        .set(ids.print_gltf_rotation, ui)
+        .was_clicked()
+    {
+        println!("{}", export::format_gltf_rotation(shown_rotation));
+    }
+
+    if widget::Button::new()
+        .label("Print 6D Rotation")
+        .set(ids.print_rotation_6d, ui)
+        .was_clicked()
+    {
+        println!("{}", export::format_rotation_6d(shown_rotation));
+    }
+
+    for value in Slider::new(demo.precision as f32, 0.0, export::MAX_PRECISION as f32)
+        .label(&format!("Decimal Places: {}", demo.precision))
+        .label_color(color::RED)
+        .padded_w_of(ids.canvas, PAD)
+        .h(30.0)
+        .set(ids.precision, ui)
+    {
+        demo.precision = value.round() as usize;
+    }
+}
+
+fn set_camera_widgets(demo: &mut Demo, ui: &mut conrod::UiCell, ids: &mut Ids, display: &glium::Display) {
+    if widget::Button::new()
+        .label("Reset Camera")
+        .set(ids.reset_camera, ui)
+        .was_clicked()
+    {
+        demo.actions.push(Action::ResetCamera);
+    }
+
+    if widget::Button::new()
+        .label("Frame Model")
+        .set(ids.frame_model, ui)
+        .was_clicked()
+    {
+        if let Err(e) = frame_model(display, &mut demo.model, &mut demo.camera) {
+            warn!("failed to frame the model: {}", e);
+        }
+    }
+
+    if widget::Button::new()
+        .label("Save View")
+        .set(ids.save_view, ui)
+        .was_clicked()
+    {
+        match demo.camera.state().save(camera::VIEW_PATH) {
+            Ok(()) => info!("Saved the view to {}", camera::VIEW_PATH),
+            Err(e) => warn!("failed to write {}: {}", camera::VIEW_PATH, e),
+        }
+    }
+
+    if widget::Button::new()
+        .label("Load View")
+        .set(ids.load_view, ui)
+        .was_clicked()
+    {
+        match camera::CameraState::load(camera::VIEW_PATH) {
+            Ok(state) => {
+                demo.camera.set_state(&state);
+                info!("Loaded the view from {}", camera::VIEW_PATH);
+            }
+            Err(e) => warn!("failed to load {}: {}", camera::VIEW_PATH, e),
+        }
+    }
+
+    if widget::Button::new()
+        .label("Front")
+        .w((demo.panel_width - 4.0 * PAD) / 3.0)
+        .set(ids.front_view, ui)
+        .was_clicked()
+    {
+        demo.camera.look_from([0.0, 0.0, 1.0].into(), [0.0, 1.0, 0.0].into());
+    }
+
+    if widget::Button::new()
+        .label("Top")
+        .right(PAD)
+        .set(ids.top_view, ui)
+        .was_clicked()
+    {
+        demo.camera.look_from([0.0, 1.0, 0.0].into(), [0.0, 0.0, -1.0].into());
+    }
+
+    if widget::Button::new()
+        .label("Right")
+        .right(PAD)
+        .set(ids.right_view, ui)
+        .was_clicked()
+    {
+        demo.camera.look_from([1.0, 0.0, 0.0].into(), [0.0, 1.0, 0.0].into());
+    }
+
+    for value in Slider::new(demo.camera.fov().to_degrees(), 20.0, 100.0)
+        .label("FOV")
+        .label_color(color::RED)
+        .down_from(ids.front_view, 20.0)
+        .align_left_of(ids.front_view)
+        .padded_w_of(ids.canvas, PAD)
+        .h(30.0)
+        .set(ids.fov, ui)
+    {
+        demo.camera.set_fov(value.to_radians());
+    }
+
+    let (angle_min, angle_max) = demo.angle_range.bounds();
+    for value in Slider::new(demo.angle_range.fit(demo.camera.roll().to_degrees()), angle_min, angle_max)
+        .label("Camera Roll")
+        .label_color(color::RED)
+        .padded_w_of(ids.canvas, PAD)
+        .h(30.0)
+        .set(ids.camera_roll, ui)
+        .into_iter()
+        .chain(reset_to_zero(ui, ids.camera_roll))
+    {
+        demo.camera.set_roll(value.to_radians());
+    }
+
+    for value in Slider::new(demo.camera.near(), 0.01, 10.0)
+        .label("Near")
+        .label_color(color::RED)
+        .padded_w_of(ids.canvas, PAD)
+        .h(30.0)
+        .set(ids.near_plane, ui)
+    {
+        let far = demo.camera.far();
+        demo.camera.set_clip_planes(value, far);
+    }
+
+    for value in Slider::new(demo.camera.far(), 10.0, 1000.0)
+        .label("Far")
+        .label_color(color::RED)
+        .padded_w_of(ids.canvas, PAD)
+        .h(30.0)
+        .set(ids.far_plane, ui)
+    {
+        let near = demo.camera.near();
+        demo.camera.set_clip_planes(near, value);
+    }
+
+    for value in widget::Toggle::new(demo.camera.orthographic())
+        .label("Orthographic")
+        .set(ids.orthographic, ui)
+    {
+        demo.camera.set_orthographic(value);
+    }
+
+    for value in widget::Toggle::new(demo.config.invert_orbit_y)
+        .label("Invert Vertical Orbit")
+        .set(ids.invert_orbit_y, ui)
+    {
+        demo.config.invert_orbit_y = value;
+        save_config(&demo.config);
+    }
+
+    for value in Slider::new(demo.camera.transition_duration, 0.0, 2.0)
+        .label("View Transition Time")
+        .label_color(color::RED)
+        .padded_w_of(ids.canvas, PAD)
+        .h(30.0)
+        .set(ids.transition_duration, ui)
+    {
+        demo.camera.transition_duration = value;
+    }
+}
+
+fn set_display_widgets(demo: &mut Demo, ui: &mut conrod::UiCell, ids: &mut Ids) {
+    for value in widget::Toggle::new(demo.show_ghost)
+        .label("Show Original Orientation")
+        .set(ids.show_ghost, ui)
+    {
+        demo.show_ghost = value;
+    }
+
+    for value in widget::Toggle::new(demo.rotate_camera)
+        .label("Rotate Camera Instead")
+        .set(ids.rotate_camera, ui)
+    {
+        demo.rotate_camera = value;
+    }
+
+    for value in widget::Toggle::new(demo.show_outline)
+        .label("Outline Model")
+        .set(ids.show_outline, ui)
+    {
+        demo.show_outline = value;
+    }
+
+    for value in widget::Toggle::new(demo.highlight_edited)
+        .label("Highlight Edited Rotation")
+        .set(ids.highlight_edited, ui)
+    {
+        demo.highlight_edited = value;
+    }
+
+    for value in widget::Toggle::new(demo.show_slerp_path)
+        .label("Show Slerp Path")
+        .set(ids.show_slerp_path, ui)
+    {
+        demo.show_slerp_path = value;
+    }
+
+    for value in widget::Toggle::new(demo.show_sphere_point)
+        .label("Point on Sphere")
+        .set(ids.show_sphere_point, ui)
+    {
+        demo.show_sphere_point = value;
+        demo.sphere_point_points.clear();
+    }
+
+    if demo.show_sphere_point {
+        for value in Slider::new(demo.sphere_point_angles.0, -180.0, 180.0)
+            .label(&format!("Point Azimuth: {:.0}°", demo.sphere_point_angles.0))
+            .label_color(color::RED)
+            .padded_w_of(ids.canvas, PAD)
+            .h(30.0)
+            .set(ids.sphere_point_azimuth, ui)
+        {
+            demo.sphere_point_angles.0 = value;
+            demo.sphere_point_points.clear();
+        }
+
+        for value in Slider::new(demo.sphere_point_angles.1, -90.0, 90.0)
+            .label(&format!("Point Elevation: {:.0}°", demo.sphere_point_angles.1))
+            .label_color(color::RED)
+            .padded_w_of(ids.canvas, PAD)
+            .h(30.0)
+            .set(ids.sphere_point_elevation, ui)
+        {
+            demo.sphere_point_angles.1 = value;
+            demo.sphere_point_points.clear();
+        }
+    }
+
+    for value in widget::Toggle::new(demo.plot_angle)
+        .label("Plot Angle Over Time")
+        .set(ids.plot_angle, ui)
+    {
+        demo.plot_angle = value;
+    }
+
+    for value in widget::Toggle::new(demo.show_normals)
+        .label("Show Normals")
+        .set(ids.show_normals, ui)
+    {
+        demo.show_normals = value;
+    }
+
+    for value in widget::Toggle::new(demo.ui_theme == theme::Theme::Light)
+        .label("Light Theme")
+        .set(ids.light_theme, ui)
+    {
+        demo.ui_theme = if value { theme::Theme::Light } else { theme::Theme::Dark };
+    }
+
+    let background_sliders = [
+        ("Background Red", ids.background_red),
+        ("Background Green", ids.background_green),
+        ("Background Blue", ids.background_blue),
+    ];
+    for (i, &(label, id)) in background_sliders.iter().enumerate() {
+        for value in Slider::new(demo.background_color[i], 0.0, 1.0)
+            .label(label)
+            .label_color(color::RED)
+            .padded_w_of(ids.canvas, PAD)
+            .h(30.0)
+            .set(id, ui)
+        {
+            demo.background_color[i] = value;
+        }
+    }
+}
+
+fn set_lighting_widgets(demo: &mut Demo, ui: &mut conrod::UiCell, ids: &mut Ids) {
+    const LIGHT_RANGE: f32 = 5.0;
+    let light_sliders = [("Light X", ids.light_x), ("Light Y", ids.light_y), ("Light Z", ids.light_z)];
+    for (i, &(label, id)) in light_sliders.iter().enumerate() {
+        for value in Slider::new(demo.light.position[i], -LIGHT_RANGE, LIGHT_RANGE)
+            .label(label)
+            .label_color(color::RED)
+            .padded_w_of(ids.canvas, PAD)
+            .h(30.0)
+            .set(id, ui)
+        {
+            demo.light.position[i] = value;
+        }
+    }
+
+    for value in Slider::new(demo.light.ambient, 0.0, 1.0)
+        .label("Ambient")
+        .label_color(color::RED)
+        .padded_w_of(ids.canvas, PAD)
+        .h(30.0)
+        .set(ids.ambient, ui)
+    {
+        demo.light.ambient = value;
+    }
+
+    for value in Slider::new(demo.light.diffuse, 0.0, 2.0)
+        .label("Diffuse")
+        .label_color(color::RED)
+        .padded_w_of(ids.canvas, PAD)
+        .h(30.0)
+        .set(ids.diffuse, ui)
+    {
+        demo.light.diffuse = value;
+    }
+
+    for value in Slider::new(demo.light.specular, 0.0, 1.0)
+        .label("Specular")
+        .label_color(color::RED)
+        .padded_w_of(ids.canvas, PAD)
+        .h(30.0)
+        .set(ids.specular, ui)
+    {
+        demo.light.specular = value;
+    }
+
+    for value in Slider::new(demo.light.shininess, 1.0, 128.0)
+        .label("Shininess")
+        .label_color(color::RED)
+        .padded_w_of(ids.canvas, PAD)
+        .h(30.0)
+        .set(ids.shininess, ui)
+    {
+        demo.light.shininess = value;
+    }
+
+    for value in widget::Toggle::new(demo.light.double_sided)
+        .label("Light Back Faces")
+        .set(ids.double_sided, ui)
+    {
+        demo.light.double_sided = value;
+    }
+
+    for value in widget::Toggle::new(demo.light.gamma_correct)
+        .label("Gamma Correct Lighting")
+        .set(ids.gamma_correct, ui)
+    {
+        demo.light.gamma_correct = value;
+    }
+}
+
+fn set_model_widgets(demo: &mut Demo, ui: &mut conrod::UiCell, ids: &mut Ids, display: &glium::Display) -> Result<(), DemoError> {
+    if let Some(index) = widget::DropDownList::new(&MESHES, demo.mesh)
+        .label("Model")
+        .padded_w_of(ids.canvas, PAD)
+        .h(30.0)
+        .set(ids.mesh, ui)
+    {
+        if demo.mesh != Some(index) {
+            if demo.mesh_cache[index].is_none() {
+                demo.mesh_cache[index] = Some(create_mesh_model(display, index)?.mesh);
+            }
+            demo.model.mesh = demo.mesh_cache[index].clone().unwrap();
+            demo.normals = None;
+            demo.mesh = Some(index);
+            // The generated meshes other than the cube come smooth shaded.
+            demo.smooth_shading = MESHES[index] != "Cube";
+        }
+    }
+
+    for value in widget::Toggle::new(demo.smooth_shading)
+        .label("Smooth Shading")
+        .set(ids.smooth_shading, ui)
+    {
+        match reshade_mesh(display, &demo.model.mesh, value) {
+            Ok(reshaded) => {
+                demo.model.mesh = Rc::new(reshaded);
+                demo.normals = None;
+                demo.smooth_shading = value;
+            }
+            Err(e) => warn!("failed to change the shading: {}", e),
+        }
+    }
+
+    for value in Slider::new(demo.model_opacity, 0.0, 1.0)
+        .label("Opacity")
+        .label_color(color::RED)
+        .padded_w_of(ids.canvas, PAD)
+        .h(30.0)
+        .set(ids.model_opacity, ui)
+    {
+        demo.model_opacity = value;
+    }
+
+    // A zero scale would collapse the model, so the sliders stop a little above it.
+    const MIN_SCALE: f32 = 0.1;
+    const MAX_SCALE: f32 = 3.0;
+    let scale_sliders = [("Scale X", ids.scale_x), ("Scale Y", ids.scale_y), ("Scale Z", ids.scale_z)];
+    for (i, &(label, id)) in scale_sliders.iter().enumerate() {
+        for value in Slider::new(demo.model.transform.scale[i], MIN_SCALE, MAX_SCALE)
+            .label(label)
+            .label_color(color::RED)
+            .padded_w_of(ids.canvas, PAD)
+            .h(30.0)
+            .set(id, ui)
+        {
+            demo.model.transform.scale[i] = value.max(MIN_SCALE);
+        }
+    }
+
+    // Keep the model close enough to the origin that the camera can still find it.
+    const POSITION_RANGE: f32 = 5.0;
+    let position_sliders = [("Position X", ids.position_x), ("Position Y", ids.position_y), ("Position Z", ids.position_z)];
+    for (i, &(label, id)) in position_sliders.iter().enumerate() {
+        for value in Slider::new(demo.model.transform.position[i], -POSITION_RANGE, POSITION_RANGE)
+            .label(label)
+            .label_color(color::RED)
+            .padded_w_of(ids.canvas, PAD)
+            .h(30.0)
+            .set(id, ui)
+        {
+            demo.model.transform.position[i] = value.max(-POSITION_RANGE).min(POSITION_RANGE);
+        }
+    }
+    Ok(())
+}
+
+// Does the actions asked for by the frame's shortcut keys and buttons.
+fn apply_actions(demo: &mut Demo) {
+    for action in demo.actions.drain(..) {
+        match action {
+            Action::AddRotation => {
+                demo.quaternion_lists[demo.active_list].push();
+                demo.sliders = RotationSliders::from_rotation(demo.quaternion_lists[demo.active_list].selected_rotation());
+            }
+            Action::ClearRotations => {
+                match demo.quaternion_lists[demo.active_list].replace_all(Vec::new()) {
+                    Ok(()) => demo.sliders = RotationSliders::from_rotation(demo.quaternion_lists[demo.active_list].selected_rotation()),
+                    Err(e) => demo.list_notice = Some(format!("Can't clear the list: {}", e)),
+                }
+            }
+            Action::ToggleAnimation => {
+                if demo.playback.playing {
+                    demo.playback.stop();
+                } else {
+                    demo.playback.start(animated_segments(&demo.quaternion_lists, demo.comparing));
+                    demo.trail_points.clear();
+                    demo.angle_history.clear();
+                }
+            }
+            Action::ResetCamera => demo.camera = Camera::new(),
+            Action::ToggleHelp => demo.show_help = !demo.show_help,
+            Action::StoreBookmark(slot) => demo.bookmarks[slot] = Some(demo.camera.state()),
+            Action::RecallBookmark(slot) => {
+                if let Some(state) = demo.bookmarks[slot] {
+                    demo.camera.set_state(&state);
+                }
+            }
+        }
+    }
+}
+
+// Draws the frame if anything changed since the last one, returning whether it did.
+fn draw_frame(demo: &mut Demo, ui: &mut conrod::Ui, display: &glium::Display, renderer: &mut conrod::backend::glium::Renderer, image_map: &conrod::image::Map<glium::texture::Texture2d>, dt: f32) -> Result<bool, DemoError> {
+    // The FPS readout and frame graph have to redraw to measure anything.
+    if demo.playback.playing || !demo.trail_points.is_empty() || demo.camera.transition.is_some() || demo.show_fps || demo.show_frame_graph
+        || demo.short_way_highlight.is_some() || !demo.sphere_point_points.is_empty()
+    {
+        demo.needs_redraw = true;
+    }
+    if !demo.needs_redraw {
+        return Ok(false);
+    }
+    demo.needs_redraw = false;
+    let primitives = ui.draw();
+    renderer.fill(display, primitives, image_map);
+    let mut target = display.draw();
+    target.clear_color_and_depth((demo.background_color[0], demo.background_color[1], demo.background_color[2], 1.0), 1.0);
+
+    // The projection follows the frame's size, less the panel, so it also catches up after a
+    // resize, after the camera is reset or the panel is collapsed.
+    let (width, height) = target.get_dimensions();
+    let hidpi_factor = f64::from(display.gl_window().hidpi_factor());
+    let panel_pixels = ((shown_panel_width(demo.panel_collapsed, demo.panel_width) * hidpi_factor) as u32).min(width.saturating_sub(1));
+    demo.camera.viewport_origin = (panel_pixels, 0);
+    demo.camera.set_viewport_size(width - panel_pixels, height);
+
+    // The model's rotation, as far as the animation has got.
+    let rotation = demo.playback.current(&demo.quaternion_lists[0].rotations());
+    let second_rotation = if demo.compare_interpolation {
+        Some(demo.playback.current_with(&demo.quaternion_lists[0].rotations(), animation::Interpolation::Nlerp))
+    } else if demo.comparing {
+        Some(demo.playback.current(&demo.quaternion_lists[1].rotations()))
+    } else {
+        None
+    };
+    let sphere_point_position = demo.model.transform.position + rotation.rotate_vector(
+        shapes::point_on_sphere(demo.sphere_point_angles.0.to_radians(), demo.sphere_point_angles.1.to_radians()));
+
+    advance_animation(demo, rotation, sphere_point_position, dt);
+    demo.camera.update(dt);
+    render_models(demo, display, &mut target, rotation, second_rotation)?;
+    render_guides(demo, display, &mut target, rotation, sphere_point_position)?;
+    render_nav_cube(demo, display, &mut target)?;
+
+    renderer.draw(display, &mut target, image_map)?;
+    target.finish()?;
+    Ok(true)
+}
+
+// Moves the animation on by a frame, along with the trails and highlights that follow it.
+fn advance_animation(demo: &mut Demo, rotation: Quaternion, sphere_point_position: Vector3<f32>, dt: f32) {
+    if demo.playback.playing {
+        if demo.trail_points.len() == TRAIL_VERTICES {
+            demo.trail_points.pop_front();
+        }
+        let corner = Vector3::from(TRAIL_CORNER).component_mul(&demo.model.transform.scale);
+        demo.trail_points.push_back(demo.model.transform.position + rotation.rotate_vector(corner));
+        if demo.show_sphere_point {
+            if demo.sphere_point_points.len() == TRAIL_VERTICES {
+                demo.sphere_point_points.pop_front();
+            }
+            demo.sphere_point_points.push_back(sphere_point_position);
+        }
+        // Every animation starts from the identity, before its first rotation.
+        demo.angle_history.add(demo.playback.elapsed(), Quaternion::identity().angle_between(rotation));
+
+        demo.playback.advance(dt, animated_segments(&demo.quaternion_lists, demo.comparing));
+    } else {
+        // Once the animation is over, the trail retracts from its oldest end.
+        demo.trail_points.pop_front();
+        demo.sphere_point_points.pop_front();
+    }
+
+    const SHORT_WAY_HIGHLIGHT_DURATION: f32 = 1.0;
+    demo.short_way_highlight = demo.short_way_highlight.map(|(time, index)| (time - dt, index)).filter(|&(time, _)| time > 0.0);
+    let segment = if demo.playback.playing { Some(demo.playback.index) } else { None };
+    if segment != demo.animated_segment {
+        demo.animated_segment = segment;
+        let lists = if demo.comparing { &demo.quaternion_lists[..] } else { &demo.quaternion_lists[..1] };
+        if let Some(index) = segment.filter(|_| lists.iter().any(|list| demo.playback.negates_endpoint(&list.rotations()))) {
+            demo.short_way_highlight = Some((SHORT_WAY_HIGHLIGHT_DURATION, index));
+        }
+    }
+}
+
+// The grid, the world axes and the model, with its copies and its own axes.
+fn render_models(demo: &mut Demo, display: &glium::Display, target: &mut glium::Frame, rotation: Quaternion, second_rotation: Option<Quaternion>) -> Result<(), DemoError> {
+    demo.model.transform.rotation = rotation;
+    // The model keeps its rotation for the readouts, and is only drawn without it.
+    let mut model_transform = demo.model.transform;
+    if demo.rotate_camera {
+        model_transform.rotation = Quaternion::identity();
+        demo.camera.set_extra_rotation(demo.model.transform.position, rotation);
+    } else {
+        demo.camera.set_extra_rotation(demo.model.transform.position, Quaternion::identity());
+    }
+    render_model(&demo.grid, &demo.program, &demo.camera, &demo.light, target)?;
+    render_model(&demo.world_axes, &demo.program, &demo.camera, &demo.light, target)?;
+    let model_translucent = demo.model_opacity < 1.0;
+    // The outline contrasts with the background. A see-through model would show it
+    // through itself, so only opaque ones get one, and none while showing the depth.
+    let outline_color = if demo.background_color.iter().sum::<f32>() < 1.5 { [1.0; 4] } else { [0.0, 0.0, 0.0, 1.0] };
+    let draw_outline = demo.show_outline && !model_translucent && !demo.light.show_depth;
+    if draw_outline {
+        render_outline(&demo.model, &model_transform, outline_color, &demo.program, &demo.camera, target)?;
+    }
+    if !model_translucent {
+        render_model_with(&demo.model, &model_transform, NO_TINT, &demo.program, &demo.camera, &demo.light, target)?;
+    }
+    render_model_with(&demo.local_axes, &model_transform, NO_TINT, &demo.program, &demo.camera, &demo.light, target)?;
+
+    if demo.show_normals {
+        if demo.normals.is_none() {
+            match create_normals_model(display, &demo.model) {
+                Ok(lines) => demo.normals = Some(lines),
+                Err(e) => {
+                    warn!("failed to show normals: {}", e);
+                    demo.show_normals = false;
+                }
+            }
+        }
+        if let Some(ref normals) = demo.normals {
+            render_model_with(normals, &model_transform, NO_TINT, &demo.program, &demo.camera, &demo.light, target)?;
+        }
+    }
+
+    if let Some(second_rotation) = second_rotation {
+        let mut second_transform = demo.model.transform;
+        second_transform.position[0] += COMPARE_OFFSET;
+        second_transform.rotation = second_rotation;
+        if draw_outline {
+            render_outline(&demo.model, &second_transform, outline_color, &demo.program, &demo.camera, target)?;
+        }
+        render_model_with(&demo.model, &second_transform, NO_TINT, &demo.program, &demo.camera, &demo.light, target)?;
+        render_model_with(&demo.local_axes, &second_transform, NO_TINT, &demo.program, &demo.camera, &demo.light, target)?;
+    }
+
+    // A see-through model doesn't write depth, so it's drawn after the opaque models for them
+    // to show through it. Its own faces aren't sorted, which is close enough for convex meshes.
+    if model_translucent {
+        let model_tint = [1.0, 1.0, 1.0, demo.model_opacity];
+        render_model_with(&demo.model, &model_transform, model_tint, &demo.program, &demo.camera, &demo.light, target)?;
+    }
+
+    if demo.show_ghost {
+        const GHOST_TINT: [f32; 4] = [1.0, 1.0, 1.0, 0.3];
+        let mut ghost_transform = demo.model.transform;
+        ghost_transform.rotation = Quaternion::identity();
+        render_model_with(&demo.model, &ghost_transform, GHOST_TINT, &demo.program, &demo.camera, &demo.light, target)?;
+    }
+
+    if demo.highlight_edited {
+        const HIGHLIGHT_TINT: [f32; 4] = [1.0, 0.8, 0.2, 0.35];
+        let mut highlight_transform = demo.model.transform;
+        highlight_transform.rotation = demo.quaternion_lists[demo.active_list].selected_rotation();
+        render_model_with(&demo.model, &highlight_transform, HIGHLIGHT_TINT, &demo.program, &demo.camera, &demo.light, target)?;
+    }
+    Ok(())
+}
+
+// The arrows, paths and trails drawn around the model to show what the rotations do.
+fn render_guides(demo: &mut Demo, display: &glium::Display, target: &mut glium::Frame, rotation: Quaternion, sphere_point_position: Vector3<f32>) -> Result<(), DemoError> {
+    if let Some(vertices) = axis_arrow_vertices(rotation) {
+        demo.axis_arrow.mesh.vertex_buffer.write(&vertices);
+        demo.axis_arrow.transform.position = demo.model.transform.position;
+        render_model(&demo.axis_arrow, &demo.program, &demo.camera, &demo.light, target)?;
+    }
+
+    if demo.show_rotation_axes {
+        let axes = animation::world_axes(&demo.quaternion_lists[demo.active_list].rotations());
+        while demo.rotation_arrows.len() < axes.len() {
+            demo.rotation_arrows.push(create_axis_arrow_model(display)?);
+        }
+        let entries = demo.quaternion_lists[demo.active_list].entries();
+        for ((&(axis, angle), entry), arrow) in axes.iter().zip(entries).zip(&mut demo.rotation_arrows) {
+            if let Some(vertices) = arrow_vertices(axis, angle, rotation_color(entry.color_id)) {
+                arrow.mesh.vertex_buffer.write(&vertices);
+                arrow.transform.position = demo.model.transform.position;
+                render_model(arrow, &demo.program, &demo.camera, &demo.light, target)?;
+            }
+        }
+    }
+
+    if !demo.trail_points.is_empty() {
+        demo.trail.mesh.vertex_buffer.write(&trail_vertices(&demo.trail_points));
+        render_model(&demo.trail, &demo.program, &demo.camera, &demo.light, target)?;
+    }
+
+    let segments = animation::segments(&demo.quaternion_lists[0].rotations());
+    if demo.show_slerp_path && demo.playback.playing && demo.playback.index < segments.len() {
+        const SPHERE_TINT: [f32; 4] = [1.0, 1.0, 1.0, 0.15];
+        let from = animation::orientation_at(&segments, demo.playback.index, 0.0);
+        let to = animation::orientation_at(&segments, demo.playback.index, 1.0);
+        let reference = Vector3::from(TRAIL_CORNER).normalize();
+        let points = animation::slerp_path(from, to, reference, SLERP_PATH_POINTS);
+        demo.slerp_path.mesh.vertex_buffer.write(&slerp_path_vertices(&points));
+        demo.slerp_path.transform.position = demo.model.transform.position;
+        demo.unit_sphere.transform.position = demo.model.transform.position;
+        render_model(&demo.slerp_path, &demo.program, &demo.camera, &demo.light, target)?;
+        render_model_with(&demo.unit_sphere, &demo.unit_sphere.transform, SPHERE_TINT, &demo.program, &demo.camera, &demo.light, target)?;
+    }
+
+    if demo.show_sphere_point {
+        const WIRE_SPHERE_TINT: [f32; 4] = [1.0, 1.0, 1.0, 0.3];
+        demo.wire_sphere.transform.position = demo.model.transform.position;
+        demo.sphere_point.transform.position = sphere_point_position;
+        render_model_with(&demo.wire_sphere, &demo.wire_sphere.transform, WIRE_SPHERE_TINT, &demo.program, &demo.camera, &demo.light, target)?;
+        render_model(&demo.sphere_point, &demo.program, &demo.camera, &demo.light, target)?;
+        if !demo.sphere_point_points.is_empty() {
+            demo.sphere_point_trail.mesh.vertex_buffer.write(&trail_vertices(&demo.sphere_point_points));
+            render_model(&demo.sphere_point_trail, &demo.program, &demo.camera, &demo.light, target)?;
+        }
+    }
+    Ok(())
+}
+
+// The navigation cube is drawn over the scene in its corner, with its own depth.
+fn render_nav_cube(demo: &mut Demo, display: &glium::Display, target: &mut glium::Frame) -> Result<(), DemoError> {
+    let (width, height) = target.get_dimensions();
+    let hidpi_factor = f64::from(display.gl_window().hidpi_factor());
+    let nav_rect = navcube::rect(f64::from(width) / hidpi_factor, f64::from(height) / hidpi_factor);
+    let nav_size = (navcube::SIZE * hidpi_factor) as u32;
+    let nav_viewport = glium::Rect {
+        left: (nav_rect.left() * hidpi_factor + f64::from(width) / 2.0).max(0.0) as u32,
+        bottom: (nav_rect.bottom() * hidpi_factor + f64::from(height) / 2.0).max(0.0) as u32,
+        width: nav_size,
+        height: nav_size,
+    };
+    demo.nav_camera.viewport_origin = (nav_viewport.left, nav_viewport.bottom);
+    demo.nav_camera.set_viewport_size(nav_size, nav_size);
+    demo.nav_camera.set_rotation(demo.camera.rotation());
+    target.clear(Some(&nav_viewport), None, false, Some(1.0), None);
+    render_model(&demo.nav_cube, &demo.program, &demo.nav_camera, &demo.light, target)?;
+    Ok(())
+}
+
+
+// Settings changed in the panel are kept for next time, but failing to save them isn't fatal.
+fn save_config(config: &Config) {
+    if let Err(e) = config.save(config::CONFIG_PATH) {
+        warn!("failed to save {}: {}", config::CONFIG_PATH, e);
+    }
+}
+
+// Returns zero if the slider was right-clicked or double-clicked, to be handled like the slider
+// being dragged to zero.
+fn reset_to_zero(ui: &conrod::UiCell, id: widget::Id) -> Option<f32> {
+    let input = ui.widget_input(id);
+    let double_clicked = input.events().any(|event| match event {
+        conrod::event::Widget::DoubleClick(click) => click.button == conrod::input::MouseButton::Left,
+        _ => false,
+    });
+    if double_clicked || input.clicks().right().next().is_some() {
+        Some(0.0)
+    } else {
+        None
+    }
+}
+
+fn duration_secs(duration: Duration) -> f32 {
+    duration.as_secs() as f32 + duration.subsec_nanos() as f32 * 1.0e-9
+}
+
+// Saves the last drawn frame to a PNG named after the current time, returning its path.
+fn save_screenshot(display: &glium::Display) -> Result<String, DemoError> {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let image: glium::texture::RawImage2d<u8> = display.read_front_buffer();
+    let pixels = export::flip_rows(&image.data, image.width as usize * 4);
+    // A clock set before 1970 only spoils the name.
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let path = format!("screenshot-{}.png", timestamp);
+    export::write_png(&path, &pixels, image.width, image.height)?;
+    Ok(path)
+}
+
+// Renders one frame showing the model turned by the given rotations to a PNG, without opening a
+// window. This is for making thumbnails, and for checking in CI that the rendering works.
+fn render_to_file(config: &Config, args: &cli::Args, path: &str) -> Result<(), DemoError> {
+    let (width, height) = (config.width, config.height);
+    let context = glium::glutin::HeadlessRendererBuilder::new(width, height).build()?;
+    let display = glium::HeadlessRenderer::new(context)?;
+
+    let program = create_shader_program(&display)?;
+    let grid = create_grid_model(&display)?;
+    let world_axes = create_world_axes_model(&display)?;
+    let local_axes = create_local_axes_model(&display)?;
+    let mut camera = Camera::new();
+    camera.set_viewport_size(width, height);
+    // There's only the one frame, so the camera goes straight to the views it's given.
+    camera.transition_duration = 0.0;
+    let mut model = match args.model_path {
+        Some(ref model_path) => {
+            let mut model = load_obj(&display, model_path)?;
+            frame_model(&display, &mut model, &mut camera)?;
+            model
+        }
+        None => create_axes_model(&display)?,
+    };
+    model.transform.rotation = Quaternion::compose(&args.rotations);
+    let light = Light::new();
+
+    let color = glium::texture::Texture2d::empty(&display, width, height)?;
+    let depth = glium::texture::DepthTexture2d::empty(&display, width, height)?;
+    {
+        let mut target = glium::framebuffer::SimpleFrameBuffer::with_depth_buffer(&display, &color, &depth)?;
+        target.clear_color_and_depth((0.0, 0.0, 0.0, 1.0), 1.0);
+        render_model(&grid, &program, &camera, &light, &mut target)?;
+        render_model(&world_axes, &program, &camera, &light, &mut target)?;
+        render_model(&model, &program, &camera, &light, &mut target)?;
+        render_model_with(&local_axes, &model.transform, NO_TINT, &program, &camera, &light, &mut target)?;
+    }
+
+    let image: glium::texture::RawImage2d<u8> = color.read();
+    let pixels = export::flip_rows(&image.data, image.width as usize * 4);
+    export::write_png(path, &pixels, image.width, image.height)?;
+    Ok(())
+}
+
+// The number of segments to animate. When comparing, the animation runs until the longer list is done.
+fn animated_segments(lists: &[RotationList; 2], comparing: bool) -> usize {
+    let first = animation::segments(&lists[0].rotations()).len();
+    if comparing {
+        first.max(animation::segments(&lists[1].rotations()).len())
+    } else {
+        first
+    }
+}
+
+// winit panics, rather than returning an error, when there's no display to open a window on, like
+// over SSH or on a server. Its panic message is replaced by the demo's own error.
+fn create_events_loop() -> Result<glium::glutin::EventsLoop, DemoError> {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let events_loop = std::panic::catch_unwind(glium::glutin::EventsLoop::new);
+    std::panic::set_hook(default_hook);
+    events_loop.map_err(|_| DemoError::NoDisplay)
+}
+
+// Not every GL context can multisample, or with as many samples as asked for, so each sample
+// count that fails is retried with the next lower one, down to none.
+fn create_display(events_loop: &glium::glutin::EventsLoop, config: &Config, msaa_samples: u16) -> Result<glium::Display, DemoError> {
+    let mut samples = msaa_samples;
+    loop {
+        let window = glium::glutin::WindowBuilder::new()
+            .with_title("Quaternion Demo")
+            .with_dimensions(config.width, config.height);
+        let context = glium::glutin::ContextBuilder::new()
+            .with_vsync(config.vsync)
+            .with_multisampling(samples);
+        match glium::Display::new(window, context, events_loop) {
+            Ok(display) => return Ok(display),
+            Err(e) => if samples == 0 {
+                return Err(e.into());
+            } else {
+                let fewer = config::fewer_msaa_samples(samples);
+                warn!("couldn't create the window with {}x multisampling, trying {}: {}", samples, fewer, e);
+                samples = fewer;
+            },
+        }
+    }
+}
+
+fn create_shader_program(display: &Facade) -> Result<Program, DemoError> {
+    build_shader_program(display, &asset!("vertex.glsl")?, &asset!("fragment.glsl")?)
+}
+
+// Like `create_shader_program`, but always reads the shaders from `assets_dir()`, even when
+// they're built into the executable, so edits show up without rebuilding.
+fn reload_shader_program(display: &Facade) -> Result<Program, DemoError> {
+    let assets = assets_dir();
+    let vertex_src = read_asset(&assets.join("vertex.glsl"))?;
+    let fragment_src = read_asset(&assets.join("fragment.glsl"))?;
+    build_shader_program(display, &vertex_src, &fragment_src)
+}
+
+fn build_shader_program(display: &Facade, vertex_src: &[u8], fragment_src: &[u8]) -> Result<Program, DemoError> {
+    let utf8 = |src| std::str::from_utf8(src).map_err(|e| DemoError::InvalidData(format!("invalid shader source: {}", e)));
+    let program = Program::from_source(display, utf8(vertex_src)?, utf8(fragment_src)?, None)?;
+    info!("Built the shaders ({} and {} bytes of source)", vertex_src.len(), fragment_src.len());
+    Ok(program)
+}
+
+const MESHES: [&str; 3] = ["Cube", "Sphere", "Cone"];
+
+fn create_mesh_model(display: &Facade, index: usize) -> Result<Model, DemoError> {
+    const GRAY: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+    let (vertices, indices) = match MESHES[index] {
+        "Sphere" => shapes::uv_sphere(0.75, 32, 16, GRAY),
+        "Cone" => shapes::cone(0.75, 1.5, 32, GRAY),
+        _ => return create_axes_model(display),
+    };
+    let model = Model::new(Mesh {
+        vertex_buffer: VertexBuffer::new(display, &vertices)?,
+        index_buffer: IndexBuffer::new(display, glium::index::PrimitiveType::TrianglesList, &indices)?,
+        texture: None,
+    });
+
+    Ok(model)
+}
+
+// Copies the mesh with its normals recomputed for smooth or flat shading. Both keep the positions,
+// but flat shading gives each triangle its own vertices.
+fn reshade_mesh(display: &Facade, mesh: &Mesh, smooth: bool) -> Result<Mesh, DemoError> {
+    let vertices = mesh.vertex_buffer.read()?;
+    let indices = mesh.index_buffer.read()?;
+    let (vertices, indices) = if smooth {
+        shapes::smooth_shaded(&vertices, &indices)
+    } else {
+        shapes::flat_shaded(&vertices, &indices)
+    };
+    if vertices.len() > u16::max_value() as usize + 1 {
+        return Err(DemoError::InvalidData(format!("too many vertices to flat shade ({})", vertices.len())));
+    }
+
+    Ok(Mesh {
+        vertex_buffer: VertexBuffer::new(display, &vertices)?,
+        index_buffer: IndexBuffer::new(display, glium::index::PrimitiveType::TrianglesList, &indices)?,
+        texture: mesh.texture.clone(),
+    })
+}
+
+// Moves the model's mesh so the center of its bounds is at the model's origin, which it turns
+// about, and puts the model back at the world's origin. Then the camera is moved to fit the whole
+// model in view, however large it is. The vertices are read back from the GPU, like
+// `reshade_mesh` does.
+fn frame_model(display: &Facade, model: &mut Model, camera: &mut Camera) -> Result<(), DemoError> {
+    let vertices = model.mesh.vertex_buffer.read()?;
+    let bounds = match shapes::Bounds::of(&vertices) {
+        Some(bounds) => bounds,
+        None => return Ok(()),
+    };
+
+    const MIN_OFFSET: f32 = 1.0e-6;
+    if bounds.center().norm() > MIN_OFFSET {
+        let centered = shapes::translated(&vertices, -bounds.center());
+        let indices = model.mesh.index_buffer.read()?;
+        model.mesh = Rc::new(Mesh {
+            vertex_buffer: VertexBuffer::new(display, &centered)?,
+            index_buffer: IndexBuffer::new(display, model.mesh.index_buffer.get_primitives_type(), &indices)?,
+            texture: model.mesh.texture.clone(),
+        });
+    }
+    model.transform.position = Vector3::zeros();
+
+    let scale = model.transform.scale.iter().fold(0.0f32, |largest, s| largest.max(s.abs()));
+    camera.frame(model.transform.position, bounds.radius() * scale);
+    Ok(())
+}
+
+// A cube with its faces colored by axis, and numbered like a die so that opposite faces add up to 7.
+fn create_axes_model(display: &Facade) -> Result<Model, DemoError> {
+    const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+    const GREEN: [f32; 4] = [0.0, 1.0, 0.0, 1.0];
+    const BLUE: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+
+    const RIGHT: [f32; 3] = [1.0, 0.0, 0.0];
+    const LEFT: [f32; 3] = [-1.0, 0.0, 0.0];
+    const UP: [f32; 3] = [0.0, 1.0, 0.0];
+    const DOWN: [f32; 3] = [0.0, -1.0, 0.0];
+    const FRONT: [f32; 3] = [0.0, 0.0, 1.0];
+    const BACK: [f32; 3] = [0.0, 0.0, -1.0];
+
+    let vertices = vec![
+        // -y
+        Vertex { position: [-0.5, -0.5, -0.5], color: GREEN, normal: DOWN, tex_coords: [1.0 / 3.0, 0.0] },
+        Vertex { position: [-0.5, -0.5, 0.5], color: GREEN, normal: DOWN, tex_coords: [1.0 / 3.0, 0.5] },
+        Vertex { position: [0.5, -0.5, 0.5], color: GREEN, normal: DOWN, tex_coords: [2.0 / 3.0, 0.5] },
+        Vertex { position: [0.5, -0.5, -0.5], color: GREEN, normal: DOWN, tex_coords: [2.0 / 3.0, 0.0] },
+
+        // +y
+        Vertex { position: [-0.5, 0.5, -0.5], color: GREEN, normal: UP, tex_coords: [1.0 / 3.0, 1.0] },
+        Vertex { position: [-0.5, 0.5, 0.5], color: GREEN, normal: UP, tex_coords: [1.0 / 3.0, 0.5] },
+        Vertex { position: [0.5, 0.5, 0.5], color: GREEN, normal: UP, tex_coords: [2.0 / 3.0, 0.5] },
+        Vertex { position: [0.5, 0.5, -0.5], color: GREEN, normal: UP, tex_coords: [2.0 / 3.0, 1.0] },
+
+        // -z
+        Vertex { position: [-0.5, -0.5, -0.5], color: BLUE, normal: BACK, tex_coords: [1.0 / 3.0, 0.0] },
+        Vertex { position: [-0.5, 0.5, -0.5], color: BLUE, normal: BACK, tex_coords: [1.0 / 3.0, 0.5] },
+        Vertex { position: [0.5, 0.5, -0.5], color: BLUE, normal: BACK, tex_coords: [0.0, 0.5] },
+        Vertex { position: [0.5, -0.5, -0.5], color: BLUE, normal: BACK, tex_coords: [0.0, 0.0] },
+
+        // +z
+        Vertex { position: [-0.5, -0.5, 0.5], color: BLUE, normal: FRONT, tex_coords: [2.0 / 3.0, 0.5] },
+        Vertex { position: [-0.5, 0.5, 0.5], color: BLUE, normal: FRONT, tex_coords: [2.0 / 3.0, 1.0] },
+        Vertex { position: [0.5, 0.5, 0.5], color: BLUE, normal: FRONT, tex_coords: [1.0, 1.0] },
+        Vertex { position: [0.5, -0.5, 0.5], color: BLUE, normal: FRONT, tex_coords: [1.0, 0.5] },
+
+        // -x
+        Vertex { position: [-0.5, -0.5, -0.5], color: RED, normal: LEFT, tex_coords: [2.0 / 3.0, 0.0] },
+        Vertex { position: [-0.5, -0.5, 0.5], color: RED, normal: LEFT, tex_coords: [1.0, 0.0] },
+        Vertex { position: [-0.5, 0.5, 0.5], color: RED, normal: LEFT, tex_coords: [1.0, 0.5] },
+        Vertex { position: [-0.5, 0.5, -0.5], color: RED, normal: LEFT, tex_coords: [2.0 / 3.0, 0.5] },
+        
+        // +x
+        Vertex { position: [0.5, -0.5, -0.5], color: RED, normal: RIGHT, tex_coords: [1.0 / 3.0, 0.5] },
+        Vertex { position: [0.5, -0.5, 0.5], color: RED, normal: RIGHT, tex_coords: [0.0, 0.5] },
+        Vertex { position: [0.5, 0.5, 0.5], color: RED, normal: RIGHT, tex_coords: [0.0, 1.0] },
+        Vertex { position: [0.5, 0.5, -0.5], color: RED, normal: RIGHT, tex_coords: [1.0 / 3.0, 1.0] },
+    ];
+    let indices = vec![
+        // y
+        0, 1, 2, 0, 2, 3,
+        4, 5, 6, 4, 6, 7,
+
+        // z
+        8, 9, 10, 8, 10, 11,
+        12, 13, 14, 12, 14, 15,
+
+        // x
+        16, 17, 18, 16, 18, 19,
+        20, 21, 22, 20, 22, 23,
+    ];
+    let model = Model::new(Mesh {
+        vertex_buffer: VertexBuffer::new(display, &vertices)?,
+        index_buffer: IndexBuffer::new(display, glium::index::PrimitiveType::TrianglesList, &indices)?,
+        texture: Some(Rc::new(load_texture(display, &asset!("cube_faces.png")?)?)),
+    });
+
+    Ok(model)
+}
+
+// Loads a texture from the contents of an image file.
+fn load_texture(display: &Facade, file: &[u8]) -> Result<glium::texture::Texture2d, DemoError> {
+    let image = image::load_from_memory(file)?.to_rgba();
+    let dimensions = image.dimensions();
+    // Images are stored top row first, but texture coordinates start at the bottom.
+    let image = glium::texture::RawImage2d::from_raw_rgba_reversed(&image.into_raw(), dimensions);
+    Ok(glium::texture::Texture2d::new(display, image)?)
+}
+
+// A grid of lines on the XZ plane, below the model.
+fn create_grid_model(display: &Facade) -> Result<Model, DemoError> {
+    const GRID_LINES: i32 = 10;
+    const GRID_SPACING: f32 = 1.0;
+    const GRID_HEIGHT: f32 = -1.0;
+    const GRID_COLOR: [f32; 4] = [0.4, 0.4, 0.4, 1.0];
+    const UP: [f32; 3] = [0.0, 1.0, 0.0];
+
+    let extent = GRID_LINES as f32 * GRID_SPACING;
+    let mut vertices = Vec::new();
+    for i in -GRID_LINES..=GRID_LINES {
+        let offset = i as f32 * GRID_SPACING;
+        vertices.push(Vertex { position: [offset, 0.0, -extent], color: GRID_COLOR, normal: UP, tex_coords: [0.0, 0.0] });
+        vertices.push(Vertex { position: [offset, 0.0, extent], color: GRID_COLOR, normal: UP, tex_coords: [0.0, 0.0] });
+        vertices.push(Vertex { position: [-extent, 0.0, offset], color: GRID_COLOR, normal: UP, tex_coords: [0.0, 0.0] });
+        vertices.push(Vertex { position: [extent, 0.0, offset], color: GRID_COLOR, normal: UP, tex_coords: [0.0, 0.0] });
+    }
+    let indices: Vec<u16> = (0..vertices.len() as u16).collect();
+
+    let mut model = Model::new(Mesh {
+        vertex_buffer: VertexBuffer::new(display, &vertices)?,
+        index_buffer: IndexBuffer::new(display, glium::index::PrimitiveType::LinesList, &indices)?,
+        texture: None,
+    });
+    model.transform.position[1] = GRID_HEIGHT;
 
     Ok(model)
 }
 
-fn render_model(model: &Model, program: &glium::Program, camera: &Camera, target: &mut glium::Frame) -> Result<(), Box<Error>> {
-    let view_matrix: [[f32; 4]; 4] = camera.transform.to_matrix().try_inverse().unwrap().into();
-    let projection_matrix: [[f32; 4]; 4] = camera.projection.into();
-    let model_matrix: [[f32; 4]; 4] = model.transform.to_matrix().into();
-    let light_pos: [f32; 3] = [2.0, 2.0, 2.0];
+const WORLD_AXIS_LENGTH: f32 = 2.0;
+// How far along X the second of two compared models is drawn from the first.
+const COMPARE_OFFSET: f32 = 2.0;
+
+// Fixed red, green and blue lines along the world X, Y and Z axes.
+fn create_world_axes_model(display: &Facade) -> Result<Model, DemoError> {
+    const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+    const GREEN: [f32; 4] = [0.0, 1.0, 0.0, 1.0];
+    const BLUE: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+
+    create_axis_lines_model(display, WORLD_AXIS_LENGTH, [RED, GREEN, BLUE])
+}
+
+// Shorter, paler lines along the model's own axes, drawn with the model's transform.
+fn create_local_axes_model(display: &Facade) -> Result<Model, DemoError> {
+    const LOCAL_AXIS_LENGTH: f32 = 1.0;
+    const PALE_RED: [f32; 4] = [1.0, 0.6, 0.6, 1.0];
+    const PALE_GREEN: [f32; 4] = [0.6, 1.0, 0.6, 1.0];
+    const PALE_BLUE: [f32; 4] = [0.6, 0.6, 1.0, 1.0];
+
+    create_axis_lines_model(display, LOCAL_AXIS_LENGTH, [PALE_RED, PALE_GREEN, PALE_BLUE])
+}
+
+// Lines of the given length from the origin along X, Y and Z, in the given colors.
+fn create_axis_lines_model(display: &Facade, length: f32, colors: [[f32; 4]; 3]) -> Result<Model, DemoError> {
+    const NORMAL: [f32; 3] = [0.0, 1.0, 0.0];
+
+    let vertices = vec![
+        Vertex { position: [0.0, 0.0, 0.0], color: colors[0], normal: NORMAL, tex_coords: [0.0, 0.0] },
+        Vertex { position: [length, 0.0, 0.0], color: colors[0], normal: NORMAL, tex_coords: [0.0, 0.0] },
+        Vertex { position: [0.0, 0.0, 0.0], color: colors[1], normal: NORMAL, tex_coords: [0.0, 0.0] },
+        Vertex { position: [0.0, length, 0.0], color: colors[1], normal: NORMAL, tex_coords: [0.0, 0.0] },
+        Vertex { position: [0.0, 0.0, 0.0], color: colors[2], normal: NORMAL, tex_coords: [0.0, 0.0] },
+        Vertex { position: [0.0, 0.0, length], color: colors[2], normal: NORMAL, tex_coords: [0.0, 0.0] },
+    ];
+    let indices: Vec<u16> = vec![0, 1, 2, 3, 4, 5];
+    let model = Model::new(Mesh {
+        vertex_buffer: VertexBuffer::new(display, &vertices)?,
+        index_buffer: IndexBuffer::new(display, glium::index::PrimitiveType::LinesList, &indices)?,
+        texture: None,
+    });
+
+    Ok(model)
+}
+
+const AXIS_ARROW_VERTICES: usize = 10;
+
+// The arrow showing the rotation axis, with a vertex buffer that is rewritten each frame.
+fn create_axis_arrow_model(display: &Facade) -> Result<Model, DemoError> {
+    let vertices = [Vertex { position: [0.0; 3], color: [0.0; 4], normal: [0.0; 3], tex_coords: [0.0, 0.0] }; AXIS_ARROW_VERTICES];
+    let indices: Vec<u16> = (0..AXIS_ARROW_VERTICES as u16).collect();
+    let model = Model::new(Mesh {
+        vertex_buffer: VertexBuffer::dynamic(display, &vertices)?,
+        index_buffer: IndexBuffer::new(display, glium::index::PrimitiveType::LinesList, &indices)?,
+        texture: None,
+    });
+
+    Ok(model)
+}
+
+// Builds an arrow from the origin along the rotation's axis, with a length proportional to its angle.
+// Returns `None` for rotations too close to the identity to have a meaningful axis.
+fn axis_arrow_vertices(rotation: Quaternion) -> Option<[Vertex; AXIS_ARROW_VERTICES]> {
+    const YELLOW: [f32; 4] = [1.0, 1.0, 0.0, 1.0];
+    let (axis, angle) = rotation.to_axis_angle();
+    arrow_vertices(axis, angle, YELLOW)
+}
+
+// Builds the arrow for turning `angle` radians about `axis`, in `color`.
+fn arrow_vertices(mut axis: Vector3<f32>, mut angle: f32, color: [f32; 4]) -> Option<[Vertex; AXIS_ARROW_VERTICES]> {
+    const MIN_ANGLE: f32 = 0.01;
+    const MAX_LENGTH: f32 = 2.0;
+    const HEAD_SIZE: f32 = 0.15;
+    use std::f32::consts::PI;
+
+    // Show the shorter way around, so the length stays within 180 degrees.
+    if angle > PI {
+        axis = -axis;
+        angle = 2.0 * PI - angle;
+    }
+    if angle < MIN_ANGLE {
+        return None;
+    }
+
+    let tip = axis * (angle / PI * MAX_LENGTH);
+    let side = if axis[0].abs() < 0.9 { Vector3::x() } else { Vector3::y() };
+    let side = axis.cross(&side).normalize() * HEAD_SIZE;
+    let up = axis.cross(&side);
+    let base = tip - axis * (HEAD_SIZE * 2.0);
+
+    let mut vertices = [Vertex { position: [0.0; 3], color, normal: [0.0; 3], tex_coords: [0.0, 0.0] }; AXIS_ARROW_VERTICES];
+    vertices[1].position = tip.into();
+    for (i, offset) in [side, -side, up, -up].iter().enumerate() {
+        vertices[2 + i * 2].position = tip.into();
+        vertices[3 + i * 2].position = (base + offset).into();
+    }
+    Some(vertices)
+}
+
+// The colors the rotations in a list cycle through, in the panel and for their axis arrows. Each
+// rotation keeps a color id as others are inserted before it, and the id picks its color.
+const ROTATION_COLORS: [[f32; 4]; 6] = [
+    [0.95, 0.35, 0.35, 1.0],
+    [0.35, 0.85, 0.35, 1.0],
+    [0.4, 0.6, 1.0, 1.0],
+    [1.0, 0.65, 0.2, 1.0],
+    [0.8, 0.45, 1.0, 1.0],
+    [0.3, 0.9, 0.9, 1.0],
+];
+
+fn rotation_color(color_id: usize) -> [f32; 4] {
+    ROTATION_COLORS[color_id % ROTATION_COLORS.len()]
+}
+
+const TRAIL_VERTICES: usize = 120;
+// The corner of the cube whose path is traced while animating.
+const TRAIL_CORNER: [f32; 3] = [0.5, 0.5, 0.5];
+
+// The trail behind the animated corner, drawn as a line strip rewritten each frame.
+fn create_trail_model(display: &Facade) -> Result<Model, DemoError> {
+    let vertices = [Vertex { position: [0.0; 3], color: [0.0; 4], normal: [0.0; 3], tex_coords: [0.0, 0.0] }; TRAIL_VERTICES];
+    let indices: Vec<u16> = (0..TRAIL_VERTICES as u16).collect();
+    let model = Model::new(Mesh {
+        vertex_buffer: VertexBuffer::dynamic(display, &vertices)?,
+        index_buffer: IndexBuffer::new(display, glium::index::PrimitiveType::LineStrip, &indices)?,
+        texture: None,
+    });
+
+    Ok(model)
+}
+
+// Builds the trail's vertices, fading out towards its oldest points. Since the buffer has a fixed
+// size, the unused vertices collapse onto the oldest point.
+fn trail_vertices(points: &VecDeque<Vector3<f32>>) -> Vec<Vertex> {
+    const CYAN: [f32; 3] = [0.0, 1.0, 1.0];
+
+    let padding = TRAIL_VERTICES - points.len();
+    let oldest = points[0].into();
+    let mut vertices = vec![Vertex { position: oldest, color: [CYAN[0], CYAN[1], CYAN[2], 0.0], normal: [0.0; 3], tex_coords: [0.0, 0.0] }; padding];
+    for (i, point) in points.iter().enumerate() {
+        let alpha = (padding + i + 1) as f32 / TRAIL_VERTICES as f32;
+        vertices.push(Vertex { position: (*point).into(), color: [CYAN[0], CYAN[1], CYAN[2], alpha], normal: [0.0; 3], tex_coords: [0.0, 0.0] });
+    }
+    vertices
+}
+
+const SLERP_PATH_POINTS: usize = 48;
+// The arc is drawn as a line between each pair of its points, followed by the chord.
+const SLERP_PATH_VERTICES: usize = (SLERP_PATH_POINTS - 1) * 2 + 2;
+
+// The slerp arc and the lerp chord, with a vertex buffer that is rewritten each frame.
+fn create_slerp_path_model(display: &Facade) -> Result<Model, DemoError> {
+    let vertices = [Vertex { position: [0.0; 3], color: [0.0; 4], normal: [0.0; 3], tex_coords: [0.0, 0.0] }; SLERP_PATH_VERTICES];
+    let indices: Vec<u16> = (0..SLERP_PATH_VERTICES as u16).collect();
+    let model = Model::new(Mesh {
+        vertex_buffer: VertexBuffer::dynamic(display, &vertices)?,
+        index_buffer: IndexBuffer::new(display, glium::index::PrimitiveType::LinesList, &indices)?,
+        texture: None,
+    });
+
+    Ok(model)
+}
+
+// Builds the arc through `points`, and the chord straight from its first point to its last.
+fn slerp_path_vertices(points: &[Vector3<f32>]) -> Vec<Vertex> {
+    const ORANGE: [f32; 4] = [1.0, 0.5, 0.0, 1.0];
+    const GRAY: [f32; 4] = [0.6, 0.6, 0.6, 1.0];
+
+    let vertex = |point: Vector3<f32>, color| Vertex { position: point.into(), color, normal: [0.0; 3], tex_coords: [0.0, 0.0] };
+    let mut vertices = Vec::with_capacity(SLERP_PATH_VERTICES);
+    for pair in points.windows(2) {
+        vertices.push(vertex(pair[0], ORANGE));
+        vertices.push(vertex(pair[1], ORANGE));
+    }
+    vertices.push(vertex(points[0], GRAY));
+    vertices.push(vertex(points[points.len() - 1], GRAY));
+    vertices
+}
+
+// A unit cube with a flat face for each of `navcube::FACES`, tinted after its axis like the world
+// axes, but muted so the labels stand out.
+fn create_nav_cube_model(display: &Facade) -> Result<Model, DemoError> {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    for face in &navcube::FACES {
+        let normal = Vector3::from(face.normal);
+        let up = Vector3::from(face.up);
+        let right = up.cross(&normal);
+        let color = [
+            0.3 + 0.4 * normal[0].abs(),
+            0.3 + 0.4 * normal[1].abs(),
+            0.3 + 0.4 * normal[2].abs(),
+            1.0,
+        ];
+        let first = vertices.len() as u16;
+        for &(x, y) in &[(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)] {
+            let position = normal * 0.5 + right * x + up * y;
+            vertices.push(Vertex { position: position.into(), color, normal: face.normal, tex_coords: [0.0, 0.0] });
+        }
+        indices.extend_from_slice(&[first, first + 1, first + 2, first, first + 2, first + 3]);
+    }
+    let model = Model::new(Mesh {
+        vertex_buffer: VertexBuffer::new(display, &vertices)?,
+        index_buffer: IndexBuffer::new(display, glium::index::PrimitiveType::TrianglesList, &indices)?,
+        texture: None,
+    });
+
+    Ok(model)
+}
+
+// The sphere that the slerp path lies on, drawn translucent around the model.
+fn create_unit_sphere_model(display: &Facade) -> Result<Model, DemoError> {
+    let (vertices, indices) = shapes::uv_sphere(1.0, 32, 16, [0.6, 0.6, 0.6, 1.0]);
+    let model = Model::new(Mesh {
+        vertex_buffer: VertexBuffer::new(display, &vertices)?,
+        index_buffer: IndexBuffer::new(display, glium::index::PrimitiveType::TrianglesList, &indices)?,
+        texture: None,
+    });
+
+    Ok(model)
+}
+
+// The lines of the sphere that the point on it moves over.
+fn create_wire_sphere_model(display: &Facade) -> Result<Model, DemoError> {
+    let (vertices, indices) = shapes::wire_sphere(1.0, 24, 12, [0.6, 0.6, 0.6, 1.0]);
+    let model = Model::new(Mesh {
+        vertex_buffer: VertexBuffer::new(display, &vertices)?,
+        index_buffer: IndexBuffer::new(display, glium::index::PrimitiveType::LinesList, &indices)?,
+        texture: None,
+    });
+
+    Ok(model)
+}
+
+// The marker for the point on the sphere.
+fn create_sphere_point_model(display: &Facade) -> Result<Model, DemoError> {
+    let (vertices, indices) = shapes::uv_sphere(0.04, 12, 6, [1.0, 1.0, 0.0, 1.0]);
+    let model = Model::new(Mesh {
+        vertex_buffer: VertexBuffer::new(display, &vertices)?,
+        index_buffer: IndexBuffer::new(display, glium::index::PrimitiveType::TrianglesList, &indices)?,
+        texture: None,
+    });
+
+    Ok(model)
+}
+
+// A line from each of the model's vertices along its normal, in the model's local space.
+fn create_normals_model(display: &Facade, model: &Model) -> Result<Model, DemoError> {
+    const NORMAL_LENGTH: f32 = 0.2;
+    const MAGENTA: [f32; 4] = [1.0, 0.0, 1.0, 1.0];
+
+    let mut vertices = Vec::new();
+    for vertex in model.mesh.vertex_buffer.read()? {
+        let end = Vector3::from(vertex.position) + Vector3::from(vertex.normal) * NORMAL_LENGTH;
+        vertices.push(Vertex { position: vertex.position, color: MAGENTA, normal: vertex.normal, tex_coords: [0.0, 0.0] });
+        vertices.push(Vertex { position: end.into(), color: MAGENTA, normal: vertex.normal, tex_coords: [0.0, 0.0] });
+    }
+    if vertices.len() > u16::max_value() as usize + 1 {
+        return Err(DemoError::InvalidData(format!("too many normals to draw ({})", vertices.len() / 2)));
+    }
+
+    let indices: Vec<u16> = (0..vertices.len() as u32).map(|i| i as u16).collect();
+    let model = Model::new(Mesh {
+        vertex_buffer: VertexBuffer::new(display, &vertices)?,
+        index_buffer: IndexBuffer::new(display, glium::index::PrimitiveType::LinesList, &indices)?,
+        texture: None,
+    });
+
+    Ok(model)
+}
+
+fn load_obj(display: &Facade, path: &str) -> Result<Model, DemoError> {
+    const GRAY: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+    let src = import::read_file(path)?;
+    let (vertices, indices) = obj::parse_obj(&src, GRAY)?;
+    info!("Loaded {} with {} vertices and {} triangles", path, vertices.len(), indices.len() / 3);
+    let model = Model::new(Mesh {
+        vertex_buffer: VertexBuffer::new(display, &vertices)?,
+        index_buffer: IndexBuffer::new(display, glium::index::PrimitiveType::TrianglesList, &indices)?,
+        texture: None,
+    });
+
+    Ok(model)
+}
+
+fn render_model<S: Surface>(model: &Model, program: &glium::Program, camera: &Camera, light: &Light, target: &mut S) -> Result<(), DemoError> {
+    render_model_with(model, &model.transform, NO_TINT, program, camera, light, target)
+}
+
+// Leaves a model's colors as they are.
+const NO_TINT: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+// Draws the model's buffers with the given transform instead of its own, with its colors
+// multiplied by `tint`. A tint alpha below one blends the model over what's behind it.
+fn render_model_with<S: Surface>(model: &Model, transform: &Transform, tint: [f32; 4], program: &glium::Program, camera: &Camera, light: &Light, target: &mut S) -> Result<(), DemoError> {
+    draw_model(model, transform, tint, false, program, camera, light, target)
+}
+
+// Draws the model's silhouette in `color`, grown around its origin and without writing depth, so
+// that the model drawn over it straight after leaves a rim of it showing. The meshes' triangles
+// aren't all wound the same way, so the model covers the inside of the silhouette by being drawn
+// after it, rather than the silhouette culling its front faces.
+fn render_outline<S: Surface>(model: &Model, transform: &Transform, color: [f32; 4], program: &glium::Program, camera: &Camera, target: &mut S) -> Result<(), DemoError> {
+    const OUTLINE_SCALE: f32 = 1.04;
+    let mut outline_transform = *transform;
+    outline_transform.scale *= OUTLINE_SCALE;
+    draw_model(model, &outline_transform, color, true, program, camera, &Light::new(), target)
+}
+
+// The model, view and projection matrices for drawing with `transform`, as the shaders take them.
+type ShaderMatrix = [[f32; 4]; 4];
+
+fn mvp_matrices(transform: &Transform, camera: &Camera) -> (ShaderMatrix, ShaderMatrix, ShaderMatrix) {
+    (transform.to_matrix().into(), camera.view_matrix().into(), camera.projection().into())
+}
+
+// With `flat_color`, the model is filled with `tint` alone and leaves the depth buffer as it was.
+#[allow(clippy::too_many_arguments)]
+fn draw_model<S: Surface>(model: &Model, transform: &Transform, tint: [f32; 4], flat_color: bool, program: &glium::Program, camera: &Camera, light: &Light, target: &mut S) -> Result<(), DemoError> {
+    let (model_matrix, view_matrix, projection_matrix) = mvp_matrices(transform, camera);
+    let camera_position: [f32; 3] = camera.position().into();
+    let translucent = tint[3] < 1.0;
+    // Lines have no surface to light, so they're drawn in their flat colors.
+    let lighting = model.mesh.index_buffer.get_primitives_type() == glium::index::PrimitiveType::TrianglesList;
 
     use glium::draw_parameters::BackfaceCullingMode;
-    target.draw(&model.vertex_buffer, &model.index_buffer, &program,
-            &uniform! {
-                u_model: model_matrix,
-                u_view: view_matrix,
-                u_projection: projection_matrix,
-                u_light_position: light_pos,
-            },
-            &glium::DrawParameters {
-                depth: glium::Depth {
-                    test: glium::DepthTest::IfLess,
-                    write: true,
-                    .. Default::default()
-                },
-                backface_culling: BackfaceCullingMode::CullingDisabled,
-                .. Default::default()
-            })?;
+    let uniforms = uniform! {
+        u_model: model_matrix,
+        u_view: view_matrix,
+        u_projection: projection_matrix,
+        u_light_position: light.position,
+        u_camera_position: camera_position,
+        u_ambient: light.ambient,
+        u_diffuse: light.diffuse,
+        u_specular: light.specular,
+        u_shininess: light.shininess,
+        u_double_sided: light.double_sided,
+        u_gamma_correct: light.gamma_correct,
+        u_lighting: lighting,
+        u_tint: tint,
+        u_flat_color: flat_color,
+        u_show_depth: light.show_depth,
+        u_near: camera.near(),
+        u_far: camera.far(),
+        u_orthographic: camera.orthographic(),
+    };
+    let params = glium::DrawParameters {
+        depth: glium::Depth {
+            test: glium::DepthTest::IfLess,
+            // Translucent models and outlines shouldn't hide what's drawn after them.
+            write: !translucent && !flat_color,
+            .. Default::default()
+        },
+        // Always blend, so lines can fade out through their vertex colors.
+        blend: glium::Blend::alpha_blending(),
+        backface_culling: BackfaceCullingMode::CullingDisabled,
+        viewport: Some(glium::Rect {
+            left: camera.viewport_origin.0,
+            bottom: camera.viewport_origin.1,
+            width: camera.viewport_size().0,
+            height: camera.viewport_size().1,
+        }),
+        .. Default::default()
+    };
+
+    let mesh = &model.mesh;
+    match mesh.texture {
+        Some(ref texture) => {
+            let uniforms = uniforms.add("u_texture", &**texture).add("u_textured", true);
+            target.draw(&mesh.vertex_buffer, &mesh.index_buffer, &program, &uniforms, &params)?;
+        }
+        None => {
+            let uniforms = uniforms.add("u_textured", false);
+            target.draw(&mesh.vertex_buffer, &mesh.index_buffer, &program, &uniforms, &params)?;
+        }
+    }
 
     Ok(())
 }
@@ -0,0 +1,135 @@
+use nalgebra::base::Vector3;
+use conrod::{Point, Rect};
+use camera::Camera;
+
+/// Width and height of the navigation cube's corner of the 3D view, in logical pixels.
+pub const SIZE: f64 = 120.0;
+/// Space between the cube's corner and the top-right of the window, leaving room for the help
+/// button above it.
+pub const MARGIN_TOP: f64 = 50.0;
+pub const MARGIN_RIGHT: f64 = 10.0;
+/// How far the cube's camera is from it. Close enough to fill the corner, but far enough that
+/// the cube's corners stay inside it from any side.
+pub const CAMERA_DISTANCE: f32 = 2.2;
+
+/// A labeled face of the cube, and the view that clicking it moves the camera to.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Face {
+    pub label: &'static str,
+    /// Points out of the face, which is also the direction the camera looks from.
+    pub normal: [f32; 3],
+    /// The camera's up direction when looking at the face.
+    pub up: [f32; 3],
+}
+
+pub const FACES: [Face; 6] = [
+    Face { label: "Front", normal: [0.0, 0.0, 1.0], up: [0.0, 1.0, 0.0] },
+    Face { label: "Back", normal: [0.0, 0.0, -1.0], up: [0.0, 1.0, 0.0] },
+    Face { label: "Right", normal: [1.0, 0.0, 0.0], up: [0.0, 1.0, 0.0] },
+    Face { label: "Left", normal: [-1.0, 0.0, 0.0], up: [0.0, 1.0, 0.0] },
+    // Looking straight down or up, the top of the view faces away from the front.
+    Face { label: "Top", normal: [0.0, 1.0, 0.0], up: [0.0, 0.0, -1.0] },
+    Face { label: "Bottom", normal: [0.0, -1.0, 0.0], up: [0.0, 0.0, 1.0] },
+];
+
+/// The cube's corner of a `win_w` by `win_h` window, in conrod's coordinates.
+pub fn rect(win_w: f64, win_h: f64) -> Rect {
+    let right = win_w / 2.0 - MARGIN_RIGHT;
+    let top = win_h / 2.0 - MARGIN_TOP;
+    Rect::from_corners([right - SIZE, top - SIZE], [right, top])
+}
+
+/// Converts `point`, in conrod's coordinates, to normalized device coordinates within `rect`.
+/// Returns `None` for points outside it.
+pub fn ndc_at(rect: Rect, point: Point) -> Option<(f32, f32)> {
+    if !rect.is_over(point) {
+        return None;
+    }
+    let x = (point[0] - rect.x()) / (rect.w() / 2.0);
+    let y = (point[1] - rect.y()) / (rect.h() / 2.0);
+    Some((x as f32, y as f32))
+}
+
+/// Returns the face under `ndc`, given in the normalized device coordinates of `camera`'s
+/// viewport, for a unit cube at the origin seen through an orthographic `camera`.
+pub fn pick_face(camera: &Camera, ndc: (f32, f32)) -> Option<&'static Face> {
+    let half_height = camera.distance() * (camera.fov() / 2.0).tan();
+    let half_width = half_height * camera.aspect();
+    let rotation = camera.rotation();
+    // Cast a ray along the view direction, from in front of the cube.
+    let origin = camera.target() + rotation.rotate_vector(
+        [ndc.0 * half_width, ndc.1 * half_height, camera.distance()].into());
+    let direction = rotation.rotate_vector([0.0, 0.0, -1.0].into());
+
+    // Clip the ray against each pair of the cube's faces, where it enters the cube at the last
+    // of the faces it goes in through.
+    const HALF_SIZE: f32 = 0.5;
+    let (mut enter, mut exit) = (0.0f32, std::f32::INFINITY);
+    let mut enter_axis = None;
+    for axis in 0..3 {
+        if direction[axis].abs() < 1.0e-6 {
+            if origin[axis].abs() > HALF_SIZE {
+                return None;
+            }
+            continue;
+        }
+        let a = (-HALF_SIZE - origin[axis]) / direction[axis];
+        let b = (HALF_SIZE - origin[axis]) / direction[axis];
+        let (near, far) = (a.min(b), a.max(b));
+        if near > enter {
+            enter = near;
+            enter_axis = Some(axis);
+        }
+        exit = exit.min(far);
+    }
+    if enter > exit {
+        return None;
+    }
+
+    let axis = enter_axis?;
+    let mut normal = Vector3::zeros();
+    normal[axis] = -direction[axis].signum();
+    FACES.iter().find(|face| Vector3::from(face.normal) == normal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn face_camera(face: &Face) -> Camera {
+        let mut camera = Camera::new();
+        camera.set_viewport_size(1, 1);
+        camera.set_distance(CAMERA_DISTANCE);
+        camera.transition_duration = 0.0;
+        camera.look_from(face.normal.into(), face.up.into());
+        camera
+    }
+
+    #[test]
+    fn test_pick_face() {
+        for face in &FACES {
+            let camera = face_camera(face);
+            assert_eq!(pick_face(&camera, (0.0, 0.0)), Some(face), "{}", face.label);
+            // The corners of the view are past the cube.
+            assert_eq!(pick_face(&camera, (0.95, -0.95)), None, "{}", face.label);
+        }
+
+        // Looking at the top front edge from above the front, its top half is the top face.
+        let mut camera = face_camera(&FACES[0]);
+        camera.look_from([0.0, 1.0, 1.0].into(), [0.0, 1.0, 0.0].into());
+        assert_eq!(pick_face(&camera, (0.0, 0.2)).map(|face| face.label), Some("Top"));
+        assert_eq!(pick_face(&camera, (0.0, -0.2)).map(|face| face.label), Some("Front"));
+    }
+
+    #[test]
+    fn test_rect() {
+        let rect = rect(800.0, 600.0);
+        assert!((rect.right() - (400.0 - MARGIN_RIGHT)).abs() < 1.0e-9);
+        assert!((rect.top() - (300.0 - MARGIN_TOP)).abs() < 1.0e-9);
+        assert!((rect.w() - SIZE).abs() < 1.0e-9 && (rect.h() - SIZE).abs() < 1.0e-9);
+
+        let (x, y) = ndc_at(rect, [rect.right(), rect.y()]).unwrap();
+        assert!((x - 1.0).abs() < 1.0e-6 && y.abs() < 1.0e-6);
+        assert_eq!(ndc_at(rect, [0.0, 0.0]), None);
+    }
+}
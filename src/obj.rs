@@ -0,0 +1,137 @@
+use nalgebra::base::Vector3;
+use Vertex;
+
+/// Parses the geometry of a Wavefront OBJ file into vertices with the given color.
+/// Polygons are triangulated as fans, and each corner gets its own vertex. Faces without
+/// normals are given flat normals computed from their positions.
+pub fn parse_obj(src: &str, color: [f32; 4]) -> Result<(Vec<Vertex>, Vec<u16>), String> {
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut normals: Vec<[f32; 3]> = Vec::new();
+    let mut vertices = Vec::new();
+
+    for (i, line) in src.lines().enumerate() {
+        let line_number = i + 1;
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("v") => positions.push(parse_vector(tokens, line_number)?),
+            Some("vn") => normals.push(parse_vector(tokens, line_number)?),
+            Some("f") => {
+                let mut corners = Vec::new();
+                for token in tokens {
+                    corners.push(parse_corner(token, &positions, &normals, line_number)?);
+                }
+                if corners.len() < 3 {
+                    return Err(format!("line {}: a face needs at least 3 vertices", line_number));
+                }
+
+                for j in 1..corners.len() - 1 {
+                    let mut triangle = [corners[0], corners[j], corners[j + 1]];
+                    if triangle.iter().any(|&(_, normal)| normal.is_none()) {
+                        let normal = face_normal(triangle[0].0, triangle[1].0, triangle[2].0);
+                        for corner in &mut triangle {
+                            corner.1 = Some(normal);
+                        }
+                    }
+
+                    for &(position, normal) in &triangle {
+                        vertices.push(Vertex { position, color, normal: normal.unwrap(), tex_coords: [0.0, 0.0] });
+                    }
+                }
+            }
+            // Texture coordinates, groups, materials and comments aren't needed.
+            _ => (),
+        }
+    }
+
+    if vertices.is_empty() {
+        return Err("no faces found".to_string());
+    }
+    if vertices.len() > u16::max_value() as usize + 1 {
+        return Err(format!("too many vertices ({}), the limit is {}", vertices.len(), u16::max_value() as usize + 1));
+    }
+
+    let indices = (0..vertices.len() as u32).map(|i| i as u16).collect();
+    Ok((vertices, indices))
+}
+
+fn parse_vector<'a, I: Iterator<Item = &'a str>>(tokens: I, line_number: usize) -> Result<[f32; 3], String> {
+    let values = tokens.take(3)
+        .map(|token| token.parse::<f32>().map_err(|_| format!("line {}: invalid number \"{}\"", line_number, token)))
+        .collect::<Result<Vec<f32>, String>>()?;
+    if values.len() != 3 {
+        return Err(format!("line {}: expected 3 coordinates", line_number));
+    }
+    Ok([values[0], values[1], values[2]])
+}
+
+// Parses a face corner of the form `v`, `v/vt`, `v//vn` or `v/vt/vn`.
+fn parse_corner(token: &str, positions: &[[f32; 3]], normals: &[[f32; 3]], line_number: usize)
+    -> Result<([f32; 3], Option<[f32; 3]>), String>
+{
+    let mut parts = token.split('/');
+    let position = match parts.next() {
+        Some(index) => positions[resolve_index(index, positions.len(), line_number)?],
+        None => return Err(format!("line {}: missing vertex index", line_number)),
+    };
+    let normal = match parts.nth(1) {
+        Some(index) if !index.is_empty() => Some(normals[resolve_index(index, normals.len(), line_number)?]),
+        _ => None,
+    };
+    Ok((position, normal))
+}
+
+// OBJ indices are 1-based, and negative indices count back from the most recent element.
+fn resolve_index(index: &str, len: usize, line_number: usize) -> Result<usize, String> {
+    let invalid = || format!("line {}: invalid index \"{}\"", line_number, index);
+    let index: i64 = index.parse().map_err(|_| invalid())?;
+    let resolved = if index < 0 { len as i64 + index } else { index - 1 };
+    if resolved < 0 || resolved >= len as i64 {
+        return Err(invalid());
+    }
+    Ok(resolved as usize)
+}
+
+fn face_normal(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> [f32; 3] {
+    let a = Vector3::from(a);
+    let normal = (Vector3::from(b) - a).cross(&(Vector3::from(c) - a));
+    let len = normal.norm();
+    if len > 0.0 {
+        (normal / len).into()
+    } else {
+        [0.0, 0.0, 1.0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+    #[test]
+    fn test_parse_obj() {
+        // A quad with normals, using every corner format.
+        let src = "# comment\nv 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nvt 0 0\nvn 0 0 1\nf 1//1 2/1/1 3//1 -1//-1\n";
+        let (vertices, indices) = parse_obj(src, WHITE).unwrap();
+        assert_eq!(vertices.len(), 6);
+        assert_eq!(indices, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(vertices[0].position, [0.0, 0.0, 0.0]);
+        assert_eq!(vertices[5].position, [0.0, 1.0, 0.0]);
+        assert!(vertices.iter().all(|v| v.normal == [0.0, 0.0, 1.0] && v.color == WHITE));
+    }
+
+    #[test]
+    fn test_parse_obj_computes_normals() {
+        let (vertices, _) = parse_obj("v 0 0 0\nv 0 0 -2\nv 0 3 0\nf 1 2 3\n", WHITE).unwrap();
+        assert!(vertices.iter().all(|v| v.normal == [1.0, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn test_parse_obj_errors() {
+        assert!(parse_obj("", WHITE).is_err());
+        assert!(parse_obj("v 0 0\nf 1 1 1\n", WHITE).is_err());
+        assert!(parse_obj("v 0 0 0\nf 1 1\n", WHITE).is_err());
+        assert!(parse_obj("v 0 0 0\nf 1 2 1\n", WHITE).is_err());
+        assert!(parse_obj("v 0 0 0\nf 1//1 1//1 1//1\n", WHITE).is_err());
+    }
+}
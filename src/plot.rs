@@ -0,0 +1,58 @@
+use std::f32::consts::PI;
+use conrod::{Point, Rect};
+
+/// The animated rotation's angle from where the animation started, recorded each frame as it
+/// plays so the graph shows where it speeds up or slows down.
+#[derive(Clone, Debug, Default)]
+pub struct AngleHistory {
+    /// `(time, angle)` pairs, in seconds since the start and radians from 0 to π.
+    pub samples: Vec<(f32, f32)>,
+}
+
+impl AngleHistory {
+    pub fn new() -> AngleHistory {
+        AngleHistory::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    pub fn add(&mut self, time: f32, angle: f32) {
+        self.samples.push((time, angle));
+    }
+
+    /// Maps the samples into `rect`, with time running from 0 to `duration` across it and the
+    /// angle from 0 to π up it.
+    pub fn points_in(&self, rect: Rect, duration: f32) -> Vec<Point> {
+        let duration = duration.max(1.0e-6);
+        self.samples.iter()
+            .map(|&(time, angle)| [
+                rect.left() + f64::from((time / duration).min(1.0)) * rect.w(),
+                rect.bottom() + f64::from(angle / PI) * rect.h(),
+            ])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_points_in() {
+        let mut history = AngleHistory::new();
+        history.add(0.0, 0.0);
+        history.add(1.0, PI / 2.0);
+        history.add(2.0, PI);
+        let rect = Rect::from_corners([10.0, 20.0], [110.0, 70.0]);
+        let points = history.points_in(rect, 2.0);
+        assert_eq!(points.len(), 3);
+        assert!((points[0][0] - 10.0).abs() < 1.0e-4 && (points[0][1] - 20.0).abs() < 1.0e-4);
+        assert!((points[1][0] - 60.0).abs() < 1.0e-4 && (points[1][1] - 45.0).abs() < 1.0e-4);
+        assert!((points[2][0] - 110.0).abs() < 1.0e-4 && (points[2][1] - 70.0).abs() < 1.0e-4);
+
+        history.clear();
+        assert!(history.points_in(rect, 2.0).is_empty());
+    }
+}
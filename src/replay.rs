@@ -0,0 +1,295 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+use conrod::event::Input;
+use conrod::input::{Button, Motion};
+use conrod::backend::winit::{map_key, map_mouse};
+use glium::glutin::{ElementState, ModifiersState, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent};
+use serde_json;
+use import;
+
+/// The keys that are recorded: the ones the demo's shortcuts use, and the ones for typing into
+/// and moving around its text boxes. Presses of any other key are left out of recordings.
+pub const RECORDED_KEYS: [VirtualKeyCode; 66] = [
+    VirtualKeyCode::A, VirtualKeyCode::B, VirtualKeyCode::C, VirtualKeyCode::D, VirtualKeyCode::E,
+    VirtualKeyCode::F, VirtualKeyCode::G, VirtualKeyCode::H, VirtualKeyCode::I, VirtualKeyCode::J,
+    VirtualKeyCode::K, VirtualKeyCode::L, VirtualKeyCode::M, VirtualKeyCode::N, VirtualKeyCode::O,
+    VirtualKeyCode::P, VirtualKeyCode::Q, VirtualKeyCode::R, VirtualKeyCode::S, VirtualKeyCode::T,
+    VirtualKeyCode::U, VirtualKeyCode::V, VirtualKeyCode::W, VirtualKeyCode::X, VirtualKeyCode::Y,
+    VirtualKeyCode::Z,
+    VirtualKeyCode::Key0, VirtualKeyCode::Key1, VirtualKeyCode::Key2, VirtualKeyCode::Key3,
+    VirtualKeyCode::Key4, VirtualKeyCode::Key5, VirtualKeyCode::Key6, VirtualKeyCode::Key7,
+    VirtualKeyCode::Key8, VirtualKeyCode::Key9,
+    VirtualKeyCode::F1, VirtualKeyCode::F2, VirtualKeyCode::F3, VirtualKeyCode::F4,
+    VirtualKeyCode::F5, VirtualKeyCode::F6, VirtualKeyCode::F7, VirtualKeyCode::F8,
+    VirtualKeyCode::F9, VirtualKeyCode::F10, VirtualKeyCode::F11, VirtualKeyCode::F12,
+    VirtualKeyCode::Space, VirtualKeyCode::Return, VirtualKeyCode::Back, VirtualKeyCode::Delete,
+    VirtualKeyCode::Tab, VirtualKeyCode::Escape, VirtualKeyCode::Left, VirtualKeyCode::Right,
+    VirtualKeyCode::Up, VirtualKeyCode::Down, VirtualKeyCode::Home, VirtualKeyCode::End,
+    VirtualKeyCode::Minus, VirtualKeyCode::Period, VirtualKeyCode::Comma,
+    VirtualKeyCode::LShift, VirtualKeyCode::RShift, VirtualKeyCode::LControl,
+];
+
+/// Returns the recorded key with the debug name `name`, like "A" or "F12".
+pub fn key_from_name(name: &str) -> Option<VirtualKeyCode> {
+    RECORDED_KEYS.iter().cloned().find(|key| format!("{:?}", key) == name)
+}
+
+/// Keys are written by their debug names, which are the names on the keys.
+mod key_name {
+    use glium::glutin::VirtualKeyCode;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use serde::de::Error;
+
+    // Serde passes the field by reference.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn serialize<S: Serializer>(key: &VirtualKeyCode, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{:?}", key))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<VirtualKeyCode, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        super::key_from_name(&name).ok_or_else(|| D::Error::custom(format!("unknown key \"{}\"", name)))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "ModifiersState")]
+struct ModifiersStateDef {
+    shift: bool,
+    ctrl: bool,
+    alt: bool,
+    logo: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "MouseButton", rename_all = "lowercase")]
+enum MouseButtonDef {
+    Left,
+    Right,
+    Middle,
+    Other(u8),
+}
+
+/// The part of a window event that the demo and its UI react to, in a form that can be written
+/// to a recording. Positions and pixel scroll amounts are in logical pixels, so a recording
+/// replays the same on a screen with a different DPI.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InputEvent {
+    Key {
+        #[serde(with = "key_name")]
+        key: VirtualKeyCode,
+        pressed: bool,
+        #[serde(with = "ModifiersStateDef")]
+        modifiers: ModifiersState,
+    },
+    /// A character typed into the window.
+    Character { character: char },
+    CursorMoved { x: f64, y: f64 },
+    MouseButton {
+        #[serde(with = "MouseButtonDef")]
+        button: MouseButton,
+        pressed: bool,
+    },
+    /// A scroll of the mouse wheel, in lines or, for touchpads, in pixels.
+    Scroll { x: f32, y: f32, pixels: bool },
+    /// The window's new size, in physical pixels.
+    Resized { width: u32, height: u32 },
+}
+
+impl InputEvent {
+    /// Converts `event`, with positions in physical pixels at `hidpi_factor`, or returns `None`
+    /// for events that aren't recorded, like focus changes or presses of unrecorded keys.
+    pub fn from_window_event(event: &WindowEvent, hidpi_factor: f64) -> Option<InputEvent> {
+        match *event {
+            WindowEvent::KeyboardInput { input, .. } => {
+                let key = input.virtual_keycode.filter(|key| RECORDED_KEYS.contains(key))?;
+                Some(InputEvent::Key {
+                    key,
+                    pressed: input.state == ElementState::Pressed,
+                    modifiers: input.modifiers,
+                })
+            }
+            WindowEvent::ReceivedCharacter(character) => Some(InputEvent::Character { character }),
+            WindowEvent::CursorMoved { position: (x, y), .. } =>
+                Some(InputEvent::CursorMoved { x: x / hidpi_factor, y: y / hidpi_factor }),
+            WindowEvent::MouseInput { state, button, .. } =>
+                Some(InputEvent::MouseButton { button, pressed: state == ElementState::Pressed }),
+            WindowEvent::MouseWheel { delta, .. } => Some(match delta {
+                MouseScrollDelta::LineDelta(x, y) => InputEvent::Scroll { x, y, pixels: false },
+                MouseScrollDelta::PixelDelta(x, y) => {
+                    let hidpi_factor = hidpi_factor as f32;
+                    InputEvent::Scroll { x: x / hidpi_factor, y: y / hidpi_factor, pixels: true }
+                }
+            }),
+            WindowEvent::Resized(width, height) => Some(InputEvent::Resized { width, height }),
+            _ => None,
+        }
+    }
+
+    /// Converts the event into the UI's input for a `win_w` by `win_h` window, the way conrod's
+    /// winit backend does with window events. Resizes are left to the window's own events.
+    pub fn to_conrod_input(&self, win_w: f64, win_h: f64) -> Option<Input> {
+        let button = |pressed, button| if pressed { Input::Press(button) } else { Input::Release(button) };
+        match *self {
+            InputEvent::Key { key, pressed, .. } => Some(button(pressed, Button::Keyboard(map_key(key)))),
+            // Control characters are typed with keys the text boxes handle themselves.
+            InputEvent::Character { character } => Some(Input::Text(match character {
+                '\u{7f}' | '\u{1b}' | '\u{8}' | '\r' | '\n' | '\t' => String::new(),
+                _ => character.to_string(),
+            })),
+            InputEvent::CursorMoved { x, y } =>
+                Some(Input::Motion(Motion::MouseCursor { x: x - win_w / 2.0, y: win_h / 2.0 - y })),
+            InputEvent::MouseButton { button: mouse_button, pressed } =>
+                Some(button(pressed, Button::Mouse(map_mouse(mouse_button)))),
+            InputEvent::Scroll { x, y, pixels } => {
+                const POINTS_PER_LINE: f64 = 10.0;
+                let scale = if pixels { 1.0 } else { POINTS_PER_LINE };
+                Some(Input::Motion(Motion::Scroll { x: f64::from(x) * scale, y: -f64::from(y) * scale }))
+            }
+            InputEvent::Resized { .. } => None,
+        }
+    }
+}
+
+/// An event and when it happened, in seconds since the start of the recording.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TimedEvent {
+    pub time: f64,
+    #[serde(flatten)]
+    pub event: InputEvent,
+}
+
+/// Writes the events to a file as they happen, one JSON object per line.
+pub struct Recorder {
+    writer: BufWriter<File>,
+    start: Instant,
+}
+
+impl Recorder {
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Recorder> {
+        let writer = BufWriter::new(File::create(path)?);
+        Ok(Recorder { writer, start: Instant::now() })
+    }
+
+    pub fn record(&mut self, event: InputEvent) -> io::Result<()> {
+        let elapsed = self.start.elapsed();
+        let time = elapsed.as_secs() as f64 + f64::from(elapsed.subsec_nanos()) / 1.0e9;
+        let line = serde_json::to_string(&TimedEvent { time, event })?;
+        writeln!(self.writer, "{}", line)
+    }
+}
+
+/// Parses a recording, checking that its events are in order. Errors report the 1-based line
+/// number.
+pub fn parse_recording(recording: &str) -> Result<Vec<TimedEvent>, String> {
+    let mut events: Vec<TimedEvent> = Vec::new();
+    for (i, line) in recording.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let event: TimedEvent = serde_json::from_str(line).map_err(|e| format!("line {}: {}", i + 1, e))?;
+        if events.last().map_or(false, |last| event.time < last.time) {
+            return Err(format!("line {}: the event is earlier than the one before it", i + 1));
+        }
+        events.push(event);
+    }
+    Ok(events)
+}
+
+/// Plays back a recording, handing out its events once their time has come.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    events: VecDeque<TimedEvent>,
+    start: Instant,
+}
+
+impl Replay {
+    pub fn new(events: Vec<TimedEvent>) -> Replay {
+        Replay { events: events.into(), start: Instant::now() }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Replay, String> {
+        let path = path.as_ref();
+        let recording = import::read_file(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let events = parse_recording(&recording).map_err(|e| format!("{}: {}", path.display(), e))?;
+        debug!("Replaying {} events from {}", events.len(), path.display());
+        Ok(Replay::new(events))
+    }
+
+    /// Removes and returns the events due `elapsed` seconds into the replay.
+    pub fn due_at(&mut self, elapsed: f64) -> Vec<InputEvent> {
+        let mut due = Vec::new();
+        while self.events.front().map_or(false, |next| next.time <= elapsed) {
+            due.push(self.events.pop_front().unwrap().event);
+        }
+        due
+    }
+
+    /// Removes and returns the events due by now.
+    pub fn due(&mut self) -> Vec<InputEvent> {
+        let elapsed = self.start.elapsed();
+        self.due_at(elapsed.as_secs() as f64 + f64::from(elapsed.subsec_nanos()) / 1.0e9)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_round_trip() {
+        let ctrl = ModifiersState { ctrl: true, ..ModifiersState::default() };
+        let events = vec![
+            TimedEvent { time: 0.0, event: InputEvent::Key { key: VirtualKeyCode::Key2, pressed: true, modifiers: ctrl } },
+            TimedEvent { time: 0.5, event: InputEvent::Character { character: 'x' } },
+            TimedEvent { time: 0.5, event: InputEvent::CursorMoved { x: 120.0, y: 40.5 } },
+            TimedEvent { time: 1.0, event: InputEvent::MouseButton { button: MouseButton::Middle, pressed: false } },
+            TimedEvent { time: 1.5, event: InputEvent::Scroll { x: 0.0, y: -2.0, pixels: false } },
+            TimedEvent { time: 2.0, event: InputEvent::Resized { width: 800, height: 600 } },
+        ];
+        let recording: Vec<String> = events.iter().map(|event| serde_json::to_string(event).unwrap()).collect();
+        assert!(recording[0].contains("\"type\":\"key\"") && recording[0].contains("\"key\":\"Key2\""));
+        assert_eq!(parse_recording(&recording.join("\n")), Ok(events));
+
+        assert_eq!(key_from_name("F12"), Some(VirtualKeyCode::F12));
+        assert_eq!(key_from_name("Numpad7"), None);
+        assert!(parse_recording(r#"{"time":0,"type":"key","key":"Numpad7","pressed":true,"modifiers":{"shift":false,"ctrl":false,"alt":false,"logo":false}}"#).is_err());
+        assert!(parse_recording("{\"time\":1,\"type\":\"character\",\"character\":\"a\"}\n{\"time\":0,\"type\":\"character\",\"character\":\"b\"}").is_err());
+        assert_eq!(parse_recording("\n"), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn test_to_conrod_input() {
+        match (InputEvent::CursorMoved { x: 500.0, y: 100.0 }).to_conrod_input(800.0, 600.0) {
+            Some(Input::Motion(Motion::MouseCursor { x, y })) => assert!((x - 100.0).abs() < 1.0e-9 && (y - 200.0).abs() < 1.0e-9),
+            input => panic!("unexpected input {:?}", input),
+        }
+        match (InputEvent::Scroll { x: 0.0, y: 1.0, pixels: false }).to_conrod_input(800.0, 600.0) {
+            Some(Input::Motion(Motion::Scroll { y, .. })) => assert!((y + 10.0).abs() < 1.0e-9),
+            input => panic!("unexpected input {:?}", input),
+        }
+        assert_eq!(InputEvent::Character { character: '\r' }.to_conrod_input(800.0, 600.0), Some(Input::Text(String::new())));
+        assert_eq!(InputEvent::Resized { width: 1, height: 1 }.to_conrod_input(800.0, 600.0), None);
+    }
+
+    #[test]
+    fn test_replay_due_at() {
+        let event = |time, character| TimedEvent { time, event: InputEvent::Character { character } };
+        let mut replay = Replay::new(vec![event(0.0, 'a'), event(0.5, 'b'), event(0.5, 'c'), event(2.0, 'd')]);
+        assert_eq!(replay.due_at(0.1), vec![InputEvent::Character { character: 'a' }]);
+        assert_eq!(replay.due_at(0.2), Vec::new());
+        assert_eq!(replay.due_at(1.0).len(), 2);
+        assert!(!replay.is_finished());
+        assert_eq!(replay.due_at(5.0), vec![InputEvent::Character { character: 'd' }]);
+        assert!(replay.is_finished());
+    }
+}
@@ -0,0 +1,378 @@
+use std::fmt::Debug;
+use nalgebra::base::Vector3;
+use nalgebra::geometry::UnitQuaternion;
+use quaternion::Quaternion;
+use animation;
+
+/// What the rotation lists store, and the animation composes and interpolates. With the
+/// `unit-quaternion` feature it's nalgebra's `UnitQuaternion`, and the panel and exports convert to
+/// and from `Quaternion` as they read and edit the lists.
+#[cfg(not(feature = "unit-quaternion"))]
+pub type StoredRotation = Quaternion;
+#[cfg(feature = "unit-quaternion")]
+pub type StoredRotation = UnitQuaternion<f32>;
+
+/// The operations the animation needs from a stored rotation. Both kinds are unit quaternions,
+/// so `-q` is the same rotation as `q`, and the interpolations go the short way round between
+/// them like `Quaternion`'s.
+pub trait Rotation: Copy + Debug + From<Quaternion> {
+    fn identity() -> Self;
+    fn to_quaternion(self) -> Quaternion;
+    /// The rotation of `self` followed by `other` in the model's frame, `self * other`.
+    fn then(self, other: Self) -> Self;
+    fn dot(self, other: Self) -> f32;
+    fn negated(self) -> Self;
+    fn inverse(self) -> Self;
+    fn slerp(self, to: Self, t: f32) -> Self;
+    fn nlerp(self, to: Self, t: f32) -> Self;
+    fn rotate_vector(self, v: Vector3<f32>) -> Vector3<f32>;
+
+    /// The product of `rotations`, the first applied first, or the identity for none.
+    fn compose(rotations: &[Self]) -> Self {
+        rotations.iter().fold(Self::identity(), |product, &q| product.then(q))
+    }
+
+    /// Whether this is the identity, with either sign.
+    fn is_identity(self) -> bool {
+        let q = self.to_quaternion();
+        q == Quaternion::identity() || -q == Quaternion::identity()
+    }
+
+    /// The unit axis and angle, as `Quaternion::to_axis_angle` gives them.
+    fn to_axis_angle(self) -> (Vector3<f32>, f32) {
+        Quaternion::to_axis_angle(&self.to_quaternion())
+    }
+}
+
+impl Rotation for Quaternion {
+    fn identity() -> Quaternion {
+        Quaternion::identity()
+    }
+
+    fn to_quaternion(self) -> Quaternion {
+        self
+    }
+
+    fn then(self, other: Quaternion) -> Quaternion {
+        self * other
+    }
+
+    fn dot(self, other: Quaternion) -> f32 {
+        Quaternion::dot(&self, other)
+    }
+
+    fn negated(self) -> Quaternion {
+        -self
+    }
+
+    fn inverse(self) -> Quaternion {
+        self.conjugate()
+    }
+
+    fn slerp(self, to: Quaternion, t: f32) -> Quaternion {
+        Quaternion::slerp(&self, to, t)
+    }
+
+    fn nlerp(self, to: Quaternion, t: f32) -> Quaternion {
+        Quaternion::nlerp(&self, to, t)
+    }
+
+    fn rotate_vector(self, v: Vector3<f32>) -> Vector3<f32> {
+        Quaternion::rotate_vector(&self, v)
+    }
+
+    fn compose(rotations: &[Quaternion]) -> Quaternion {
+        Quaternion::compose(rotations)
+    }
+}
+
+/// nalgebra's products and slerp don't renormalize, so the results are renormalized here where
+/// `Quaternion`'s would be, to stay as close to unit length.
+impl Rotation for UnitQuaternion<f32> {
+    fn identity() -> UnitQuaternion<f32> {
+        UnitQuaternion::identity()
+    }
+
+    fn to_quaternion(self) -> Quaternion {
+        self.into()
+    }
+
+    fn then(self, other: UnitQuaternion<f32>) -> UnitQuaternion<f32> {
+        UnitQuaternion::new_normalize((self * other).unwrap())
+    }
+
+    fn dot(self, other: UnitQuaternion<f32>) -> f32 {
+        self.quaternion().coords.dot(&other.quaternion().coords)
+    }
+
+    fn negated(self) -> UnitQuaternion<f32> {
+        UnitQuaternion::new_unchecked(-self.unwrap())
+    }
+
+    fn inverse(self) -> UnitQuaternion<f32> {
+        UnitQuaternion::conjugate(&self)
+    }
+
+    /// nalgebra interpolates the long way round between quaternions on opposite sides of the
+    /// 3-sphere, and its slerp has no answer for opposite ones, so `to` is flipped to the same side
+    /// first. Keyframes too close together for the slerp to find the arc between are nlerped.
+    fn slerp(self, to: UnitQuaternion<f32>, t: f32) -> UnitQuaternion<f32> {
+        const MIN_SIN_HALF_ANGLE: f32 = 1.0e-6;
+        let to = if Rotation::dot(self, to) < 0.0 { to.negated() } else { to };
+        let q = self.try_slerp(&to, t, MIN_SIN_HALF_ANGLE).unwrap_or_else(|| UnitQuaternion::nlerp(&self, &to, t));
+        UnitQuaternion::new_normalize(q.unwrap())
+    }
+
+    fn nlerp(self, to: UnitQuaternion<f32>, t: f32) -> UnitQuaternion<f32> {
+        let to = if Rotation::dot(self, to) < 0.0 { to.negated() } else { to };
+        UnitQuaternion::nlerp(&self, &to, t)
+    }
+
+    fn rotate_vector(self, v: Vector3<f32>) -> Vector3<f32> {
+        self * v
+    }
+}
+
+/// One rotation in a `RotationList`, with the panel's state for it.
+#[derive(Clone, Copy, Debug)]
+pub struct Entry {
+    pub rotation: StoredRotation,
+    /// Keeps the rotation out of the edits until unpinned. It's still composed like the others.
+    pub pinned: bool,
+    /// Picks the rotation's color, which it keeps as others are inserted before it.
+    pub color_id: usize,
+}
+
+/// A list of rotations, applied first to last, and which of them the sliders edit. It always
+/// holds at least one rotation, and the selected index is always one of them.
+#[derive(Clone, Debug)]
+pub struct RotationList {
+    entries: Vec<Entry>,
+    selected: usize,
+}
+
+impl RotationList {
+    /// A list of `rotations`, none pinned, with the last selected. No rotations make a list of just
+    /// the identity.
+    pub fn new(rotations: Vec<StoredRotation>) -> RotationList {
+        let rotations = if rotations.is_empty() { vec![StoredRotation::identity()] } else { rotations };
+        let entries: Vec<Entry> = rotations.into_iter()
+            .enumerate()
+            .map(|(color_id, rotation)| Entry { rotation, pinned: false, color_id })
+            .collect();
+        RotationList { selected: entries.len() - 1, entries }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// The rotations, first applied first, for the animation and the exports.
+    pub fn rotations(&self) -> Vec<StoredRotation> {
+        self.entries.iter().map(|entry| entry.rotation).collect()
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn select(&mut self, index: usize) {
+        assert!(index < self.entries.len(), "no rotation {} to select", index);
+        self.selected = index;
+    }
+
+    pub fn selected_entry(&self) -> &Entry {
+        &self.entries[self.selected]
+    }
+
+    /// The selected rotation, as the sliders show it.
+    pub fn selected_rotation(&self) -> Quaternion {
+        self.selected_entry().rotation.to_quaternion()
+    }
+
+    pub fn any_pinned(&self) -> bool {
+        self.entries.iter().any(|entry| entry.pinned)
+    }
+
+    /// Replaces the rotation at `index`, with its sign matched to the rotation before it.
+    #[allow(clippy::identity_conversion)]
+    pub fn set(&mut self, index: usize, q: Quaternion) {
+        let q = StoredRotation::from(q);
+        self.entries[index].rotation = if index > 0 { animation::align_sign(q, self.entries[index - 1].rotation) } else { q };
+    }
+
+    pub fn set_selected(&mut self, q: Quaternion) {
+        let selected = self.selected;
+        self.set(selected, q);
+    }
+
+    /// Sets just the rotation at `index` to the identity, keeping its place in the list.
+    pub fn reset(&mut self, index: usize) {
+        self.set(index, Quaternion::identity());
+    }
+
+    pub fn toggle_pin(&mut self, index: usize) {
+        self.entries[index].pinned = !self.entries[index].pinned;
+    }
+
+    /// Adds an identity at the end, with the next color, and selects it.
+    pub fn push(&mut self) {
+        let color_id = self.next_color_id();
+        self.entries.push(Entry { rotation: StoredRotation::identity(), pinned: false, color_id });
+        let last = self.entries.len() - 1;
+        self.selected = last;
+        self.reset(last);
+    }
+
+    /// Adds an identity just before the selected rotation, which is applied before it, and selects
+    /// the new one. The rotations after it keep their pins and colors.
+    pub fn insert_before_selected(&mut self) {
+        let index = self.selected;
+        let color_id = self.next_color_id();
+        self.entries.insert(index, Entry { rotation: StoredRotation::identity(), pinned: false, color_id });
+        self.reset(index);
+        // Only the sign of the rotation pushed along changes, which a pinned one keeps.
+        let next = self.entries[index + 1];
+        if !next.pinned {
+            self.set(index + 1, next.rotation.to_quaternion());
+        }
+    }
+
+    /// Replaces the whole list, as `new` makes it. The pinned rotations would go too, so the list
+    /// is left alone while any are pinned.
+    pub fn replace_all(&mut self, rotations: Vec<StoredRotation>) -> Result<(), String> {
+        if self.any_pinned() {
+            return Err("unpin its rotations first".to_string());
+        }
+        *self = RotationList::new(rotations);
+        Ok(())
+    }
+
+    /// Replaces the list with the one rotation that does all of it, unless any are pinned.
+    pub fn collapse(&mut self) -> Result<(), String> {
+        let composed = self.compose();
+        self.replace_all(vec![composed])
+    }
+
+    pub fn compose(&self) -> StoredRotation {
+        StoredRotation::compose(&self.rotations())
+    }
+
+    /// Flips the signs of the unpinned rotations to keep the list on one side of the 3-sphere, as
+    /// `animation::align_signs` does.
+    pub fn align_signs(&mut self) {
+        let mut rotations = self.rotations();
+        let pinned: Vec<bool> = self.entries.iter().map(|entry| entry.pinned).collect();
+        animation::align_signs(&mut rotations, &pinned);
+        for (entry, rotation) in self.entries.iter_mut().zip(rotations) {
+            entry.rotation = rotation;
+        }
+    }
+
+    /// The color id after the latest in the list.
+    fn next_color_id(&self) -> usize {
+        self.entries.iter().map(|entry| entry.color_id).max().map_or(0, |id| id + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::std::f32::consts::PI;
+
+    fn assert_same_rotation<R: Rotation>(a: R, b: Quaternion) {
+        let a = a.to_quaternion();
+        assert!(a.angle_between(b) < 1.0e-3, "Not the same rotation: {:?} {:?}", a, b);
+        assert!((a.len() - 1.0).abs() < 1.0e-6, "Not unit length: {:?}", a);
+    }
+
+    fn check_rotation<R: Rotation>() {
+        let p = Quaternion::from_euler_angles(0.3, -1.2, 2.0);
+        let q = Quaternion::from_axis_angle(0.0, 1.0, 0.0, PI / 2.0);
+        let (rp, rq) = (R::from(p), R::from(q));
+
+        assert_same_rotation(rp.then(rq), p * q);
+        assert_same_rotation(R::compose(&[rp, rq, rp]), p * q * p);
+        assert_same_rotation(rp.then(rp.inverse()), Quaternion::identity());
+        assert!((rp.dot(rq) - p.dot(q)).abs() < 1.0e-6);
+        assert!((rp.rotate_vector(Vector3::x()) - p.rotate_vector(Vector3::x())).norm() < 1.0e-5);
+
+        // Both ways of blending go the short way, whichever sign the keyframe has.
+        assert_same_rotation(rp.slerp(rq.negated(), 0.3), p.slerp(q, 0.3));
+        assert_same_rotation(rp.nlerp(rq.negated(), 0.3), p.nlerp(q, 0.3));
+        assert_same_rotation(rq.slerp(rq, 0.5), q);
+
+        assert!(R::identity().negated().is_identity());
+        assert!(!rq.is_identity());
+    }
+
+    #[test]
+    fn test_rotations_agree() {
+        check_rotation::<Quaternion>();
+        check_rotation::<UnitQuaternion<f32>>();
+    }
+
+    #[test]
+    #[allow(clippy::identity_conversion)]
+    fn test_rotation_list_insert() {
+        let p = Quaternion::from_euler_angles(0.3, -1.2, 2.0);
+        let q = Quaternion::from_axis_angle(0.0, 1.0, 0.0, PI / 2.0);
+        let mut list = RotationList::new(vec![StoredRotation::from(p), StoredRotation::from(q)]);
+        list.push();
+        assert_eq!(list.selected(), 2);
+        list.toggle_pin(1);
+
+        list.select(1);
+        list.insert_before_selected();
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.selected(), 1);
+        {
+            let entries = list.entries();
+            assert!(entries[1].rotation.is_identity() && !entries[1].pinned);
+            // The rotations after it keep their pins and colors.
+            let ids: Vec<usize> = entries.iter().map(|entry| entry.color_id).collect();
+            assert_eq!(ids, vec![0, 3, 1, 2]);
+            let pins: Vec<bool> = entries.iter().map(|entry| entry.pinned).collect();
+            assert_eq!(pins, vec![false, false, true, false]);
+            assert_same_rotation(entries[2].rotation, q);
+        }
+        assert_same_rotation(list.compose(), p * q);
+
+        // The new one is edited, leaving the one it went before alone.
+        list.set_selected(q);
+        assert_same_rotation(list.entries()[1].rotation, q);
+        assert_same_rotation(list.entries()[2].rotation, q);
+        list.reset(1);
+        assert!(list.selected_entry().rotation.is_identity());
+    }
+
+    #[test]
+    #[allow(clippy::identity_conversion)]
+    fn test_rotation_list_replace() {
+        let q = StoredRotation::from(Quaternion::from_axis_angle(1.0, 0.0, 0.0, 0.7));
+        let mut list = RotationList::new(Vec::new());
+        assert_eq!(list.len(), 1);
+        list.push();
+        list.set_selected(q.to_quaternion());
+        list.toggle_pin(0);
+        // Nothing is replaced while a rotation is pinned.
+        assert!(list.collapse().is_err());
+        assert!(list.replace_all(vec![q]).is_err());
+        assert_eq!(list.len(), 2);
+        assert!(list.entries()[0].pinned);
+        list.toggle_pin(0);
+        list.collapse().unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!((list.selected(), list.entries()[0].color_id), (0, 0));
+        assert!(!list.entries()[0].pinned);
+        assert_same_rotation(list.entries()[0].rotation, q.to_quaternion());
+
+        list.replace_all(vec![q, q, q]).unwrap();
+        assert_eq!(list.selected(), 2);
+        assert!(list.entries().iter().all(|entry| !entry.pinned));
+    }
+}
@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use nalgebra::base::Vector3;
+use Vertex;
+
+/// Generates a UV sphere centered on the origin, with `segments` slices around the Y axis and
+/// `rings` stacked from pole to pole. Normals point straight out from the center, and the texture
+/// coordinates wrap once around the sphere.
+pub fn uv_sphere(radius: f32, segments: u16, rings: u16, color: [f32; 4]) -> (Vec<Vertex>, Vec<u16>) {
+    let mut vertices = Vec::new();
+    for ring in 0..=rings {
+        let polar = PI * f32::from(ring) / f32::from(rings);
+        for segment in 0..=segments {
+            let azimuth = 2.0 * PI * f32::from(segment) / f32::from(segments);
+            let normal = [polar.sin() * azimuth.cos(), polar.cos(), -polar.sin() * azimuth.sin()];
+            let position = [normal[0] * radius, normal[1] * radius, normal[2] * radius];
+            let tex_coords = [f32::from(segment) / f32::from(segments), 1.0 - f32::from(ring) / f32::from(rings)];
+            vertices.push(Vertex { position, color, normal, tex_coords });
+        }
+    }
+
+    // The seam repeats the first column of vertices, so each row has `segments + 1` of them.
+    let row = segments + 1;
+    let mut indices = Vec::new();
+    for ring in 0..rings {
+        for segment in 0..segments {
+            let a = ring * row + segment;
+            let b = a + row;
+            indices.extend_from_slice(&[a, b, a + 1, a + 1, b, b + 1]);
+        }
+    }
+    (vertices, indices)
+}
+
+/// Generates the lines of a UV sphere like `uv_sphere`'s, with a circle around each ring between
+/// the poles and a line from pole to pole along each segment, for drawing as a lines list.
+pub fn wire_sphere(radius: f32, segments: u16, rings: u16, color: [f32; 4]) -> (Vec<Vertex>, Vec<u16>) {
+    let (vertices, _) = uv_sphere(radius, segments, rings, color);
+    let row = segments + 1;
+    let mut indices = Vec::new();
+    for ring in 1..rings {
+        for segment in 0..segments {
+            let a = ring * row + segment;
+            indices.extend_from_slice(&[a, a + 1]);
+        }
+    }
+    for segment in 0..segments {
+        for ring in 0..rings {
+            let a = ring * row + segment;
+            indices.extend_from_slice(&[a, a + row]);
+        }
+    }
+    (vertices, indices)
+}
+
+/// Returns the point on the unit sphere at `azimuth` radians about the Y axis, from +Z towards
+/// +X, and `elevation` radians above the XZ plane.
+pub fn point_on_sphere(azimuth: f32, elevation: f32) -> Vector3<f32> {
+    Vector3::new(elevation.cos() * azimuth.sin(), elevation.sin(), elevation.cos() * azimuth.cos())
+}
+
+/// Generates a cone along the Y axis, with its apex at `height / 2` and its base at `-height / 2`.
+/// The sides are smooth shaded, and the base is a flat cap.
+pub fn cone(radius: f32, height: f32, segments: u16, color: [f32; 4]) -> (Vec<Vertex>, Vec<u16>) {
+    let top = height / 2.0;
+    let side_normal = |angle: f32| -> [f32; 3] {
+        Vector3::new(angle.cos() * height, radius, -angle.sin() * height).normalize().into()
+    };
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    for segment in 0..segments {
+        let start = 2.0 * PI * f32::from(segment) / f32::from(segments);
+        let end = 2.0 * PI * f32::from(segment + 1) / f32::from(segments);
+        let first = vertices.len() as u16;
+        // Each side triangle gets its own apex, with a normal halfway between its edges.
+        vertices.push(Vertex { position: [0.0, top, 0.0], color, normal: side_normal((start + end) / 2.0), tex_coords: [0.0, 0.0] });
+        vertices.push(Vertex { position: [start.cos() * radius, -top, -start.sin() * radius], color, normal: side_normal(start), tex_coords: [0.0, 0.0] });
+        vertices.push(Vertex { position: [end.cos() * radius, -top, -end.sin() * radius], color, normal: side_normal(end), tex_coords: [0.0, 0.0] });
+        indices.extend_from_slice(&[first, first + 1, first + 2]);
+    }
+
+    const DOWN: [f32; 3] = [0.0, -1.0, 0.0];
+    let center = vertices.len() as u16;
+    vertices.push(Vertex { position: [0.0, -top, 0.0], color, normal: DOWN, tex_coords: [0.0, 0.0] });
+    for segment in 0..segments {
+        let angle = 2.0 * PI * f32::from(segment) / f32::from(segments);
+        vertices.push(Vertex { position: [angle.cos() * radius, -top, -angle.sin() * radius], color, normal: DOWN, tex_coords: [0.0, 0.0] });
+    }
+    for segment in 0..segments {
+        let next = (segment + 1) % segments;
+        indices.extend_from_slice(&[center, center + 1 + next, center + 1 + segment]);
+    }
+    (vertices, indices)
+}
+
+/// The normal of each triangle, scaled by its area. Triangles aren't all wound the same way, so
+/// each faces the same side as its vertices' current normals.
+fn face_normals(vertices: &[Vertex], indices: &[u16]) -> Vec<Vector3<f32>> {
+    indices.chunks(3)
+        .filter(|triangle| triangle.len() == 3)
+        .map(|triangle| {
+            let (a, b, c) = (&vertices[triangle[0] as usize], &vertices[triangle[1] as usize], &vertices[triangle[2] as usize]);
+            let a_position = Vector3::from(a.position);
+            let face = (Vector3::from(b.position) - a_position).cross(&(Vector3::from(c.position) - a_position)) / 2.0;
+            let current = Vector3::from(a.normal) + Vector3::from(b.normal) + Vector3::from(c.normal);
+            if face.dot(&current) < 0.0 { -face } else { face }
+        })
+        .collect()
+}
+
+/// Gives every triangle its own copies of its vertices, with normals straight out of it, so each
+/// triangle is evenly lit.
+pub fn flat_shaded(vertices: &[Vertex], indices: &[u16]) -> (Vec<Vertex>, Vec<u16>) {
+    let mut flat_vertices = Vec::with_capacity(indices.len());
+    for (triangle, face) in indices.chunks(3).zip(face_normals(vertices, indices)) {
+        let normal = if face.norm() > 0.0 { face.normalize().into() } else { vertices[triangle[0] as usize].normal };
+        for &i in triangle {
+            flat_vertices.push(Vertex { normal, ..vertices[i as usize] });
+        }
+    }
+    let flat_indices = (0..flat_vertices.len()).map(|i| i as u16).collect();
+    (flat_vertices, flat_indices)
+}
+
+/// Points each vertex's normal along the area weighted average of the triangles around its
+/// position, so the lighting blends across edges. Vertices at the same position, like the copies
+/// of a corner in a flat shaded mesh, end up with the same normal.
+pub fn smooth_shaded(vertices: &[Vertex], indices: &[u16]) -> (Vec<Vertex>, Vec<u16>) {
+    // Positions are matched to a fraction of a unit, so copies that rounding moved apart, like
+    // those along a sphere's seam, are still joined.
+    const PRECISION: f32 = 1.0e5;
+    let key = |vertex: &Vertex| {
+        let p = vertex.position;
+        [(p[0] * PRECISION).round() as i32, (p[1] * PRECISION).round() as i32, (p[2] * PRECISION).round() as i32]
+    };
+    let mut sums: HashMap<[i32; 3], Vector3<f32>> = HashMap::new();
+    for (triangle, face) in indices.chunks(3).zip(face_normals(vertices, indices)) {
+        for &i in triangle {
+            *sums.entry(key(&vertices[i as usize])).or_insert_with(Vector3::zeros) += face;
+        }
+    }
+
+    let smooth_vertices = vertices.iter()
+        .map(|vertex| match sums.get(&key(vertex)) {
+            Some(sum) if sum.norm() > 0.0 => Vertex { normal: sum.normalize().into(), ..*vertex },
+            _ => *vertex,
+        })
+        .collect();
+    (smooth_vertices, indices.to_vec())
+}
+
+/// An axis-aligned box around a mesh's vertices.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Bounds {
+    pub min: Vector3<f32>,
+    pub max: Vector3<f32>,
+}
+
+impl Bounds {
+    /// The bounds of the vertices' positions, or `None` if there aren't any.
+    pub fn of(vertices: &[Vertex]) -> Option<Bounds> {
+        let first = Vector3::from(vertices.first()?.position);
+        let mut bounds = Bounds { min: first, max: first };
+        for vertex in vertices {
+            for axis in 0..3 {
+                bounds.min[axis] = bounds.min[axis].min(vertex.position[axis]);
+                bounds.max[axis] = bounds.max[axis].max(vertex.position[axis]);
+            }
+        }
+        Some(bounds)
+    }
+
+    pub fn center(&self) -> Vector3<f32> {
+        (self.min + self.max) / 2.0
+    }
+
+    /// The radius of the smallest sphere around the center that holds the whole box.
+    pub fn radius(&self) -> f32 {
+        (self.max - self.min).norm() / 2.0
+    }
+}
+
+/// Moves every vertex by `offset`.
+pub fn translated(vertices: &[Vertex], offset: Vector3<f32>) -> Vec<Vertex> {
+    vertices.iter()
+        .map(|vertex| Vertex { position: (Vector3::from(vertex.position) + offset).into(), ..*vertex })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+    #[test]
+    fn test_uv_sphere() {
+        let (vertices, indices) = uv_sphere(2.0, 8, 4, WHITE);
+        assert_eq!(vertices.len(), 9 * 5);
+        assert_eq!(indices.len(), 8 * 4 * 6);
+        assert!(indices.iter().all(|&i| (i as usize) < vertices.len()));
+        for v in &vertices {
+            let (position, normal) = (Vector3::from(v.position), Vector3::from(v.normal));
+            assert!((normal.norm() - 1.0).abs() < 1.0e-5);
+            assert!((position - normal * 2.0).norm() < 1.0e-5);
+        }
+    }
+
+    #[test]
+    fn test_wire_sphere() {
+        let (vertices, indices) = wire_sphere(1.0, 8, 4, WHITE);
+        assert_eq!(vertices.len(), 9 * 5);
+        assert_eq!(indices.len(), (3 * 8 + 8 * 4) * 2);
+        assert!(indices.iter().all(|&i| (i as usize) < vertices.len()));
+        // Every line joins neighboring points on the sphere.
+        for line in indices.chunks(2) {
+            let length = (Vector3::from(vertices[line[0] as usize].position) - Vector3::from(vertices[line[1] as usize].position)).norm();
+            assert!(length > 0.0 && length < 1.0, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn test_point_on_sphere() {
+        assert!((point_on_sphere(0.0, 0.0) - Vector3::z()).norm() < 1.0e-6);
+        assert!((point_on_sphere(PI / 2.0, 0.0) - Vector3::x()).norm() < 1.0e-6);
+        assert!((point_on_sphere(1.0, PI / 2.0) - Vector3::y()).norm() < 1.0e-6);
+        assert!((point_on_sphere(2.0, -0.5).norm() - 1.0).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn test_cone() {
+        let (vertices, indices) = cone(1.0, 2.0, 6, WHITE);
+        assert_eq!(vertices.len(), 6 * 3 + 7);
+        assert_eq!(indices.len(), 6 * 3 * 2);
+        assert!(indices.iter().all(|&i| (i as usize) < vertices.len()));
+
+        // Side normals point outwards and upwards, and the triangles wind counter-clockwise
+        // when seen from outside.
+        for triangle in indices.chunks(3) {
+            let [a, b, c] = [triangle[0], triangle[1], triangle[2]];
+            let a_position = Vector3::from(vertices[a as usize].position);
+            let face = (Vector3::from(vertices[b as usize].position) - a_position)
+                .cross(&(Vector3::from(vertices[c as usize].position) - a_position));
+            assert!(face.dot(&Vector3::from(vertices[a as usize].normal)) > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_bounds() {
+        assert_eq!(Bounds::of(&[]), None);
+
+        let (vertices, _) = cone(1.0, 2.0, 4, WHITE);
+        let vertices = translated(&vertices, Vector3::new(5.0, 0.0, -1.0));
+        let bounds = Bounds::of(&vertices).unwrap();
+        assert!((bounds.min - Vector3::new(4.0, -1.0, -2.0)).norm() < 1.0e-5, "{:?}", bounds);
+        assert!((bounds.max - Vector3::new(6.0, 1.0, 0.0)).norm() < 1.0e-5, "{:?}", bounds);
+        assert!((bounds.center() - Vector3::new(5.0, 0.0, -1.0)).norm() < 1.0e-5);
+        assert!((bounds.radius() - 3.0f32.sqrt()).abs() < 1.0e-5);
+
+        let centered = Bounds::of(&translated(&vertices, -bounds.center())).unwrap();
+        assert!(centered.center().norm() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_shading() {
+        let (vertices, indices) = uv_sphere(1.0, 8, 4, WHITE);
+
+        let (flat_vertices, flat_indices) = flat_shaded(&vertices, &indices);
+        assert_eq!(flat_vertices.len(), indices.len());
+        assert_eq!(flat_indices.len(), indices.len());
+        for triangle in flat_vertices.chunks(3).filter(|triangle| triangle[0].position != triangle[1].position) {
+            // Every corner of a triangle shares its normal, which faces out of the sphere.
+            let normal = Vector3::from(triangle[0].normal);
+            assert!(triangle.iter().all(|v| (Vector3::from(v.normal) - normal).norm() < 1.0e-5));
+            assert!(normal.dot(&Vector3::from(triangle[0].position)) > 0.0);
+        }
+
+        // Smoothing the flat sphere brings its normals back close to pointing out of the center.
+        let (smooth_vertices, smooth_indices) = smooth_shaded(&flat_vertices, &flat_indices);
+        assert_eq!(smooth_indices, flat_indices);
+        for v in smooth_vertices.iter().filter(|v| v.position[1].abs() < 0.99) {
+            let (position, normal) = (Vector3::from(v.position), Vector3::from(v.normal));
+            assert!((normal.norm() - 1.0).abs() < 1.0e-5);
+            assert!(normal.dot(&position) > 0.95);
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use std::f32::consts::PI;
+use nalgebra::base::Vector3;
+use quaternion::{self, Quaternion};
+use shapes;
+
+/// The values shown on the rotation sliders. They describe the selected rotation only, and are
+/// rebuilt from it whenever a different rotation is selected, so no entry inherits another's angles.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RotationSliders {
+    /// Yaw, pitch and roll in radians, within 0 to 2π or the range of the sliders they were set on.
+    pub euler_angles: [f32; 3],
+    pub axis: Vector3<f32>,
+    /// The axis's direction in spherical coordinates, in radians, as in `shapes::point_on_sphere`.
+    /// Kept in step with `axis` by `set_axis_component` and `set_axis_spherical`.
+    pub axis_azimuth: f32,
+    pub axis_elevation: f32,
+    pub axis_angle: f32,
+}
+
+impl RotationSliders {
+    /// Returns the slider values that rebuild `q`, so the next drag carries on from it instead of jumping.
+    pub fn from_rotation(q: Quaternion) -> RotationSliders {
+        let wrap = |angle: f32| if angle < 0.0 { angle + 2.0 * PI } else { angle };
+        let (yaw, pitch, roll) = q.to_euler_angles();
+        let (axis, axis_angle) = q.to_axis_angle();
+        let mut sliders = RotationSliders {
+            euler_angles: [wrap(yaw), wrap(pitch), wrap(roll)],
+            axis,
+            axis_azimuth: 0.0,
+            axis_elevation: 0.0,
+            axis_angle,
+        };
+        sliders.update_axis_spherical();
+        sliders
+    }
+
+    /// Sets one of the axis's Cartesian components, as on its slider.
+    pub fn set_axis_component(&mut self, index: usize, value: f32) {
+        self.axis[index] = value;
+        self.update_axis_spherical();
+    }
+
+    /// Points the axis by its spherical coordinates. It's always a unit vector this way.
+    pub fn set_axis_spherical(&mut self, azimuth: f32, elevation: f32) {
+        self.axis_azimuth = azimuth;
+        self.axis_elevation = elevation;
+        self.axis = shapes::point_on_sphere(azimuth, elevation);
+    }
+
+    /// Straight up or down, or with no direction at all, the axis has no azimuth, so the last
+    /// one is kept for the azimuth slider not to jump.
+    fn update_axis_spherical(&mut self) {
+        const MIN_HORIZONTAL: f32 = 1.0e-6;
+        let n = quaternion::normalize_axis(self.axis);
+        if n[0].hypot(n[2]) > MIN_HORIZONTAL {
+            self.axis_azimuth = n[0].atan2(n[2]);
+        }
+        self.axis_elevation = n[1].max(-1.0).min(1.0).asin();
+    }
+
+    pub fn euler_rotation(&self) -> Quaternion {
+        Quaternion::from_euler_angles(self.euler_angles[0], self.euler_angles[1], self.euler_angles[2])
+    }
+
+    pub fn axis_angle_rotation(&self) -> Quaternion {
+        let n = quaternion::normalize_axis(self.axis);
+        Quaternion::from_axis_angle(n[0], n[1], n[2], self.axis_angle)
+    }
+}
+
+/// Rounds an angle in degrees to the nearest multiple of `step`, keeping it within the
+/// sliders' `range`.
+pub fn snap_degrees(degrees: f32, step: f32, range: AngleRange) -> f32 {
+    let (min, max) = range.bounds();
+    ((degrees / step).round() * step).max(min).min(max)
+}
+
+/// The range that angles are shown in, on the sliders and readouts.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AngleRange {
+    /// From 0 up to 360°.
+    Unsigned,
+    /// From -180° up to 180°, so small turns either way show as small angles.
+    Signed,
+}
+
+impl AngleRange {
+    /// The ends of the sliders, in degrees.
+    pub fn bounds(self) -> (f32, f32) {
+        match self {
+            AngleRange::Unsigned => (0.0, 360.0),
+            AngleRange::Signed => (-180.0, 180.0),
+        }
+    }
+
+    /// Wraps an angle in degrees into the range: [0, 360) or (-180, 180].
+    pub fn wrap(self, degrees: f32) -> f32 {
+        let unsigned = |degrees: f32| {
+            let wrapped = degrees - 360.0 * (degrees / 360.0).floor();
+            // Just below zero would round up to 360.
+            if wrapped >= 360.0 { 0.0 } else { wrapped }
+        };
+        match self {
+            AngleRange::Unsigned => unsigned(degrees),
+            AngleRange::Signed => 180.0 - unsigned(180.0 - degrees),
+        }
+    }
+
+    /// Returns an angle in degrees to show on a slider. Angles already between its ends are left
+    /// as they are, so that one dragged to the end of its slider stays there, and the rest are
+    /// wrapped.
+    pub fn fit(self, degrees: f32) -> f32 {
+        let (min, max) = self.bounds();
+        if degrees >= min && degrees <= max { degrees } else { self.wrap(degrees) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_same_rotation(a: Quaternion, b: Quaternion) {
+        // q and -q are the same rotation.
+        let dot = a.x*b.x + a.y*b.y + a.z*b.z + a.w*b.w;
+        assert!((dot.abs() - 1.0).abs() < 1.0e-5, "Not the same rotation: {:?} {:?}", a, b);
+    }
+
+    #[test]
+    fn test_from_rotation() {
+        let q = Quaternion::from_euler_angles(0.3, -0.4, 2.5);
+        let sliders = RotationSliders::from_rotation(q);
+        assert!(sliders.euler_angles.iter().all(|&angle| angle >= 0.0 && angle < 2.0 * PI));
+        assert_same_rotation(sliders.euler_rotation(), q);
+        assert_same_rotation(sliders.axis_angle_rotation(), q);
+    }
+
+    #[test]
+    fn test_axis_spherical() {
+        let mut sliders = RotationSliders::from_rotation(Quaternion::from_axis_angle(1.0, 0.0, 0.0, 0.5));
+        assert!((sliders.axis_azimuth - PI / 2.0).abs() < 1.0e-6 && sliders.axis_elevation.abs() < 1.0e-6);
+
+        sliders.set_axis_spherical(PI / 2.0, PI / 4.0);
+        assert!((sliders.axis - Vector3::new(0.5f32.sqrt(), 0.5f32.sqrt(), 0.0)).norm() < 1.0e-6);
+        assert_same_rotation(sliders.axis_angle_rotation(), Quaternion::from_axis_angle(0.5f32.sqrt(), 0.5f32.sqrt(), 0.0, 0.5));
+
+        // Moving the axis onto Y keeps the azimuth for when it's moved off again.
+        sliders.set_axis_component(0, 0.0);
+        assert!((sliders.axis_azimuth - PI / 2.0).abs() < 1.0e-6);
+        assert!((sliders.axis_elevation - PI / 2.0).abs() < 1.0e-6);
+        sliders.set_axis_component(2, -(0.5f32.sqrt()));
+        assert!((sliders.axis_azimuth - PI).abs() < 1.0e-6);
+        assert!((sliders.axis_elevation - PI / 4.0).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn test_snap_degrees() {
+        let assert_snaps_to = |degrees: f32, step: f32, expected: f32| {
+            let snapped = snap_degrees(degrees, step, AngleRange::Unsigned);
+            assert!((snapped - expected).abs() < 1.0e-4, "{} snapped to {}, not {}", degrees, snapped, expected);
+        };
+        assert_snaps_to(7.4, 15.0, 0.0);
+        assert_snaps_to(7.6, 15.0, 15.0);
+        assert_snaps_to(92.0, 5.0, 90.0);
+        assert_snaps_to(359.0, 5.0, 360.0);
+        // A step that doesn't divide 360 mustn't snap past the end of the slider.
+        assert_snaps_to(359.0, 100.0, 360.0);
+
+        assert!((snap_degrees(-179.0, 100.0, AngleRange::Signed) + 180.0).abs() < 1.0e-4);
+        assert!((snap_degrees(-20.0, 15.0, AngleRange::Signed) + 15.0).abs() < 1.0e-4);
+    }
+
+    #[test]
+    fn test_angle_range() {
+        let assert_wraps_to = |range: AngleRange, degrees: f32, expected: f32| {
+            let wrapped = range.wrap(degrees);
+            assert!((wrapped - expected).abs() < 1.0e-4, "{} wrapped to {}, not {}", degrees, wrapped, expected);
+        };
+        assert_wraps_to(AngleRange::Unsigned, 0.0, 0.0);
+        assert_wraps_to(AngleRange::Unsigned, 180.0, 180.0);
+        assert_wraps_to(AngleRange::Unsigned, 360.0, 0.0);
+        assert_wraps_to(AngleRange::Unsigned, -180.0, 180.0);
+        assert_wraps_to(AngleRange::Unsigned, 370.0, 10.0);
+        assert_wraps_to(AngleRange::Unsigned, -1.0e-6, 0.0);
+
+        assert_wraps_to(AngleRange::Signed, 0.0, 0.0);
+        assert_wraps_to(AngleRange::Signed, 180.0, 180.0);
+        assert_wraps_to(AngleRange::Signed, 360.0, 0.0);
+        assert_wraps_to(AngleRange::Signed, -180.0, 180.0);
+        assert_wraps_to(AngleRange::Signed, 190.0, -170.0);
+        assert_wraps_to(AngleRange::Signed, -540.0, 180.0);
+
+        // The ends of the sliders stay put.
+        assert!((AngleRange::Unsigned.fit(360.0) - 360.0).abs() < 1.0e-6);
+        assert!((AngleRange::Signed.fit(-180.0) + 180.0).abs() < 1.0e-6);
+        assert!((AngleRange::Signed.fit(270.0) + 90.0).abs() < 1.0e-4);
+    }
+
+    #[test]
+    fn test_new_rotation_starts_from_identity() {
+        // Editing one rotation and then adding another used to leave the first one's angles on
+        // the sliders, so the first drag copied them into the new entry.
+        let edited = RotationSliders::from_rotation(Quaternion::from_euler_angles(1.0, 0.5, 0.25));
+        assert_ne!(edited, RotationSliders::from_rotation(Quaternion::identity()));
+
+        let added = RotationSliders::from_rotation(Quaternion::identity());
+        assert_eq!(added.euler_angles, [0.0, 0.0, 0.0]);
+        assert!(added.axis_angle.abs() < 1.0e-6);
+        assert_eq!(added.euler_rotation(), Quaternion::identity());
+    }
+}
@@ -0,0 +1,27 @@
+use conrod::{self, color};
+
+/// The UI's color scheme, chosen in the panel and saved in the config file.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    Dark,
+    Light,
+}
+
+impl Theme {
+    /// Returns the conrod theme for the widgets. Widgets given their own colors, like the red
+    /// slider labels, keep them in either theme.
+    pub fn conrod_theme(self) -> conrod::Theme {
+        let (background_color, shape_color, border_color, label_color) = match self {
+            Theme::Dark => (color::CHARCOAL, color::DARK_GRAY, color::BLACK, color::WHITE),
+            Theme::Light => (color::LIGHT_GRAY, color::WHITE, color::DARK_GRAY, color::BLACK),
+        };
+        conrod::Theme {
+            background_color,
+            shape_color,
+            border_color,
+            label_color,
+            ..conrod::Theme::default()
+        }
+    }
+}
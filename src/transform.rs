@@ -0,0 +1,73 @@
+use nalgebra::base::Vector3;
+use nalgebra::core::Matrix4;
+use quaternion::Quaternion;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Transform {
+    pub position: Vector3<f32>,
+    pub rotation: Quaternion,
+    pub scale: Vector3<f32>,
+}
+
+impl Transform {
+    pub fn new() -> Transform {
+        Transform {
+            position: [0.0, 0.0, 0.0].into(),
+            rotation: Quaternion::identity(),
+            scale: [1.0, 1.0, 1.0].into(),
+        }
+    }
+
+    /// Returns the model matrix `position * rotation * scale`, which scales a point first, then
+    /// rotates it, and then moves it to `position`.
+    pub fn to_matrix(&self) -> Matrix4<f32> {
+        let mut pos = Matrix4::identity();
+        pos[(0,3)] = self.position[0];
+        pos[(1,3)] = self.position[1];
+        pos[(2,3)] = self.position[2];
+        
+        let rot = self.rotation.into_matrix();
+
+        let mut scale = Matrix4::identity();
+        scale[(0,0)] = self.scale[0];
+        scale[(1,1)] = self.scale[1];
+        scale[(2,2)] = self.scale[2];
+
+        pos * rot * scale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::base::Vector4;
+    use std::f32::consts::PI;
+
+    fn transform_point(matrix: Matrix4<f32>, point: Vector3<f32>) -> Vector3<f32> {
+        let transformed = matrix * Vector4::new(point[0], point[1], point[2], 1.0);
+        Vector3::new(transformed[0], transformed[1], transformed[2])
+    }
+
+    #[test]
+    fn test_to_matrix() {
+        let mut transform = Transform::new();
+        assert_eq!(transform.to_matrix(), Matrix4::identity());
+
+        transform.position = Vector3::new(1.0, -2.0, 3.0);
+        let matrix = transform.to_matrix();
+        let mut expected = Matrix4::identity();
+        expected.set_column(3, &Vector4::new(1.0, -2.0, 3.0, 1.0));
+        assert_eq!(matrix, expected);
+
+        let mut transform = Transform::new();
+        transform.rotation = Quaternion::from_euler_angles(0.4, -1.1, 2.3);
+        assert_eq!(transform.to_matrix(), transform.rotation.into_matrix());
+
+        // Scaled along X, then turned a quarter about Z onto Y, then moved.
+        transform.rotation = Quaternion::from_axis_angle(0.0, 0.0, 1.0, PI / 2.0);
+        transform.scale = Vector3::new(2.0, 1.0, 1.0);
+        transform.position = Vector3::new(0.0, 0.0, 5.0);
+        let point = transform_point(transform.to_matrix(), Vector3::x());
+        assert!((point - Vector3::new(0.0, 2.0, 5.0)).norm() < 1.0e-5, "{:?}", point);
+    }
+}